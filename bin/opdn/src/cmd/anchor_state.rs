@@ -0,0 +1,198 @@
+//! Contains logic to generate anchor state fixtures from an on-chain anchor state registry.
+
+use crate::cmd::{
+    envelope,
+    exit::ExitCode,
+    output::{OutputArgs, TemplateValues},
+    parse::{parse_address, parse_block_number_or_tag},
+    plan::Plan,
+    rpc::{block_number, request},
+};
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use clap::{ArgAction, Parser};
+use color_eyre::{
+    eyre::{bail, eyre},
+    Result,
+};
+use op_test_vectors::anchor_state::{
+    AnchorStateFixture, OutputRoot, CANNON_GAME_TYPE, PERMISSIONED_CANNON_GAME_TYPE,
+};
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use tracing::{info, trace};
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "anchor-state";
+
+/// The signature of the anchor state registry's `anchors` getter.
+const ANCHORS_SIGNATURE: &str = "anchors(uint32)";
+
+/// CLI arguments for the `anchor-state` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct AnchorState {
+    /// The L2 chain ID the anchor state registry belongs to.
+    #[clap(long, help = "L2 chain ID")]
+    pub l2_chain_id: u64,
+    /// The address of the anchor state registry on L1.
+    #[clap(
+        long,
+        value_parser = parse_address,
+        help = "Address of the anchor state registry on L1"
+    )]
+    pub anchor_state_registry: Address,
+    /// The dispute game types to read anchors for.
+    #[clap(
+        long,
+        help = "Comma-separated dispute game types to read anchors for",
+        value_delimiter = ',',
+        default_values_t = [CANNON_GAME_TYPE, PERMISSIONED_CANNON_GAME_TYPE]
+    )]
+    pub game_types: Vec<u32>,
+    /// The L1 block to read the registry at.
+    #[clap(
+        long,
+        value_parser = parse_block_number_or_tag,
+        default_value = "latest",
+        help = "L1 block number or tag (latest, finalized, safe) to read the registry at"
+    )]
+    pub l1_block: BlockNumberOrTag,
+    /// An L1 RPC URL to read the registry from.
+    #[clap(long, help = "RPC url to read the anchor state registry from")]
+    pub l1_rpc_url: String,
+    /// A description of the scenario, recorded in the fixture.
+    #[clap(long, help = "Description of the scenario recorded in the fixture")]
+    pub description: Option<String>,
+    /// Resolve and check every input without reading the anchors.
+    #[clap(long, help = "Resolve and check all inputs, print the plan and exit")]
+    pub dry_run: bool,
+    /// The output location for the test fixture.
+    #[command(flatten)]
+    pub output: OutputArgs,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+impl AnchorState {
+    /// Runs the anchor state fixture generation.
+    pub async fn run(&self) -> Result<()> {
+        if self.game_types.is_empty() {
+            return Err(ExitCode::Usage.classify(eyre!("No game types provided")));
+        }
+        self.output.precheck()?;
+        let url = Url::parse(&self.l1_rpc_url).map_err(|e| eyre!("Invalid RPC URL: {}", e))?;
+        let client = reqwest::Client::new();
+
+        // Pin every read to the same L1 block so the anchors are consistent.
+        let l1_block_number = block_number(&client, &url, self.l1_block).await?;
+        let block_tag = format!("{:#x}", l1_block_number);
+        let block: Value = request(
+            &client,
+            &url,
+            "eth_getBlockByNumber",
+            json!([block_tag, false]),
+        )
+        .await?;
+        let l1_block_hash: B256 = serde_json::from_value(block["hash"].clone())
+            .map_err(|e| eyre!("Missing hash for L1 block {l1_block_number}: {e}"))?;
+        trace!(target: TARGET, "Reading anchors at L1 block {} ({})", l1_block_number, l1_block_hash);
+
+        if self.dry_run {
+            let code: Bytes = request(
+                &client,
+                &url,
+                "eth_getCode",
+                json!([self.anchor_state_registry, block_tag]),
+            )
+            .await?;
+            if code.is_empty() {
+                return Err(ExitCode::Environment.classify(eyre!(
+                    "No contract at {} in L1 block {}",
+                    self.anchor_state_registry,
+                    l1_block_number
+                )));
+            }
+            let values = TemplateValues {
+                chain: self.l2_chain_id,
+                fixture_type: "anchor-state",
+                l1_start: l1_block_number,
+                l1_end: l1_block_number,
+                ..Default::default()
+            };
+            let game_types = self
+                .game_types
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>();
+            let plan = Plan::new("anchor-state")
+                .with("L2 chain", self.l2_chain_id)
+                .with("Registry", self.anchor_state_registry)
+                .with("L1 block", format!("{l1_block_number} ({l1_block_hash})"))
+                .with("Game types", game_types.join(", "))
+                .with("Output", self.output.resolve(&values)?.display());
+            envelope::human(plan);
+            return Ok(());
+        }
+
+        let mut anchors = BTreeMap::new();
+        for &game_type in &self.game_types {
+            let mut calldata = keccak256(ANCHORS_SIGNATURE)[..4].to_vec();
+            calldata.extend_from_slice(&B256::left_padding_from(&game_type.to_be_bytes())[..]);
+            let call = json!({
+                "to": self.anchor_state_registry,
+                "data": Bytes::from(calldata),
+            });
+            let output: Bytes =
+                request(&client, &url, "eth_call", json!([call, block_tag])).await?;
+            let anchor = decode_output_root(&output)
+                .map_err(|e| eyre!("Invalid anchor for game type {game_type}: {e}"))?;
+            info!(target: TARGET,
+                "Game type {} anchored at L2 block {} with root {}",
+                game_type, anchor.l2_block_number, anchor.root
+            );
+            anchors.insert(game_type, anchor);
+        }
+
+        let fixture = AnchorStateFixture {
+            description: self.description.clone(),
+            deprecated: false,
+            superseded_by: None,
+            l2_chain_id: self.l2_chain_id,
+            anchor_state_registry: self.anchor_state_registry,
+            l1_block_number,
+            l1_block_hash,
+            anchors,
+        };
+
+        let l2_numbers = fixture.anchors.values().map(|a| a.l2_block_number);
+        let values = TemplateValues {
+            chain: self.l2_chain_id,
+            fixture_type: "anchor-state",
+            l1_start: l1_block_number,
+            l1_end: l1_block_number,
+            l2_start: l2_numbers.clone().min().unwrap_or_default(),
+            l2_end: l2_numbers.max().unwrap_or_default(),
+        };
+        let path = self.output.write(&values, &fixture)?;
+        envelope::metric("anchors", fixture.anchors.len());
+        info!(target: TARGET, "Wrote anchor state fixture to: {:?}", path);
+
+        Ok(())
+    }
+}
+
+/// Decodes the ABI-encoded `(bytes32 root, uint256 l2BlockNumber)` returned by `anchors`.
+fn decode_output_root(output: &[u8]) -> Result<OutputRoot> {
+    if output.len() != 64 {
+        bail!("Expected 64 bytes of return data, got {}", output.len());
+    }
+    let l2_block_number = U256::from_be_slice(&output[32..]);
+    Ok(OutputRoot {
+        root: B256::from_slice(&output[..32]),
+        l2_block_number: l2_block_number
+            .try_into()
+            .map_err(|_| eyre!("L2 block number out of range: {l2_block_number}"))?,
+    })
+}
@@ -1,15 +1,129 @@
 //! Blob Loading Module
 
 use alloy_consensus::{Transaction, TxEip4844Variant, TxEnvelope, TxType};
+use alloy_eips::{
+    eip2718::Decodable2718,
+    eip4844::{env_settings::EnvKzgSettings, kzg_to_versioned_hash},
+};
 use alloy_primitives::{Address, TxKind};
-use color_eyre::Result;
+use c_kzg::KzgCommitment;
+use color_eyre::{eyre::eyre, Result};
 use tracing::warn;
 
+use crate::cmd::exit::{ExitCode, WithExitCode};
 use kona_derive::online::{
-    OnlineBeaconClient, OnlineBlobProviderWithFallback, SimpleSlotDerivation,
+    BeaconClient, OnlineBeaconClient, OnlineBlobProviderWithFallback, SimpleSlotDerivation,
 };
 use kona_derive::traits::BlobProvider;
-use kona_derive::types::{Blob, BlockInfo, IndexedBlobHash};
+use kona_derive::types::{Blob, BlockInfo, IndexedBlobHash, RollupConfig};
+use op_test_vectors::derivation::{BeaconConfig, FixtureBlock};
+use std::borrow::Borrow;
+
+/// Fetches the beacon chain config used to map L1 block timestamps to blob slots.
+pub async fn beacon_config(beacon_url: &str) -> Result<BeaconConfig> {
+    let client = OnlineBeaconClient::new_http(beacon_url.to_string());
+    let genesis = client
+        .beacon_genesis()
+        .await
+        .map_err(|e| eyre!("Failed to fetch beacon genesis: {e}"))?;
+    let spec = client
+        .config_spec()
+        .await
+        .map_err(|e| eyre!("Failed to fetch beacon config spec: {e}"))?;
+    Ok(BeaconConfig {
+        genesis_time: genesis.data.genesis_time,
+        seconds_per_slot: spec.data.seconds_per_slot,
+    })
+}
+
+/// Fails with a usage error if `block` is past Ecotone, from which batches may be posted
+/// as blobs that only a beacon client can serve.
+pub fn ensure_pre_ecotone(cfg: &RollupConfig, block: &BlockInfo) -> Result<()> {
+    if cfg.is_ecotone_active(block.timestamp) {
+        return Err(ExitCode::Usage.classify(eyre!(
+            "Block {} is past Ecotone, pass --beacon-url to fetch its blobs",
+            block.number
+        )));
+    }
+    Ok(())
+}
+
+/// Loads the blobs batched in an L1 block if it is past Ecotone, since batches are posted
+/// as calldata before it.
+///
+/// `provider` may be omitted for pre-Ecotone blocks.
+pub async fn load_from_ecotone(
+    cfg: &RollupConfig,
+    b: &BlockInfo,
+    txs: &[TxEnvelope],
+    provider: Option<
+        &mut OnlineBlobProviderWithFallback<
+            OnlineBeaconClient,
+            OnlineBeaconClient,
+            SimpleSlotDerivation,
+        >,
+    >,
+) -> Result<Vec<Box<Blob>>> {
+    match provider {
+        Some(provider) if cfg.is_ecotone_active(b.timestamp) => {
+            load(b, txs, cfg.batch_inbox_address, batcher(cfg), provider).await
+        }
+        Some(_) => Ok(Vec::new()),
+        None => ensure_pre_ecotone(cfg, b).map(|_| Vec::new()),
+    }
+}
+
+/// Returns the genesis batcher, whose blob transactions to the batch inbox carry batches.
+fn batcher(cfg: &RollupConfig) -> Address {
+    cfg.genesis
+        .system_config
+        .as_ref()
+        .map(|sc| sc.batcher_address)
+        .unwrap_or_default()
+}
+
+/// Checks that the blobs stored in a fixture block are the ones its batcher transactions
+/// commit to, selecting them the same way they were when the block was fetched.
+pub fn verify_fixture_block(cfg: &RollupConfig, block: &FixtureBlock) -> Result<()> {
+    if !cfg.is_ecotone_active(block.header.timestamp) {
+        return Ok(());
+    }
+    let hashes = fixture_block_blob_hashes(cfg, block)?;
+    let info = BlockInfo {
+        hash: block.header.hash_slow(),
+        number: block.header.number,
+        parent_hash: block.header.parent_hash,
+        timestamp: block.header.timestamp,
+    };
+    verify(&info, &block.blobs, &hashes)
+}
+
+/// Returns the versioned hashes the batcher transactions of a fixture block commit to, in
+/// the order its blobs are stored, or none if the block is before Ecotone.
+pub fn fixture_block_blob_hashes(
+    cfg: &RollupConfig,
+    block: &FixtureBlock,
+) -> Result<Vec<IndexedBlobHash>> {
+    if !cfg.is_ecotone_active(block.header.timestamp) {
+        return Ok(Vec::new());
+    }
+    let txs = block
+        .transactions
+        .iter()
+        .map(|tx| TxEnvelope::decode_2718(&mut tx.as_ref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            eyre!(
+                "Invalid transaction in L1 block {}: {e}",
+                block.header.number
+            )
+        })?;
+    Ok(extract_blob_data(
+        cfg.batch_inbox_address,
+        batcher(cfg),
+        &txs,
+    ))
+}
 
 /// Loads blobs for the given block number.
 pub async fn load(
@@ -30,19 +144,59 @@ pub async fn load(
         return Ok(vec![]);
     }
 
-    provider
-        .get_blobs(b, &blob_hashes)
-        .await
-        .map_err(|e| {
-            warn!(target: "blobs", "Failed to fetch blobs: {e}");
-            color_eyre::eyre::eyre!("Failed to fetch blobs: {e}")
-        })
-        .map(|blobs| {
-            blobs
-                .into_iter()
-                .map(|b| Box::new(b) as Box<Blob>)
-                .collect()
-        })
+    let blobs = provider.get_blobs(b, &blob_hashes).await.map_err(|e| {
+        warn!(target: "blobs", "Failed to fetch blobs: {e}");
+        color_eyre::eyre::eyre!("Failed to fetch blobs: {e}")
+    })?;
+    verify(b, &blobs, &blob_hashes)?;
+    Ok(blobs
+        .into_iter()
+        .map(|b| Box::new(b) as Box<Blob>)
+        .collect())
+}
+
+/// Checks that each blob commits to the versioned hash its transaction carries, so a
+/// faulty beacon node cannot put the wrong blob data in a fixture.
+///
+/// The KZG commitment is recomputed from the blob with the mainnet trusted setup,
+/// rather than taken from the sidecar, so the check does not trust the beacon node.
+pub fn verify(
+    b: &BlockInfo,
+    blobs: &[impl Borrow<Blob>],
+    hashes: &[IndexedBlobHash],
+) -> Result<()> {
+    if blobs.len() != hashes.len() {
+        return Err(ExitCode::Environment.classify(eyre!(
+            "Got {} blobs for L1 block {}, expected {}",
+            blobs.len(),
+            b.number,
+            hashes.len()
+        )));
+    }
+    let settings = EnvKzgSettings::Default.get();
+    for (blob, hash) in blobs.iter().zip(hashes) {
+        let commitment = c_kzg::Blob::from_bytes(blob.borrow().as_slice())
+            .and_then(|blob| KzgCommitment::blob_to_kzg_commitment(&blob, settings))
+            .map_err(|e| {
+                eyre!(
+                    "Invalid blob {} in L1 block {}: {e:?}",
+                    hash.index,
+                    b.number
+                )
+            })
+            .exit_code(ExitCode::Environment)?;
+        let versioned_hash = kzg_to_versioned_hash(commitment.as_slice());
+        if versioned_hash != hash.hash {
+            return Err(ExitCode::Environment.classify(eyre!(
+                "Blob {} in L1 block {} commits to {}, but its transaction expects {}",
+                hash.index,
+                b.number,
+                versioned_hash,
+                hash.hash
+            )));
+        }
+    }
+    Ok(())
 }
 
 fn extract_blob_data(
@@ -0,0 +1,75 @@
+//! Contains logic to convert derivation fixtures between file formats.
+
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    output::{write_fixture, OutputArgs, TemplateValues},
+};
+use clap::{ArgAction, Parser};
+use color_eyre::Result;
+use op_test_vectors::{
+    derivation::DerivationFixture,
+    format::{load_with, Format, LoadMode},
+};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "convert";
+
+/// CLI arguments for the `convert` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct Convert {
+    /// The fixture to convert, in JSON or YAML.
+    #[clap(long, help = "Input fixture file (.json, .yaml or .yml)")]
+    pub input: PathBuf,
+    /// The format to convert the fixture to.
+    ///
+    /// Defaults to the format implied by the output file extension.
+    #[clap(
+        long,
+        help = "Output format (json or yaml), defaults to the output extension"
+    )]
+    pub to: Option<Format>,
+    /// Reject fixtures containing fields that are not part of the schema.
+    #[clap(long, help = "Fail on unknown fixture fields instead of warning")]
+    pub strict: bool,
+    /// The output location for the converted fixture.
+    #[command(flatten)]
+    pub output: OutputArgs,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+impl Convert {
+    /// Runs the conversion.
+    ///
+    /// The fixture is parsed into a [DerivationFixture] before being written, so the
+    /// output is always a canonical, schema-valid fixture.
+    pub async fn run(&self) -> Result<()> {
+        self.output.precheck()?;
+        let mode = if self.strict {
+            LoadMode::Strict
+        } else {
+            LoadMode::Lenient
+        };
+        let (fixture, unknown): (DerivationFixture, _) =
+            load_with(&self.input, mode).exit_code(ExitCode::FixtureInvalid)?;
+        for field in &unknown {
+            warn!(target: TARGET, "Ignoring unknown field in {:?}: {}", self.input, field);
+        }
+
+        let values = TemplateValues::from_derivation_fixture(&fixture);
+        let (path, _lock) = self.output.lock(&values)?;
+        let format = self.to.unwrap_or_else(|| Format::from_path(&path));
+        write_fixture(&path, &fixture, format, self.output.allow_secrets)?;
+        envelope::artifact(&path);
+        values.record();
+        envelope::metric("unknownFields", unknown.len());
+        info!(target: TARGET, "Wrote {} fixture to: {:?}", format, path);
+        self.output.post_process(&path, &values)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//! Contains the `dedupe` subcommand, which finds derivation fixtures that capture the same
+//! inputs under different names, formatting or metadata.
+
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    lock::PathLock,
+    output::write_fixture,
+    util::fixture_files,
+};
+use clap::{ArgAction, Parser};
+use color_eyre::{eyre::eyre, Result};
+use op_test_vectors::{
+    derivation::DerivationFixture,
+    format::{load, Format},
+};
+use std::{collections::BTreeMap, path::PathBuf};
+use tracing::{debug, info};
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "dedupe";
+
+/// CLI arguments for the `dedupe` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct Dedupe {
+    /// The directory of fixtures to search for duplicates.
+    #[clap(long, help = "Directory of fixtures to search for duplicates")]
+    pub fixtures_dir: PathBuf,
+    /// Remove all but one fixture of each duplicate group.
+    #[clap(
+        long,
+        help = "Remove all but one fixture of each duplicate group, keeping their metadata"
+    )]
+    pub remove: bool,
+    /// Write fixtures that appear to contain secrets, warning about them instead.
+    #[clap(
+        long,
+        help = "Write fixtures even if they appear to contain secrets, warning instead"
+    )]
+    pub allow_secrets: bool,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+/// A derivation fixture loaded from the corpus.
+type Loaded = (PathBuf, DerivationFixture);
+
+impl Dedupe {
+    /// Runs the search, printing each group of duplicates.
+    ///
+    /// Fixtures are duplicates when they derive from the same inputs, see
+    /// [DerivationFixture::same_inputs]. Duplicates whose derived L2 data differs are
+    /// reported as conflicts, and fail the command rather than being removed. Deprecated
+    /// fixtures are left alone, since they are kept for consumers that pin their names.
+    pub async fn run(&self) -> Result<()> {
+        let files = fixture_files(&self.fixtures_dir).exit_code(ExitCode::Environment)?;
+
+        // Bucket by chain and range first, so only likely duplicates are compared in full.
+        let mut buckets: BTreeMap<(u64, u64, u64), Vec<Loaded>> = BTreeMap::new();
+        for path in files {
+            match load::<DerivationFixture>(&path) {
+                Ok(fixture) if fixture.deprecated => {
+                    debug!(target: TARGET, "Skipping deprecated {:?}", path)
+                }
+                Ok(fixture) => {
+                    let key = (
+                        fixture.rollup_config.l2_chain_id,
+                        fixture.l2_cursor_start,
+                        fixture.l2_cursor_end,
+                    );
+                    buckets.entry(key).or_default().push((path, fixture));
+                }
+                Err(e) => debug!(target: TARGET, "Skipping {:?}: {e}", path),
+            }
+        }
+
+        let (mut groups, mut redundant, mut conflicts) = (0, 0, 0);
+        for bucket in buckets.into_values() {
+            for group in group_duplicates(bucket) {
+                let (keep, rest) = group.split_first().expect("groups have two fixtures");
+                if let Some((path, _)) = rest.iter().find(|(_, f)| !keep.1.same_outputs(f)) {
+                    conflicts += 1;
+                    envelope::human(format_args!(
+                        "[conflict] {} and {} share inputs but differ in derived data",
+                        keep.0.display(),
+                        path.display()
+                    ));
+                    continue;
+                }
+                let names: Vec<_> = rest.iter().map(|(p, _)| p.display().to_string()).collect();
+                envelope::human(format_args!(
+                    "[dup]  {} duplicated by {}",
+                    keep.0.display(),
+                    names.join(", ")
+                ));
+                if self.remove && !remove_duplicates(keep, rest, self.allow_secrets)? {
+                    continue;
+                }
+                groups += 1;
+                redundant += rest.len();
+            }
+        }
+
+        let action = if self.remove { "removed" } else { "found" };
+        envelope::human(format_args!(
+            "{groups} duplicate groups, {redundant} redundant fixtures {action}"
+        ));
+        envelope::metric("duplicateGroups", groups);
+        envelope::metric("redundant", redundant);
+        envelope::metric("conflicts", conflicts);
+        if conflicts > 0 {
+            return Err(ExitCode::FixtureInvalid.classify(eyre!(
+                "{conflicts} fixture pairs share inputs but differ in derived data"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Groups fixtures with the same inputs, returning only groups of two or more.
+fn group_duplicates(fixtures: Vec<Loaded>) -> Vec<Vec<Loaded>> {
+    let mut groups: Vec<Vec<Loaded>> = Vec::new();
+    for loaded in fixtures {
+        match groups.iter_mut().find(|g| g[0].1.same_inputs(&loaded.1)) {
+            Some(group) => group.push(loaded),
+            None => groups.push(vec![loaded]),
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// Removes the duplicates of a kept fixture, first copying any metadata it lacks from
+/// them, and returns whether it did.
+///
+/// Every fixture of the group is locked and reloaded first, so metadata written since
+/// the search is kept, and a group that no longer holds duplicates is skipped rather than
+/// having a fixture removed.
+fn remove_duplicates(keep: &Loaded, duplicates: &[Loaded], allow_secrets: bool) -> Result<bool> {
+    let path = &keep.0;
+    let _lock = PathLock::acquire(path)?;
+    let fixture = load::<DerivationFixture>(path).exit_code(ExitCode::FixtureInvalid)?;
+    let mut locked = Vec::with_capacity(duplicates.len());
+    for (duplicate, _) in duplicates {
+        let lock = PathLock::acquire(duplicate)?;
+        let reloaded = load::<DerivationFixture>(duplicate).exit_code(ExitCode::FixtureInvalid)?;
+        if fixture.deprecated
+            || reloaded.deprecated
+            || !fixture.same_inputs(&reloaded)
+            || !fixture.same_outputs(&reloaded)
+        {
+            envelope::human(format_args!(
+                "[skip] {} or {} changed since the search",
+                path.display(),
+                duplicate.display()
+            ));
+            return Ok(false);
+        }
+        locked.push((duplicate, reloaded, lock));
+    }
+
+    let mut merged = fixture.clone();
+    for (_, duplicate, _) in &locked {
+        merged.adopt_metadata(duplicate);
+    }
+    if merged != fixture {
+        write_fixture(path, &merged, Format::from_path(path), allow_secrets)?;
+        envelope::artifact(path);
+        info!(target: TARGET, "Copied metadata from duplicates into {:?}", path);
+    }
+    for (duplicate, _, _lock) in locked {
+        std::fs::remove_file(duplicate)
+            .map_err(|e| eyre!("Failed to remove {:?}: {e}", duplicate))
+            .exit_code(ExitCode::Environment)?;
+        info!(target: TARGET, "Removed {:?}", duplicate);
+    }
+    Ok(true)
+}
@@ -0,0 +1,304 @@
+//! Contains the `doctor` subcommand, which diagnoses environment problems before generation.
+
+use crate::cmd::{
+    envelope,
+    exit::ExitCode,
+    rpc::{chain_id, request, sync_status},
+    util::{fixture_files, AnyFixture},
+};
+use clap::{ArgAction, Parser};
+use color_eyre::{
+    eyre::{ensure, eyre},
+    Result,
+};
+use op_test_vectors::format::{load_with, Deprecation, LoadMode};
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use superchain_registry::ROLLUP_CONFIGS;
+
+/// The raw `debug_` methods kona's online providers fetch L1 data with.
+const L1_DEBUG_METHODS: [&str; 2] = ["debug_getRawHeader", "debug_getRawReceipts"];
+
+/// The raw `debug_` methods kona's online providers fetch L2 data with.
+const L2_DEBUG_METHODS: [&str; 1] = ["debug_getRawBlock"];
+
+/// The remediation for a node that doesn't expose the `debug_` namespace.
+const DEBUG_FIX: &str = "enable the debug namespace on the node (e.g. `--http.api eth,debug`) \
+                         or use a provider that exposes it";
+
+/// CLI arguments for the `doctor` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct Doctor {
+    /// An L1 RPC URL to check.
+    #[clap(long, help = "L1 RPC url to check")]
+    pub l1_rpc_url: Option<String>,
+    /// An L2 RPC URL to check.
+    #[clap(long, help = "L2 RPC url to check")]
+    pub l2_rpc_url: Option<String>,
+    /// A rollup node RPC URL to check.
+    #[clap(long, help = "Rollup node RPC url to check")]
+    pub rollup_rpc_url: Option<String>,
+    /// A beacon client URL to check.
+    #[clap(long, help = "Beacon client url to check")]
+    pub beacon_url: Option<String>,
+    /// A directory that fixtures will be written to.
+    #[clap(long, help = "Directory fixtures will be written to")]
+    pub output_dir: Option<PathBuf>,
+    /// A directory of existing fixtures to check.
+    #[clap(long, help = "Directory of existing fixtures to check")]
+    pub fixtures_dir: Option<PathBuf>,
+    /// Check deprecated fixtures too, rather than only their supersession pointers.
+    #[clap(
+        long,
+        requires = "fixtures_dir",
+        help = "Also check deprecated fixtures"
+    )]
+    pub include_deprecated: bool,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+impl Doctor {
+    /// Runs every check that has an input, printing each outcome with a remediation for
+    /// failures.
+    pub async fn run(&self) -> Result<()> {
+        let mut report = Report::default();
+        let client = Client::new();
+
+        if let Some(url) = &self.l1_rpc_url {
+            check_rpc(&mut report, &client, "L1 RPC", url, &L1_DEBUG_METHODS).await;
+        }
+        if let Some(url) = &self.l2_rpc_url {
+            if let Some(l2_chain_id) =
+                check_rpc(&mut report, &client, "L2 RPC", url, &L2_DEBUG_METHODS).await
+            {
+                report.check(
+                    "L2 rollup config",
+                    "opdn only supports chains in the superchain registry",
+                    ROLLUP_CONFIGS
+                        .get(&l2_chain_id)
+                        .map(|cfg| format!("found, L1 chain {}", cfg.l1_chain_id))
+                        .ok_or_else(|| eyre!("chain {l2_chain_id} is not in the registry")),
+                );
+            }
+        }
+        if let Some(url) = &self.rollup_rpc_url {
+            let outcome = match parse_url(url) {
+                Ok(url) => sync_status(&client, &url).await.map(|status| {
+                    format!(
+                        "unsafe {}, safe {}, finalized {}",
+                        status.unsafe_l2.number, status.safe_l2.number, status.finalized_l2.number
+                    )
+                }),
+                Err(e) => Err(e),
+            };
+            report.check(
+                "Rollup RPC sync status",
+                "pass the op-node RPC url, which serves optimism_syncStatus",
+                outcome,
+            );
+        }
+        if let Some(url) = &self.beacon_url {
+            report.check(
+                "Beacon API",
+                "pass a beacon node url; blob sidecars need a node that hasn't pruned them",
+                crate::cmd::blobs::beacon_config(url).await.map(|config| {
+                    format!(
+                        "genesis time {}, {}s slots",
+                        config.genesis_time, config.seconds_per_slot
+                    )
+                }),
+            );
+        }
+        if let Some(dir) = &self.output_dir {
+            report.check(
+                "Output directory",
+                "create the directory or pick one you can write to",
+                check_writable(dir),
+            );
+        }
+        if let Some(dir) = &self.fixtures_dir {
+            check_fixtures(&mut report, dir, self.include_deprecated);
+        }
+
+        envelope::metric("checks", report.checks);
+        envelope::metric("failures", report.failures);
+        if report.checks == 0 {
+            return Err(ExitCode::Usage.classify(eyre!(
+                "Nothing to check, pass at least one url or directory"
+            )));
+        }
+        if report.failures > 0 {
+            // Fixture failures only decide the exit code when the environment is sound.
+            let code = if report.failures > report.fixture_failures {
+                ExitCode::Environment
+            } else {
+                ExitCode::FixtureInvalid
+            };
+            return Err(code.classify(eyre!(
+                "{} of {} checks failed",
+                report.failures,
+                report.checks
+            )));
+        }
+        envelope::human(format_args!("All {} checks passed", report.checks));
+        Ok(())
+    }
+}
+
+/// The running tally of check outcomes.
+#[derive(Debug, Default)]
+struct Report {
+    /// The number of checks run.
+    checks: usize,
+    /// The number of checks that failed.
+    failures: usize,
+    /// The number of failed checks that were of individual fixtures.
+    fixture_failures: usize,
+}
+
+impl Report {
+    /// Records and prints the outcome of a check, with the remediation if it failed.
+    fn check(&mut self, name: &str, fix: &str, outcome: Result<String>) {
+        self.checks += 1;
+        match outcome {
+            Ok(detail) => envelope::human(format_args!("[ok]   {name}: {detail}")),
+            Err(e) => {
+                self.failures += 1;
+                envelope::human(format_args!("[fail] {name}: {e}\n       fix: {fix}"));
+            }
+        }
+    }
+}
+
+/// Checks that an execution client is reachable and serves the given `debug_` methods,
+/// returning its chain ID if it is reachable.
+async fn check_rpc(
+    report: &mut Report,
+    client: &Client,
+    name: &str,
+    url: &str,
+    debug_methods: &[&str],
+) -> Option<u64> {
+    let url = match parse_url(url) {
+        Ok(url) => url,
+        Err(e) => {
+            report.check(name, "pass an http(s) url", Err(e));
+            return None;
+        }
+    };
+    let id = chain_id(client, &url).await;
+    let reachable = id.as_ref().ok().copied();
+    report.check(
+        name,
+        "check the url, and that the node is running and accepts http requests",
+        id.map(|id| format!("chain {id}")),
+    );
+    if reachable.is_some() {
+        for method in debug_methods {
+            let outcome = request::<Value>(client, &url, method, json!(["latest"]))
+                .await
+                .map(|_| "supported".to_string());
+            report.check(&format!("{name} {method}"), DEBUG_FIX, outcome);
+        }
+    }
+    reachable
+}
+
+/// Checks that a directory exists and a file can be created in it.
+fn check_writable(dir: &Path) -> Result<String> {
+    ensure!(dir.is_dir(), "{:?} is not a directory", dir);
+    let probe = dir.join(".opdn-doctor");
+    std::fs::write(&probe, b"").map_err(|e| eyre!("{:?} is not writable: {e}", dir))?;
+    std::fs::remove_file(&probe)?;
+    Ok(format!("{:?} is writable", dir))
+}
+
+/// Checks that every fixture in a directory loads as a known fixture type, and that
+/// derivation fixtures pass their consistency checks.
+///
+/// Deprecated fixtures are skipped unless `include_deprecated` is set, but the fixture
+/// each one is superseded by must exist either way.
+fn check_fixtures(report: &mut Report, dir: &Path, include_deprecated: bool) {
+    let fix = "regenerate the fixture, or run `opdn convert --strict` on it to find bad fields";
+    let files = match fixture_files(dir) {
+        Ok(files) => files,
+        Err(e) => {
+            report.check("Fixtures directory", "check the path", Err(e));
+            return;
+        }
+    };
+    let deprecations: Vec<_> = files
+        .iter()
+        .map(|file| load_with::<Deprecation>(file, LoadMode::Lenient).map(|(d, _)| d))
+        .collect();
+    let deprecated = deprecations
+        .iter()
+        .filter(|d| d.as_ref().is_ok_and(|d| d.deprecated))
+        .count();
+    report.check(
+        "Fixtures directory",
+        "check the path",
+        Ok(format!(
+            "{} fixture files in {:?}, {deprecated} deprecated",
+            files.len(),
+            dir
+        )),
+    );
+    for (file, deprecation) in files.iter().zip(deprecations) {
+        let name = format!("Fixture {}", file.display());
+        let deprecation = deprecation.unwrap_or_default();
+        let outcome = check_deprecation(file, &deprecation).and_then(|detail| match detail {
+            Some(detail) if !include_deprecated => Ok(format!("skipped, {detail}")),
+            Some(detail) => check_fixture(file).map(|kind| format!("{kind}, {detail}")),
+            None => check_fixture(file),
+        });
+        let failures = report.failures;
+        report.check(&name, fix, outcome);
+        report.fixture_failures += report.failures - failures;
+    }
+}
+
+/// Describes a deprecated fixture, or returns `None` if it is not deprecated.
+///
+/// Fails if the fixture it is superseded by does not exist, or if it names one without
+/// being deprecated.
+fn check_deprecation(path: &Path, deprecation: &Deprecation) -> Result<Option<String>> {
+    match (&deprecation.superseded_by, deprecation.deprecated) {
+        (None, false) => Ok(None),
+        (None, true) => Ok(Some("deprecated".to_string())),
+        (Some(superseded_by), false) => {
+            Err(eyre!("superseded by {superseded_by} but not deprecated"))
+        }
+        (Some(superseded_by), true) => {
+            let target = path.parent().unwrap_or(Path::new("")).join(superseded_by);
+            ensure!(
+                target.is_file(),
+                "superseded by {superseded_by}, which does not exist"
+            );
+            Ok(Some(format!("deprecated, superseded by {superseded_by}")))
+        }
+    }
+}
+
+/// Loads a fixture as each known fixture type in turn, describing the first that matches.
+fn check_fixture(path: &Path) -> Result<String> {
+    let (fixture, unknown) = AnyFixture::load(path)?;
+    if let AnyFixture::Derivation(fixture) = &fixture {
+        let violations = fixture.validate();
+        if let Some(first) = violations.first() {
+            return Err(eyre!("{} violations, first {first}", violations.len()));
+        }
+    }
+    Ok(match unknown.len() {
+        0 => fixture.kind().to_string(),
+        n => format!("{}, ignoring {n} unknown fields", fixture.kind()),
+    })
+}
+
+/// Parses a url, naming it in the error.
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url).map_err(|e| eyre!("Invalid url {url:?}: {e}"))
+}
@@ -0,0 +1,206 @@
+//! Contains the machine-readable result envelope printed with `--output-json`.
+//!
+//! Commands record metrics and the artifacts they write as they run, and warnings are
+//! collected from [tracing] at any verbosity. Once the command finishes, the result is
+//! printed to stdout as a single JSON object, and everything meant for humans, logs
+//! included, goes to stderr instead.
+
+use crate::cmd::exit::ExitCode;
+use color_eyre::{eyre::Report, Result};
+use op_test_vectors::derivation::DerivationFixture;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::Duration,
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Whether the envelope is printed, set once by `--output-json`.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The warnings, metrics and artifacts collected so far.
+static COLLECTED: Mutex<Collected> = Mutex::new(Collected::new());
+
+/// The parts of the envelope recorded while the command runs.
+#[derive(Debug)]
+struct Collected {
+    /// The warnings logged so far.
+    warnings: Vec<String>,
+    /// The metrics recorded so far, by name.
+    metrics: BTreeMap<String, Value>,
+    /// The files written so far.
+    artifacts: Vec<PathBuf>,
+}
+
+impl Collected {
+    /// Returns an empty collection.
+    const fn new() -> Self {
+        Self {
+            warnings: Vec::new(),
+            metrics: BTreeMap::new(),
+            artifacts: Vec::new(),
+        }
+    }
+}
+
+/// Whether the command succeeded.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The command succeeded.
+    Ok,
+    /// The command failed, see the exit code and error.
+    Failed,
+}
+
+/// The result of a command, printed to stdout with `--output-json`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    /// The subcommand that ran, e.g. `from-l2`.
+    pub command: &'static str,
+    /// Whether the command succeeded.
+    pub status: Status,
+    /// The process exit code, see [ExitCode].
+    pub exit_code: u8,
+    /// The error the command failed with, if it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The warnings logged while the command ran.
+    pub warnings: Vec<String>,
+    /// The command-specific metrics, plus the run time as `durationMs`.
+    pub metrics: BTreeMap<String, Value>,
+    /// The files the command wrote.
+    pub artifacts: Vec<PathBuf>,
+}
+
+impl Envelope {
+    /// Builds the envelope for a finished command from everything recorded while it ran.
+    pub fn new(command: &'static str, result: &Result<()>, duration: Duration) -> Self {
+        let Collected {
+            warnings,
+            mut metrics,
+            artifacts,
+        } = std::mem::replace(&mut *collected(), Collected::new());
+        metrics.insert(
+            "durationMs".to_string(),
+            (duration.as_millis() as u64).into(),
+        );
+        let (status, exit_code, error) = match result {
+            Ok(()) => (Status::Ok, ExitCode::Success, None),
+            Err(report) => (Status::Failed, ExitCode::of(report), Some(error(report))),
+        };
+        Self {
+            command,
+            status,
+            exit_code: exit_code as u8,
+            error,
+            warnings,
+            metrics,
+            artifacts,
+        }
+    }
+}
+
+impl Display for Envelope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+/// Prints the envelope instead of human-oriented output from now on.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Returns whether the envelope is printed.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Records a metric, replacing any earlier value of the same name.
+///
+/// Names are camelCase, like fixture fields. Values that fail to serialize are recorded
+/// as `null`.
+pub fn metric(name: &str, value: impl Serialize) {
+    let value = serde_json::to_value(value).unwrap_or_default();
+    collected().metrics.insert(name.to_string(), value);
+}
+
+/// Records the size of a derivation fixture as metrics.
+pub fn fixture_metrics(fixture: &DerivationFixture) {
+    let blobs: usize = fixture.l1_blocks.iter().map(|b| b.blobs.len()).sum();
+    metric("l1Blocks", fixture.l1_blocks.len());
+    metric("blobs", blobs);
+    metric("l2Payloads", fixture.l2_payloads.len());
+}
+
+/// Records a file the command wrote.
+pub fn artifact(path: &Path) {
+    collected().artifacts.push(path.to_path_buf());
+}
+
+/// Prints a line of human-oriented output, to stdout normally and to stderr when stdout
+/// is reserved for the envelope.
+pub fn human(line: impl Display) {
+    if enabled() {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
+/// A [tracing] layer recording the message of every warning.
+///
+/// It is filtered separately from the log output, so warnings reach the envelope even
+/// when they are not logged.
+#[derive(Debug, Default)]
+pub struct Warnings;
+
+impl<S: Subscriber> Layer<S> for Warnings {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::WARN {
+            return;
+        }
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        collected().warnings.push(message.0);
+    }
+}
+
+/// Visits the `message` field of an event.
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{value:?}");
+        }
+    }
+}
+
+/// Locks the collected parts, recovering them if a panic poisoned the lock.
+fn collected() -> MutexGuard<'static, Collected> {
+    COLLECTED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns the error and its causes on one line, without the backtrace or spantrace.
+fn error(report: &Report) -> String {
+    report
+        .chain()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(": ")
+}
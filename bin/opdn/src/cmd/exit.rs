@@ -0,0 +1,115 @@
+//! Stable process exit codes, so wrappers can branch on the class of a failure.
+
+use color_eyre::{eyre::Report, Result};
+use std::{error::Error, fmt, process};
+
+/// The class of a failure, reported as the process exit code.
+///
+/// These values are stable. New classes may be added, but existing codes are never
+/// reassigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// The command succeeded.
+    Success = 0,
+    /// A fixture failed to load or failed its consistency checks.
+    FixtureInvalid = 2,
+    /// The command failed for a reason not covered by another class.
+    Failure = 3,
+    /// An RPC endpoint, file or directory the command depends on is unusable.
+    Environment = 4,
+    /// A request to an RPC endpoint timed out.
+    Timeout = 5,
+    /// A `--post-process` program exited non-zero on a written fixture.
+    PostProcess = 6,
+    /// The command line arguments were invalid.
+    Usage = 64,
+}
+
+impl ExitCode {
+    /// Returns the exit code for an error, which is the outermost classification in its
+    /// chain, or [ExitCode::Failure] if it was never classified.
+    pub fn of(report: &Report) -> Self {
+        report
+            .chain()
+            .find_map(|e| e.downcast_ref::<Classified>())
+            .map_or(Self::Failure, |classified| classified.code)
+    }
+
+    /// Classifies an error with this exit code, leaving its message unchanged.
+    #[track_caller]
+    pub fn classify(self, error: impl Into<Report>) -> Report {
+        Report::new(Classified {
+            code: self,
+            error: error.into(),
+        })
+    }
+
+    /// Returns the exit code for a command line parse error, which also covers `--help`
+    /// and `--version`.
+    pub fn of_clap(error: &clap::Error) -> Self {
+        if error.use_stderr() {
+            Self::Usage
+        } else {
+            Self::Success
+        }
+    }
+}
+
+impl fmt::Display for ExitCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let class = match self {
+            Self::Success => "success",
+            Self::FixtureInvalid => "invalid fixture",
+            Self::Failure => "failure",
+            Self::Environment => "environment error",
+            Self::Timeout => "timeout",
+            Self::PostProcess => "post-processor failure",
+            Self::Usage => "invalid arguments",
+        };
+        f.write_str(class)
+    }
+}
+
+impl From<ExitCode> for process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        Self::from(code as u8)
+    }
+}
+
+/// An error classified with an [ExitCode], displayed as the error it wraps so that
+/// classifying an error does not change its message.
+#[derive(Debug)]
+struct Classified {
+    /// The class of the error.
+    code: ExitCode,
+    /// The classified error.
+    error: Report,
+}
+
+impl fmt::Display for Classified {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl Error for Classified {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Classifies the error of a [Result] with an [ExitCode].
+pub trait WithExitCode<T> {
+    /// Wraps the error, if any, with `code`.
+    fn exit_code(self, code: ExitCode) -> Result<T>;
+}
+
+impl<T, E: Into<Report>> WithExitCode<T> for std::result::Result<T, E> {
+    #[track_caller]
+    fn exit_code(self, code: ExitCode) -> Result<T> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(e) => Err(code.classify(e)),
+        }
+    }
+}
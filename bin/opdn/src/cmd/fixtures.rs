@@ -1,27 +1,50 @@
 //! Logic for building the derivation fixture blocks.
 
-use crate::cmd::blobs;
-use alloy_eips::eip2718::Encodable2718;
-use alloy_primitives::Address;
+use crate::cmd::{
+    blobs,
+    exit::{ExitCode, WithExitCode},
+    rpc,
+};
+use alloy_consensus::TxEnvelope;
+use alloy_eips::eip2718::{Decodable2718, Encodable2718};
+use alloy_primitives::keccak256;
 use color_eyre::eyre::{eyre, Result};
 use kona_derive::online::{
     AlloyChainProvider, OnlineBeaconClient, OnlineBlobProviderWithFallback, SimpleSlotDerivation,
 };
-use kona_derive::traits::ChainProvider;
+use kona_derive::{
+    traits::ChainProvider,
+    types::{BlockInfo, RollupConfig},
+};
 use op_test_vectors::derivation::FixtureBlock;
+use reqwest::{Client, Url};
+use tracing::warn;
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "fixtures";
 
 /// Constructs [FixtureBlock]s for the given L1 blocks.
+///
+/// Blobs are only fetched for L1 blocks from Ecotone, so `blob_provider` may be omitted
+/// for pre-Ecotone ranges.
+///
+/// Blocks whose transactions or receipts roots don't match their header are rejected
+/// unless `allow_invalid` is set.
 pub async fn build_fixture_blocks(
-    batcher_address: Address,
-    signer: Address,
+    cfg: &RollupConfig,
     blocks: &[u64],
+    l1_rpc_url: &Url,
+    allow_invalid: bool,
     l1_provider: &mut AlloyChainProvider,
-    blob_provider: &mut OnlineBlobProviderWithFallback<
-        OnlineBeaconClient,
-        OnlineBeaconClient,
-        SimpleSlotDerivation,
+    mut blob_provider: Option<
+        &mut OnlineBlobProviderWithFallback<
+            OnlineBeaconClient,
+            OnlineBeaconClient,
+            SimpleSlotDerivation,
+        >,
     >,
 ) -> Result<Vec<FixtureBlock>> {
+    let client = Client::new();
     let mut fixtures = Vec::with_capacity(blocks.len());
     for b in blocks {
         let block_info = l1_provider
@@ -40,6 +63,15 @@ pub async fn build_fixture_blocks(
         for tx in txs.as_slice() {
             let mut out = Vec::new();
             tx.encode_2718(&mut out);
+            // Subtle re-encoding differences would produce a transactions root that does
+            // not match the header, so fall back to the node's raw bytes on a mismatch.
+            let hash = *tx.tx_hash();
+            if keccak256(&out) != hash {
+                warn!(target: TARGET, "Re-encoded transaction {} does not match its hash, fetching raw bytes", hash);
+                out = rpc::raw_transaction(&client, l1_rpc_url, hash)
+                    .await?
+                    .into();
+            }
             transactions.push(out.into());
         }
         let receipts = l1_provider
@@ -47,12 +79,11 @@ pub async fn build_fixture_blocks(
             .await
             .map_err(|e| eyre!(e))?;
 
-        let blobs = blobs::load(
+        let blobs = blobs::load_from_ecotone(
+            cfg,
             &block_info,
             txs.as_slice(),
-            batcher_address,
-            signer,
-            blob_provider,
+            blob_provider.as_deref_mut(),
         )
         .await?;
 
@@ -61,8 +92,51 @@ pub async fn build_fixture_blocks(
             transactions,
             blobs,
             receipts,
+            notes: Default::default(),
         };
+        match fixture.validate_roots() {
+            Ok(()) => {}
+            Err(e) if allow_invalid => warn!(target: TARGET, "Keeping invalid block: {e}"),
+            Err(e) => {
+                return Err(ExitCode::FixtureInvalid
+                    .classify(eyre!("{e}, pass --allow-invalid to keep it")))
+            }
+        }
         fixtures.push(fixture);
     }
     Ok(fixtures)
 }
+
+/// Refetches the blobs of existing [FixtureBlock]s from their encoded transactions, e.g.
+/// after fixing the beacon client they were first fetched from.
+pub async fn refetch_blobs(
+    cfg: &RollupConfig,
+    blocks: &mut [FixtureBlock],
+    mut blob_provider: Option<
+        &mut OnlineBlobProviderWithFallback<
+            OnlineBeaconClient,
+            OnlineBeaconClient,
+            SimpleSlotDerivation,
+        >,
+    >,
+) -> Result<()> {
+    for block in blocks {
+        let number = block.header.number;
+        let txs = block
+            .transactions
+            .iter()
+            .map(|tx| TxEnvelope::decode_2718(&mut tx.as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| eyre!("Invalid transaction in L1 block {number}: {e}"))
+            .exit_code(ExitCode::FixtureInvalid)?;
+        let block_info = BlockInfo {
+            hash: block.header.hash_slow(),
+            number,
+            parent_hash: block.header.parent_hash,
+            timestamp: block.header.timestamp,
+        };
+        block.blobs =
+            blobs::load_from_ecotone(cfg, &block_info, &txs, blob_provider.as_deref_mut()).await?;
+    }
+    Ok(())
+}
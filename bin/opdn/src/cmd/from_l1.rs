@@ -1,20 +1,26 @@
 //! Contains logic to generate derivation test fixtures using L1 source block information.
 
-use clap::{ArgAction, Parser};
-use color_eyre::{
-    eyre::{ensure, eyre},
-    Result,
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    only::{Component, OnlyArgs},
+    output::{OutputArgs, TemplateValues},
+    parse::{parse_block_range, parse_relative_block, BlockRange, RelativeBlock},
+    plan::Plan,
+    rollup::RollupArgs,
+    rpc::{block_number, chain_id, resolve_block},
 };
+use alloy_eips::BlockNumberOrTag;
+use clap::{ArgAction, Parser};
+use color_eyre::{eyre::eyre, Result};
 use hashbrown::HashMap;
 use kona_derive::{
     online::*,
     types::{L2BlockInfo, StageError},
 };
-use op_test_vectors::derivation::DerivationFixture;
+use op_test_vectors::{derivation::DerivationFixture, l2::IntoFixture};
 use reqwest::Url;
-use std::path::PathBuf;
 use std::sync::Arc;
-use superchain_registry::ROLLUP_CONFIGS;
 use tracing::{debug, error, info, trace, warn};
 
 /// The logging target to use for [tracing].
@@ -24,23 +30,64 @@ const TARGET: &str = "from-l1";
 #[derive(Parser, Clone, Debug)]
 pub struct FromL1 {
     /// The L1 block number to start from
-    #[clap(short, long, help = "Starting L1 block number")]
-    pub start_block: u64,
+    #[clap(
+        short,
+        long,
+        value_parser = parse_relative_block,
+        required_unless_present = "l1_block",
+        help = "Starting L1 block number or tag, e.g. 1000 or finalized-100"
+    )]
+    pub start_block: Option<RelativeBlock>,
     /// The L1 block number to end at
-    #[clap(short, long, help = "Ending L1 block number")]
-    pub end_block: u64,
+    #[clap(
+        short,
+        long,
+        value_parser = parse_relative_block,
+        required_unless_present = "l1_block",
+        help = "Ending L1 block number or tag, e.g. 1100 or finalized"
+    )]
+    pub end_block: Option<RelativeBlock>,
+    /// The L1 block range, as an alternative to the start and end blocks.
+    #[clap(
+        long,
+        value_parser = parse_block_range,
+        conflicts_with_all = ["start_block", "end_block"],
+        help = "L1 block range, e.g. finalized-100..finalized"
+    )]
+    pub l1_block: Option<BlockRange>,
     /// An L1 RPC URL to fetch L1 block data from.
     #[clap(long, help = "RPC url to fetch L1 block data from")]
     pub l1_rpc_url: String,
     /// An L2 RPC URL to validate span batches.
     #[clap(long, help = "L2 RPC URL to validate span batches")]
     pub l2_rpc_url: String,
-    /// A beacon client to fetch blob data from.
-    #[clap(long, help = "Beacon client url to fetch blob data from")]
-    pub beacon_url: String,
-    /// The output file for the test fixture.
-    #[clap(long, help = "Output file for the test fixture")]
-    pub output: PathBuf,
+    /// A beacon client to fetch blob data from, needed from Ecotone.
+    #[clap(
+        long,
+        help = "Beacon client url to fetch blob data from, required from Ecotone"
+    )]
+    pub beacon_url: Option<String>,
+    /// A description of the scenario, recorded in the fixture.
+    #[clap(long, help = "Description of the scenario recorded in the fixture")]
+    pub description: Option<String>,
+    /// Keep L1 blocks whose transactions or receipts roots don't match their header.
+    #[clap(
+        long,
+        help = "Keep L1 blocks whose transactions or receipts roots don't validate"
+    )]
+    pub allow_invalid: bool,
+    /// Resolve and check every input without generating the fixture.
+    #[clap(long, help = "Resolve and check all inputs, print the plan and exit")]
+    pub dry_run: bool,
+    /// The components to regenerate from an existing fixture.
+    #[command(flatten)]
+    pub only: OnlyArgs,
+    /// The overrides of the registry's rollup config.
+    #[command(flatten)]
+    pub rollup: RollupArgs,
+    /// The output location for the test fixture.
+    #[command(flatten)]
+    pub output: OutputArgs,
     /// Verbosity level (0-4)
     #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
     pub v: u8,
@@ -50,21 +97,125 @@ impl FromL1 {
     /// Runs the derivation test fixture generation using the L1 source block information.
     /// This function effectively takes the L1 block info and fetches any calldata or blob
     /// data associated with this block.
+    ///
+    /// With `--only`, just the selected components are generated, and the rest are
+    /// reused from the `--base` fixture.
     pub async fn run(&self) -> Result<()> {
-        ensure!(
-            self.end_block > self.start_block,
-            "End block must come after the start block"
-        );
-        trace!(target: "from-l1", "Producing derivation fixture for L1 block range [{}, {}]", self.start_block, self.end_block);
+        let (start_block, end_block) = self.block_range().await?;
+        if end_block <= start_block {
+            return Err(
+                ExitCode::Usage.classify(eyre!("End block must come after the start block"))
+            );
+        }
+        self.output.precheck()?;
+        let only = self.only.load()?;
+        trace!(target: "from-l1", "Producing derivation fixture for L1 block range [{}, {}]", start_block, end_block);
+
+        if self.dry_run {
+            envelope::human(self.plan(start_block, end_block).await?);
+            return Ok(());
+        }
 
-        // Build the pipeline
         let cfg = Arc::new(self.rollup_config().await?);
         let mut l1_provider = self.l1_provider()?;
+        // Pre-Ecotone batches are posted as calldata, so the beacon client is only needed
+        // for ranges reaching Ecotone.
+        if self.beacon_url.is_none() {
+            let end = l1_provider
+                .block_info_by_number(end_block)
+                .await
+                .map_err(|e| eyre!(e))?;
+            crate::cmd::blobs::ensure_pre_ecotone(&cfg, &end)?;
+        }
+        let mut blob_provider = self.blob_provider();
+
+        let (derived, first_l1_block) = if only.runs_pipeline() {
+            self.derive(cfg.clone(), start_block, end_block).await?
+        } else {
+            // The reused L2 data was derived from the cursor's L1 origin on, so the L1
+            // blocks still start there.
+            let first_l1_block = self.first_l1_block(start_block).await?;
+            (DerivationFixture::default(), first_l1_block)
+        };
+
+        // The pipeline starts from the L1 origin of its cursor, which may come before the
+        // start block, so the L1 blocks run from there to the end block.
+        let blocks = (first_l1_block..=end_block).collect::<Vec<_>>();
+
+        // Construct the fixture blocks, or refetch the blobs of the base fixture's.
+        let fixture_blocks = if only.regenerates(Component::L1Blocks) {
+            crate::cmd::build_fixture_blocks(
+                &cfg,
+                &blocks,
+                &self.l1_rpc_url()?,
+                self.allow_invalid,
+                &mut l1_provider,
+                self.beacon_url.is_some().then_some(&mut blob_provider),
+            )
+            .await?
+        } else if let Some(base) = only.base().filter(|_| only.regenerates(Component::Blobs)) {
+            let mut blocks = base.l1_blocks.clone();
+            crate::cmd::fixtures::refetch_blobs(
+                &cfg,
+                &mut blocks,
+                self.beacon_url.is_some().then_some(&mut blob_provider),
+            )
+            .await?;
+            blocks
+        } else {
+            Vec::new()
+        };
+
+        // Capture the beacon config used to map L1 timestamps to blob slots.
+        let l1_beacon_config = match &self.beacon_url {
+            Some(url) if fixture_blocks.iter().any(|b| !b.blobs.is_empty()) => {
+                Some(crate::cmd::blobs::beacon_config(url).await?)
+            }
+            _ => None,
+        };
+
+        let mut fixture = DerivationFixture {
+            description: self.description.clone(),
+            rollup_config: Arc::unwrap_or_clone(cfg),
+            rollup_config_overrides: self.rollup.recorded(),
+            l1_blocks: fixture_blocks,
+            l1_beacon_config,
+            ..derived
+        };
+        only.reuse(&mut fixture)?;
+        fixture
+            .validate_blob_slots()
+            .and_then(|_| fixture.validate_forks())
+            .exit_code(ExitCode::FixtureInvalid)?;
+        info!(target: "from-l1", "Successfully built derivation test fixture");
+
+        // Write the derivation fixture to the specified output location.
+        let values = TemplateValues::from_derivation_fixture(&fixture);
+        let path = self.output.write(&values, &fixture)?;
+        envelope::fixture_metrics(&fixture);
+        info!(target: TARGET, "Wrote derivation fixture to: {:?}", path);
+
+        Ok(())
+    }
+
+    /// Runs the derivation pipeline over the L1 block range, returning a fixture with the
+    /// derived payloads and the L2 data read alongside them, but no L1 blocks, along with
+    /// the first L1 block the pipeline read.
+    ///
+    /// The pipeline starts from the last L2 block with an L1 origin before the range, and
+    /// stops once its origin moves past the end of the range.
+    async fn derive(
+        &self,
+        cfg: Arc<RollupConfig>,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<(DerivationFixture, u64)> {
+        // Build the pipeline
+        let mut l1_provider = self.l1_provider()?;
         let mut l2_provider = self.l2_provider(cfg.clone())?;
         let attributes = self.attributes(cfg.clone(), &l2_provider, &l1_provider);
-        let mut blob_provider = self.blob_provider();
-        let dap = self.dap(l1_provider.clone(), blob_provider.clone(), &cfg);
-        let mut l2_cursor = self.cursor().await?;
+        let dap = self.dap(l1_provider.clone(), self.blob_provider(), &cfg);
+        let mut l2_cursor = self.cursor(start_block).await?;
         let l1_tip = l1_provider
             .block_info_by_number(l2_cursor.l1_origin.number)
             .await
@@ -88,15 +239,19 @@ impl FromL1 {
             .system_config_by_number(l2_cursor.block_info.number, Arc::clone(&cfg))
             .await
             .map_err(|e| eyre!(e))?;
-        configs.insert(l2_cursor.block_info.number, first_system_config);
-        l2_block_infos.insert(l2_cursor.block_info.number, l2_cursor);
+        configs.insert(
+            l2_cursor.block_info.number,
+            first_system_config.into_fixture(),
+        );
+        l2_block_infos.insert(l2_cursor.block_info.number, l2_cursor.into_fixture());
         let start_l2_cursor = l2_cursor.block_info.number;
+        let first_l1_block = l2_cursor.l1_origin.number.min(start_block);
 
         // Run the pipeline
         loop {
-            // If the cursor is beyond the end block, break the loop.
-            if l2_cursor.block_info.number >= self.end_block {
-                trace!(target: TARGET, "Cursor is beyond the end block, breaking loop");
+            // If the pipeline has read past the end block, break the loop.
+            if pipeline.origin().is_some_and(|o| o.number > end_block) {
+                trace!(target: TARGET, "Origin is beyond the end block, breaking loop");
                 break;
             }
 
@@ -135,7 +290,7 @@ impl FromL1 {
                 "L2 Block [{}] L1 Range: [{}, {}]",
                 derived, l2_block_info.l1_origin.number, origin.number
             );
-            payloads.insert(derived as u64, attributes.attributes);
+            payloads.insert(derived as u64, attributes.attributes.into_fixture());
 
             // Keep trying to advance the cursor in case the fetch fails.
             loop {
@@ -160,8 +315,8 @@ impl FromL1 {
                 .system_config_by_number(l2_cursor.block_info.number, Arc::clone(&cfg))
                 .await
                 .map_err(|e| eyre!(e))?;
-            configs.insert(l2_cursor.block_info.number, system_config);
-            l2_block_infos.insert(l2_cursor.block_info.number, l2_cursor);
+            configs.insert(l2_cursor.block_info.number, system_config.into_fixture());
+            l2_block_infos.insert(l2_cursor.block_info.number, l2_cursor.into_fixture());
 
             // Get reference payloads by l2 block number for span batch validation
             let l2_payload = l2_provider
@@ -174,65 +329,147 @@ impl FromL1 {
             );
         }
 
-        // Construct a sequential list of block numbers from [start_block, end_block].
-        let blocks = (self.start_block..=self.end_block).collect::<Vec<_>>();
-
-        // Construct the derivation fixture.
-        let fixture_blocks = crate::cmd::build_fixture_blocks(
-            cfg.batch_inbox_address,
-            cfg.genesis
-                .system_config
-                .as_ref()
-                .map(|sc| sc.batcher_address)
-                .unwrap_or_default(),
-            &blocks,
-            &mut l1_provider,
-            &mut blob_provider,
-        )
-        .await?;
-
         let fixture = DerivationFixture {
-            rollup_config: Arc::unwrap_or_clone(cfg),
-            l1_blocks: fixture_blocks,
             l2_payloads: payloads,
             ref_payloads,
             l2_system_configs: configs,
             l2_block_infos,
             l2_cursor_start: start_l2_cursor,
-            l2_cursor_end: self.end_block,
+            l2_cursor_end: l2_cursor.block_info.number,
+            ..Default::default()
         };
-        info!(target: "from-l1", "Successfully built derivation test fixture");
+        Ok((fixture, first_l1_block))
+    }
 
-        // Write the derivation fixture to the specified output location.
-        let file = std::fs::File::create(&self.output)?;
-        serde_json::to_writer_pretty(file, &fixture)?;
-        info!(target: "from-l1", "Wrote derivation fixture to: {:?}", self.output);
+    /// Returns an [L2BlockInfo] cursor for the pipeline, which is the last L2 block with
+    /// an L1 origin before the start block.
+    pub async fn cursor(&self, start_block: u64) -> Result<L2BlockInfo> {
+        let cfg = self.rollup_config().await?;
+        self.last_l2_block_before(start_block, Arc::new(cfg))
+            .await
+            .map_err(|e| eyre!("Failed to find the L2 block info for the pipeline cursor: {e}"))
+    }
 
-        Ok(())
+    /// Returns the first L1 block of the fixture: the L1 origin of the pipeline cursor,
+    /// unless the start block comes before it.
+    pub async fn first_l1_block(&self, start_block: u64) -> Result<u64> {
+        let l2_cursor = self.cursor(start_block).await?;
+        Ok(l2_cursor.l1_origin.number.min(start_block))
     }
 
-    /// Gets the L2 starting block number.
-    /// Returns the genesis L2 block number if the start block is less than the genesis block number.
-    pub fn start_block(&self, cfg: &RollupConfig) -> u64 {
-        if self.start_block < cfg.genesis.l2.number {
-            cfg.genesis.l2.number
-        } else if self.start_block != 0 {
-            self.start_block - 1
-        } else {
-            self.start_block
+    /// Returns the last L2 block with an L1 origin before `l1_block`, or the L2 genesis
+    /// block if there is none.
+    ///
+    /// L1 origins never decrease along the L2 chain, so the block is found by a binary
+    /// search between the L2 genesis and the latest L2 block.
+    pub async fn last_l2_block_before(
+        &self,
+        l1_block: u64,
+        cfg: Arc<RollupConfig>,
+    ) -> Result<L2BlockInfo> {
+        let client = reqwest::Client::new();
+        let latest = block_number(&client, &self.l2_rpc_url()?, BlockNumberOrTag::Latest).await?;
+        let genesis = cfg.genesis.l2.number;
+        let mut l2_provider = self.l2_provider(cfg)?;
+
+        // Find the first block after genesis with an origin at or after the L1 block.
+        let (mut low, mut high) = (genesis + 1, latest + 1);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let info = l2_provider
+                .l2_block_info_by_number(mid)
+                .await
+                .map_err(|e| eyre!(e))?;
+            if info.l1_origin.number < l1_block {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
         }
+        l2_provider
+            .l2_block_info_by_number(low - 1)
+            .await
+            .map_err(|e| eyre!(e))
+    }
+
+    /// Resolves the block range, which may be relative to tags, to L1 block numbers.
+    pub async fn block_range(&self) -> Result<(u64, u64)> {
+        let (start, end) = match self.l1_block {
+            Some(range) => (range.start, range.end),
+            None => (
+                self.start_block
+                    .ok_or_else(|| eyre!("Missing start block"))?,
+                self.end_block.ok_or_else(|| eyre!("Missing end block"))?,
+            ),
+        };
+        let client = reqwest::Client::new();
+        let url = self.l1_rpc_url()?;
+        let start_block = resolve_block(&client, &url, None, start).await?;
+        let end_block = resolve_block(&client, &url, None, end).await?;
+        Ok((start_block, end_block))
     }
 
-    /// Returns an [L2BlockInfo] cursor for the pipeline.
-    pub async fn cursor(&self) -> Result<L2BlockInfo> {
+    /// Resolves and checks every input, returning the plan without generating the fixture.
+    pub async fn plan(&self, start_block: u64, end_block: u64) -> Result<Plan> {
         let cfg = self.rollup_config().await?;
-        let start_block = self.start_block(&cfg);
-        let mut l2_provider = self.l2_provider(Arc::new(cfg))?;
-        let cursor = l2_provider
-            .l2_block_info_by_number(start_block)
-            .await
-            .map_err(|_| eyre!("Failed to fetch genesis L2 block info for pipeline cursor"))?;
-        Ok(cursor)
+        let client = reqwest::Client::new();
+        let l1_chain_id = chain_id(&client, &self.l1_rpc_url()?).await?;
+        if l1_chain_id != cfg.l1_chain_id {
+            return Err(ExitCode::Environment.classify(eyre!(
+                "L1 RPC is on chain {}, expected {}",
+                l1_chain_id,
+                cfg.l1_chain_id
+            )));
+        }
+        let beacon = match &self.beacon_url {
+            Some(url) => {
+                let beacon = crate::cmd::blobs::beacon_config(url).await?;
+                format!(
+                    "genesis time {}, {}s slots",
+                    beacon.genesis_time, beacon.seconds_per_slot
+                )
+            }
+            None => "none, calldata batches only".to_string(),
+        };
+        let l2_cursor = self.cursor(start_block).await?;
+        // The L2 blocks actually derived depend on when their batches were posted, so the
+        // end is estimated from the L1 origins.
+        let l2_end = self
+            .last_l2_block_before(end_block + 1, Arc::new(cfg.clone()))
+            .await?;
+        let first_l1_block = l2_cursor.l1_origin.number.min(start_block);
+
+        let values = TemplateValues {
+            chain: cfg.l2_chain_id,
+            fixture_type: "derivation",
+            l1_start: first_l1_block,
+            l1_end: end_block,
+            l2_start: l2_cursor.block_info.number + 1,
+            l2_end: l2_end.block_info.number,
+        };
+        Ok(Plan::new("from-l1")
+            .with("L2 chain", cfg.l2_chain_id)
+            .with("L1 chain", l1_chain_id)
+            .with("Beacon", beacon)
+            .with(
+                "L1 blocks",
+                format!(
+                    "[{first_l1_block}, {end_block}], {} blocks",
+                    end_block - first_l1_block + 1
+                ),
+            )
+            .with(
+                "L2 blocks",
+                format!(
+                    "cursor {}, about {} derived",
+                    l2_cursor.block_info.number,
+                    l2_end
+                        .block_info
+                        .number
+                        .saturating_sub(l2_cursor.block_info.number)
+                ),
+            )
+            .with("Output", self.output.resolve(&values)?.display()))
     }
 
     /// Returns a new [AlloyChainProvider] using the l1 rpc url.
@@ -256,12 +493,17 @@ impl FromL1 {
     }
 
     /// Returns a new [OnlineBlobProviderWithFallback] using the beacon url.
+    ///
+    /// Without a beacon url the provider has no endpoint, which is only sound for
+    /// pre-Ecotone ranges, where the data source never asks it for blobs.
     pub fn blob_provider(
         &self,
     ) -> OnlineBlobProviderWithFallback<OnlineBeaconClient, OnlineBeaconClient, SimpleSlotDerivation>
     {
         OnlineBlobProviderBuilder::new()
-            .with_beacon_client(OnlineBeaconClient::new_http(self.beacon_url.clone()))
+            .with_beacon_client(OnlineBeaconClient::new_http(
+                self.beacon_url.clone().unwrap_or_default(),
+            ))
             .build()
     }
 
@@ -286,16 +528,12 @@ impl FromL1 {
         EthereumDataSource::new(l1_provider, blob_provider, cfg)
     }
 
-    /// Gets the rollup config from the l2 rpc url.
+    /// Gets the rollup config of the l2 rpc url's chain, with any overrides applied.
     pub async fn rollup_config(&self) -> Result<RollupConfig> {
         let mut l2_provider =
             AlloyL2ChainProvider::new_http(self.l2_rpc_url()?, Arc::new(Default::default()));
         let l2_chain_id = l2_provider.chain_id().await.map_err(|e| eyre!(e))?;
-        let cfg = ROLLUP_CONFIGS
-            .get(&l2_chain_id)
-            .cloned()
-            .ok_or_else(|| eyre!("No rollup config found for L2 chain ID: {}", l2_chain_id))?;
-        Ok(cfg)
+        self.rollup.config(l2_chain_id)
     }
 
     /// Returns the l1 rpc url from CLI or environment variable.
@@ -309,7 +547,7 @@ impl FromL1 {
     }
 
     /// Returns the beacon url from CLI or environment variable.
-    pub fn beacon_url(&self) -> String {
+    pub fn beacon_url(&self) -> Option<String> {
         self.beacon_url.clone()
     }
 }
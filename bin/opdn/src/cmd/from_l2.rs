@@ -1,20 +1,25 @@
 //! From L2 Subcommand
 
-use clap::{ArgAction, Parser};
-use color_eyre::{
-    eyre::{ensure, eyre},
-    Result,
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    only::{Component, OnlyArgs},
+    output::{OutputArgs, TemplateValues},
+    parse::{parse_block_range, parse_relative_block, BlockRange, RelativeBlock},
+    plan::Plan,
+    rollup::RollupArgs,
+    rpc::{chain_id, resolve_block, sync_status},
 };
+use clap::{ArgAction, Parser};
+use color_eyre::{eyre::eyre, Result};
 use hashbrown::HashMap;
 use kona_derive::{
     online::*,
     types::{L2BlockInfo, StageError},
 };
-use op_test_vectors::derivation::DerivationFixture;
+use op_test_vectors::{derivation::DerivationFixture, l2::IntoFixture};
 use reqwest::Url;
-use std::path::PathBuf;
 use std::sync::Arc;
-use superchain_registry::ROLLUP_CONFIGS;
 use tracing::{debug, error, info, trace, warn};
 
 /// The logging target to use for [tracing].
@@ -23,24 +28,71 @@ const TARGET: &str = "from-l2";
 /// CLI arguments for the `from-l2` subcommand of `opdn`.
 #[derive(Parser, Clone, Debug)]
 pub struct FromL2 {
-    /// The L2 block number to start from
-    #[clap(short, long, help = "Starting L1 block number")]
-    pub start_block: u64,
+    /// The first L2 block to derive, so the starting cursor is the block before it.
+    #[clap(
+        short,
+        long,
+        value_parser = parse_relative_block,
+        required_unless_present = "l2_block",
+        help = "Starting L2 block number or tag, e.g. 1000 or finalized-100"
+    )]
+    pub start_block: Option<RelativeBlock>,
     /// The L2 block number to end at
-    #[clap(short, long, help = "Ending L1 block number")]
-    pub end_block: u64,
+    #[clap(
+        short,
+        long,
+        value_parser = parse_relative_block,
+        required_unless_present = "l2_block",
+        help = "Ending L2 block number or tag, e.g. 1100 or finalized"
+    )]
+    pub end_block: Option<RelativeBlock>,
+    /// The L2 block range, as an alternative to the start and end blocks.
+    #[clap(
+        long,
+        value_parser = parse_block_range,
+        conflicts_with_all = ["start_block", "end_block"],
+        help = "L2 block range, e.g. finalized-100..finalized"
+    )]
+    pub l2_block: Option<BlockRange>,
     /// An RPC URL to fetch L1 block data from.
     #[clap(long, help = "RPC url to fetch L1 block data from")]
     pub l1_rpc_url: String,
     /// An L2 RPC URL to validate span batches.
     #[clap(long, help = "L2 RPC URL to validate span batches")]
     pub l2_rpc_url: String,
-    /// A beacon client to fetch blob data from.
-    #[clap(long, help = "Beacon client url to fetch blob data from")]
-    pub beacon_url: String,
-    /// The output file for the test fixture.
-    #[clap(long, help = "Output file for the test fixture")]
-    pub output: PathBuf,
+    /// A rollup node RPC URL to resolve L2 block tags from.
+    #[clap(
+        long,
+        help = "Rollup node RPC url to resolve L2 block tags from its sync status"
+    )]
+    pub rollup_rpc_url: Option<String>,
+    /// A beacon client to fetch blob data from, needed from Ecotone.
+    #[clap(
+        long,
+        help = "Beacon client url to fetch blob data from, required from Ecotone"
+    )]
+    pub beacon_url: Option<String>,
+    /// A description of the scenario, recorded in the fixture.
+    #[clap(long, help = "Description of the scenario recorded in the fixture")]
+    pub description: Option<String>,
+    /// Keep L1 blocks whose transactions or receipts roots don't match their header.
+    #[clap(
+        long,
+        help = "Keep L1 blocks whose transactions or receipts roots don't validate"
+    )]
+    pub allow_invalid: bool,
+    /// Resolve and check every input without generating the fixture.
+    #[clap(long, help = "Resolve and check all inputs, print the plan and exit")]
+    pub dry_run: bool,
+    /// The components to regenerate from an existing fixture.
+    #[command(flatten)]
+    pub only: OnlyArgs,
+    /// The overrides of the registry's rollup config.
+    #[command(flatten)]
+    pub rollup: RollupArgs,
+    /// The output location for the test fixture.
+    #[command(flatten)]
+    pub output: OutputArgs,
     /// Verbosity level (0-4)
     #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
     pub v: u8,
@@ -48,21 +100,127 @@ pub struct FromL2 {
 
 impl FromL2 {
     /// Runs the from-l2 subcommand.
+    ///
+    /// With `--only`, just the selected components are generated, and the rest are
+    /// reused from the `--base` fixture.
     pub async fn run(&self) -> Result<()> {
-        ensure!(
-            self.end_block > self.start_block,
-            "End block before start block"
-        );
-        trace!(target: TARGET, "Producing derivation fixture for L2 block range [{}, {}]", self.start_block, self.end_block);
+        let (start_block, end_block) = self.block_range().await?;
+        if end_block <= start_block {
+            return Err(ExitCode::Usage.classify(eyre!("End block before start block")));
+        }
+        self.output.precheck()?;
+        let only = self.only.load()?;
+        trace!(target: TARGET, "Producing derivation fixture for L2 block range [{}, {}]", start_block, end_block);
+
+        if self.dry_run {
+            envelope::human(self.plan(start_block, end_block).await?);
+            return Ok(());
+        }
 
-        // Build the pipeline
         let cfg = Arc::new(self.rollup_config().await?);
         let mut l1_provider = self.l1_provider()?;
+        // Pre-Ecotone batches are posted as calldata, so the beacon client is only needed
+        // for ranges reaching Ecotone. Batches are posted after the blocks they hold, so
+        // a range ending just before Ecotone may still need it, which is reported once
+        // its L1 blocks are fetched.
+        if self.beacon_url.is_none() {
+            let end = self
+                .l2_provider(cfg.clone())?
+                .l2_block_info_by_number(end_block)
+                .await
+                .map_err(|e| eyre!(e))?;
+            crate::cmd::blobs::ensure_pre_ecotone(&cfg, &end.block_info)?;
+        }
+        let mut blob_provider = self.blob_provider();
+
+        let (derived, first_l1_block, last_l1_block) = if only.runs_pipeline() {
+            self.derive(cfg.clone(), start_block, end_block).await?
+        } else {
+            // Without the pipeline, the L1 range is the one the base fixture covers.
+            let numbers = only
+                .base()
+                .into_iter()
+                .flat_map(|base| &base.l1_blocks)
+                .map(|b| b.header.number);
+            let first_l1_block = numbers.clone().min().unwrap_or_default();
+            let last_l1_block = numbers.max().unwrap_or_default();
+            (DerivationFixture::default(), first_l1_block, last_l1_block)
+        };
+
+        // Take the full L1 range of blocks and get all needed data.
+        let l1_blocks = (first_l1_block..=last_l1_block).collect::<Vec<u64>>();
+
+        // Construct the fixture blocks, or refetch the blobs of the base fixture's.
+        let blocks = if only.regenerates(Component::L1Blocks) {
+            crate::cmd::build_fixture_blocks(
+                &cfg,
+                &l1_blocks,
+                &self.l1_rpc_url()?,
+                self.allow_invalid,
+                &mut l1_provider,
+                self.beacon_url.is_some().then_some(&mut blob_provider),
+            )
+            .await?
+        } else if let Some(base) = only.base().filter(|_| only.regenerates(Component::Blobs)) {
+            let mut blocks = base.l1_blocks.clone();
+            crate::cmd::fixtures::refetch_blobs(
+                &cfg,
+                &mut blocks,
+                self.beacon_url.is_some().then_some(&mut blob_provider),
+            )
+            .await?;
+            blocks
+        } else {
+            Vec::new()
+        };
+
+        // Capture the beacon config used to map L1 timestamps to blob slots.
+        let l1_beacon_config = match &self.beacon_url {
+            Some(url) if blocks.iter().any(|b| !b.blobs.is_empty()) => {
+                Some(crate::cmd::blobs::beacon_config(url).await?)
+            }
+            _ => None,
+        };
+
+        let mut fixture = DerivationFixture {
+            description: self.description.clone(),
+            rollup_config: Arc::unwrap_or_clone(cfg),
+            rollup_config_overrides: self.rollup.recorded(),
+            l1_blocks: blocks,
+            l1_beacon_config,
+            ..derived
+        };
+        only.reuse(&mut fixture)?;
+        fixture
+            .validate_blob_slots()
+            .and_then(|_| fixture.validate_forks())
+            .exit_code(ExitCode::FixtureInvalid)?;
+        info!(target: TARGET, "Successfully built derivation test fixture");
+
+        // Write the derivation fixture to the specified output location.
+        let values = TemplateValues::from_derivation_fixture(&fixture);
+        let path = self.output.write(&values, &fixture)?;
+        envelope::fixture_metrics(&fixture);
+        info!(target: TARGET, "Wrote derivation fixture to: {:?}", path);
+
+        Ok(())
+    }
+
+    /// Runs the derivation pipeline over the L2 block range, returning a fixture with the
+    /// derived payloads and the L2 data read alongside them, but no L1 blocks, along with
+    /// the first and last L1 blocks the payloads were derived from.
+    async fn derive(
+        &self,
+        cfg: Arc<RollupConfig>,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<(DerivationFixture, u64, u64)> {
+        // Build the pipeline
+        let mut l1_provider = self.l1_provider()?;
         let mut l2_provider = self.l2_provider(cfg.clone())?;
         let attributes = self.attributes(cfg.clone(), &l2_provider, &l1_provider);
-        let mut blob_provider = self.blob_provider();
-        let dap = self.dap(l1_provider.clone(), blob_provider.clone(), &cfg);
-        let mut l2_cursor = self.cursor().await?;
+        let dap = self.dap(l1_provider.clone(), self.blob_provider(), &cfg);
+        let mut l2_cursor = self.cursor(start_block).await?;
         let l1_tip = l1_provider
             .block_info_by_number(l2_cursor.l1_origin.number)
             .await
@@ -91,13 +249,16 @@ impl FromL2 {
             .system_config_by_number(l2_cursor.block_info.number, Arc::clone(&cfg))
             .await
             .map_err(|e| eyre!(e))?;
-        configs.insert(l2_cursor.block_info.number, first_system_config);
-        l2_block_infos.insert(l2_cursor.block_info.number, l2_cursor);
+        configs.insert(
+            l2_cursor.block_info.number,
+            first_system_config.into_fixture(),
+        );
+        l2_block_infos.insert(l2_cursor.block_info.number, l2_cursor.into_fixture());
         let start_l2_cursor = l2_cursor.block_info.number;
 
         // TODO: Temporary patch to provide all span batch data to check.
         // 100 blocks before the start block.
-        for i in (self.start_block.saturating_sub(500)..self.start_block).rev() {
+        for i in (start_block.saturating_sub(500)..start_block).rev() {
             let l2_block_info = l2_provider
                 .l2_block_info_by_number(i)
                 .await
@@ -106,8 +267,8 @@ impl FromL2 {
                 .system_config_by_number(i, Arc::clone(&cfg))
                 .await
                 .map_err(|e| eyre!(e))?;
-            configs.insert(i, system_config);
-            l2_block_infos.insert(i, l2_block_info);
+            configs.insert(i, system_config.into_fixture());
+            l2_block_infos.insert(i, l2_block_info.into_fixture());
             // Get reference payloads by l2 block number for span batch validation
             let l2_payload = l2_provider
                 .payload_by_number(i)
@@ -119,7 +280,7 @@ impl FromL2 {
         // Run the pipeline
         loop {
             // If the cursor is beyond the end block, break the loop.
-            if l2_cursor.block_info.number >= self.end_block {
+            if l2_cursor.block_info.number >= end_block {
                 trace!(target: TARGET, "Cursor is beyond the end block, breaking loop");
                 break;
             }
@@ -159,7 +320,7 @@ impl FromL2 {
                 "L2 Block [{}] L1 Range: [{}, {}]",
                 derived, l2_block_info.l1_origin.number, origin.number
             );
-            payloads.insert(derived as u64, attributes.attributes);
+            payloads.insert(derived as u64, attributes.attributes.into_fixture());
             last_l1_block = origin.number;
 
             // Keep trying to advance the cursor in case the fetch fails.
@@ -185,62 +346,38 @@ impl FromL2 {
                 .system_config_by_number(l2_cursor.block_info.number, Arc::clone(&cfg))
                 .await
                 .map_err(|e| eyre!(e))?;
-            configs.insert(l2_cursor.block_info.number, system_config);
-            l2_block_infos.insert(l2_cursor.block_info.number, l2_cursor);
+            configs.insert(l2_cursor.block_info.number, system_config.into_fixture());
+            l2_block_infos.insert(l2_cursor.block_info.number, l2_cursor.into_fixture());
         }
 
-        // Take the full L1 range of blocks and get all needed data.
-        let l1_blocks = (first_l1_block..=last_l1_block).collect::<Vec<u64>>();
-
-        // Construct the fixture blocks.
-        let blocks = crate::cmd::build_fixture_blocks(
-            cfg.batch_inbox_address,
-            cfg.genesis
-                .system_config
-                .as_ref()
-                .map(|sc| sc.batcher_address)
-                .unwrap_or_default(),
-            &l1_blocks,
-            &mut l1_provider,
-            &mut blob_provider,
-        )
-        .await?;
         let fixture = DerivationFixture {
-            rollup_config: Arc::unwrap_or_clone(cfg),
-            l1_blocks: blocks,
             l2_payloads: payloads,
             ref_payloads,
             l2_system_configs: configs,
             l2_block_infos,
             l2_cursor_start: start_l2_cursor,
-            l2_cursor_end: self.end_block,
+            l2_cursor_end: end_block,
+            ..Default::default()
         };
-        info!(target: TARGET, "Successfully built derivation test fixture");
-
-        // Write the derivation fixture to the specified output location.
-        let file = std::fs::File::create(&self.output)?;
-        serde_json::to_writer_pretty(file, &fixture)?;
-        info!(target: "from-l1", "Wrote derivation fixture to: {:?}", self.output);
-
-        Ok(())
+        Ok((fixture, first_l1_block, last_l1_block))
     }
 
-    /// Gets the L2 starting block number.
+    /// Gets the L2 block number the pipeline cursor starts at.
     /// Returns the genesis L2 block number if the start block is less than the genesis block number.
-    pub fn start_block(&self, cfg: &RollupConfig) -> u64 {
-        if self.start_block < cfg.genesis.l2.number {
+    pub fn cursor_block(&self, start_block: u64, cfg: &RollupConfig) -> u64 {
+        if start_block < cfg.genesis.l2.number {
             cfg.genesis.l2.number
-        } else if self.start_block != 0 {
-            self.start_block - 1
+        } else if start_block != 0 {
+            start_block - 1
         } else {
-            self.start_block
+            start_block
         }
     }
 
     /// Returns an [L2BlockInfo] cursor for the pipeline.
-    pub async fn cursor(&self) -> Result<L2BlockInfo> {
+    pub async fn cursor(&self, start_block: u64) -> Result<L2BlockInfo> {
         let cfg = self.rollup_config().await?;
-        let start_block = self.start_block(&cfg);
+        let start_block = self.cursor_block(start_block, &cfg);
         let mut l2_provider = self.l2_provider(Arc::new(cfg))?;
         let cursor = l2_provider
             .l2_block_info_by_number(start_block)
@@ -249,6 +386,99 @@ impl FromL2 {
         Ok(cursor)
     }
 
+    /// Resolves the block range, which may be relative to tags, to L2 block numbers.
+    ///
+    /// Tags are resolved from the rollup node's sync status if a rollup RPC url is given,
+    /// and from the L2 execution client otherwise.
+    pub async fn block_range(&self) -> Result<(u64, u64)> {
+        let (start, end) = match self.l2_block {
+            Some(range) => (range.start, range.end),
+            None => (
+                self.start_block
+                    .ok_or_else(|| eyre!("Missing start block"))?,
+                self.end_block.ok_or_else(|| eyre!("Missing end block"))?,
+            ),
+        };
+        let client = reqwest::Client::new();
+        let status = match &self.rollup_rpc_url {
+            Some(url) => {
+                let url = Url::parse(url).map_err(|e| eyre!("Invalid rollup RPC URL: {}", e))?;
+                Some(sync_status(&client, &url).await?)
+            }
+            None => None,
+        };
+        let url = self.l2_rpc_url()?;
+        let start_block = resolve_block(&client, &url, status.as_ref(), start).await?;
+        let end_block = resolve_block(&client, &url, status.as_ref(), end).await?;
+        Ok((start_block, end_block))
+    }
+
+    /// Resolves and checks every input, returning the plan without generating the fixture.
+    ///
+    /// Only the L2 blocks at either end of the range are fetched, to estimate the L1
+    /// origin range the fixture will span.
+    pub async fn plan(&self, start_block: u64, end_block: u64) -> Result<Plan> {
+        let cfg = self.rollup_config().await?;
+        let client = reqwest::Client::new();
+        let l1_chain_id = chain_id(&client, &self.l1_rpc_url()?).await?;
+        if l1_chain_id != cfg.l1_chain_id {
+            return Err(ExitCode::Environment.classify(eyre!(
+                "L1 RPC is on chain {}, expected {}",
+                l1_chain_id,
+                cfg.l1_chain_id
+            )));
+        }
+        let beacon = match &self.beacon_url {
+            Some(url) => {
+                let beacon = crate::cmd::blobs::beacon_config(url).await?;
+                format!(
+                    "genesis time {}, {}s slots",
+                    beacon.genesis_time, beacon.seconds_per_slot
+                )
+            }
+            None => "none, calldata batches only".to_string(),
+        };
+        let mut l2_provider = self.l2_provider(Arc::new(cfg.clone()))?;
+        let cursor = self.cursor_block(start_block, &cfg);
+        let first_l1_block = l2_provider
+            .l2_block_info_by_number(cursor)
+            .await
+            .map_err(|e| eyre!(e))?
+            .l1_origin
+            .number;
+        let last_l1_block = l2_provider
+            .l2_block_info_by_number(end_block)
+            .await
+            .map_err(|e| eyre!(e))?
+            .l1_origin
+            .number;
+
+        let values = TemplateValues {
+            chain: cfg.l2_chain_id,
+            fixture_type: "derivation",
+            l1_start: first_l1_block,
+            l1_end: last_l1_block,
+            l2_start: cursor + 1,
+            l2_end: end_block,
+        };
+        Ok(Plan::new("from-l2")
+            .with("L2 chain", cfg.l2_chain_id)
+            .with("L1 chain", l1_chain_id)
+            .with("Beacon", beacon)
+            .with(
+                "L2 blocks",
+                format!("cursor {cursor}, {} derived", end_block - cursor),
+            )
+            .with(
+                "L1 origins",
+                format!(
+                    "[{first_l1_block}, {last_l1_block}], at least {} blocks",
+                    last_l1_block - first_l1_block + 1
+                ),
+            )
+            .with("Output", self.output.resolve(&values)?.display()))
+    }
+
     /// Returns a new [AlloyChainProvider] using the l1 rpc url.
     pub fn l1_provider(&self) -> Result<AlloyChainProvider> {
         Ok(AlloyChainProvider::new_http(self.l1_rpc_url()?))
@@ -270,12 +500,17 @@ impl FromL2 {
     }
 
     /// Returns a new [OnlineBlobProviderWithFallback] using the beacon url.
+    ///
+    /// Without a beacon url the provider has no endpoint, which is only sound for
+    /// pre-Ecotone ranges, where the data source never asks it for blobs.
     pub fn blob_provider(
         &self,
     ) -> OnlineBlobProviderWithFallback<OnlineBeaconClient, OnlineBeaconClient, SimpleSlotDerivation>
     {
         OnlineBlobProviderBuilder::new()
-            .with_beacon_client(OnlineBeaconClient::new_http(self.beacon_url.clone()))
+            .with_beacon_client(OnlineBeaconClient::new_http(
+                self.beacon_url.clone().unwrap_or_default(),
+            ))
             .build()
     }
 
@@ -300,16 +535,12 @@ impl FromL2 {
         EthereumDataSource::new(l1_provider, blob_provider, cfg)
     }
 
-    /// Gets the rollup config from the l2 rpc url.
+    /// Gets the rollup config of the l2 rpc url's chain, with any overrides applied.
     pub async fn rollup_config(&self) -> Result<RollupConfig> {
         let mut l2_provider =
             AlloyL2ChainProvider::new_http(self.l2_rpc_url()?, Arc::new(Default::default()));
         let l2_chain_id = l2_provider.chain_id().await.map_err(|e| eyre!(e))?;
-        let cfg = ROLLUP_CONFIGS
-            .get(&l2_chain_id)
-            .cloned()
-            .ok_or_else(|| eyre!("No rollup config found for L2 chain ID: {}", l2_chain_id))?;
-        Ok(cfg)
+        self.rollup.config(l2_chain_id)
     }
 
     /// Returns the l1 rpc url from CLI or environment variable.
@@ -323,7 +554,7 @@ impl FromL2 {
     }
 
     /// Returns the beacon url from CLI or environment variable.
-    pub fn beacon_url(&self) -> String {
+    pub fn beacon_url(&self) -> Option<String> {
         self.beacon_url.clone()
     }
 }
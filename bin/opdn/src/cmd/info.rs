@@ -1,25 +1,58 @@
 //! Info Module
 
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    parse::parse_block_number_or_tag,
+    rpc::block_number,
+    util::AnyFixture,
+};
+use alloy_eips::BlockNumberOrTag;
 use clap::{ArgAction, Parser};
 use color_eyre::eyre::{eyre, Result};
 use kona_derive::online::AlloyL2ChainProvider;
 use kona_derive::traits::L2ChainProvider;
+use op_test_vectors::{
+    anchor_state::AnchorStateFixture, building::BlockBuildingFixture,
+    derivation::DerivationFixture, execution::ExecutionFixture, l2::IntoFixture,
+};
 use reqwest::Url;
-use std::sync::Arc;
+use serde_json::json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use superchain_registry::ROLLUP_CONFIGS;
 
 /// CLI arguments for the `info` subcommand of `opdn`.
 #[derive(Parser, Clone, Debug)]
 pub struct Info {
     /// The L2 Chain ID
-    #[clap(long, help = "L2 chain ID")]
-    l2_chain_id: u64,
-    /// The L2 block number to get info for
-    #[clap(long, help = "L2 block number")]
-    l2_block: u64,
+    #[clap(long, required_unless_present = "fixture", help = "L2 chain ID")]
+    l2_chain_id: Option<u64>,
+    /// The L2 block number or tag to get info for
+    #[clap(
+        long,
+        required_unless_present = "fixture",
+        value_parser = parse_block_number_or_tag,
+        help = "L2 block number or tag (latest, finalized, safe)"
+    )]
+    l2_block: Option<BlockNumberOrTag>,
     /// The rpc url to fetch L2 block info from.
-    #[clap(long, help = "RPC url to fetch L2 block info from")]
-    rpc_url: String,
+    #[clap(
+        long,
+        required_unless_present = "fixture",
+        help = "RPC url to fetch L2 block info from"
+    )]
+    rpc_url: Option<String>,
+    /// A fixture to summarize instead of fetching block info.
+    #[clap(
+        long,
+        conflicts_with_all = ["l2_chain_id", "l2_block", "rpc_url"],
+        help = "Summarize a fixture file (.json, .yaml or .yml) of any type instead"
+    )]
+    fixture: Option<PathBuf>,
     /// Verbosity level (0-4)
     #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
     pub v: u8,
@@ -28,17 +61,217 @@ pub struct Info {
 impl Info {
     /// Runs the info subcommand.
     pub async fn run(&self) -> Result<()> {
-        let url = Url::parse(&self.rpc_url).map_err(|e| eyre!("Invalid RPC URL: {}", e))?;
+        if let Some(path) = &self.fixture {
+            return summarize(path);
+        }
+        // Clap requires these whenever no fixture is given.
+        let (Some(l2_chain_id), Some(l2_block), Some(rpc_url)) =
+            (self.l2_chain_id, self.l2_block, &self.rpc_url)
+        else {
+            return Err(eyre!("Missing --l2-chain-id, --l2-block or --rpc-url"));
+        };
+        let url = Url::parse(rpc_url).map_err(|e| eyre!("Invalid RPC URL: {}", e))?;
         let rollup_config = ROLLUP_CONFIGS
-            .get(&self.l2_chain_id)
-            .ok_or_else(|| eyre!("No rollup config found for chain id: {}", self.l2_chain_id))?;
+            .get(&l2_chain_id)
+            .ok_or_else(|| eyre!("No rollup config found for chain id: {}", l2_chain_id))?;
         let rollup_config = Arc::new(rollup_config.clone());
+        let l2_block = block_number(&reqwest::Client::new(), &url, l2_block).await?;
         let mut provider = AlloyL2ChainProvider::new_http(url, rollup_config);
         let info = provider
-            .l2_block_info_by_number(self.l2_block)
+            .l2_block_info_by_number(l2_block)
             .await
             .map_err(|e| eyre!("Failed to fetch block info: {}", e))?;
-        println!("{:#?}", info);
+        envelope::human(format_args!("{:#?}", info));
+        envelope::metric("l2BlockInfo", info.into_fixture());
         Ok(())
     }
 }
+
+/// Prints a summary of a fixture of any known type, without its data.
+///
+/// The fixture type is detected by loading it as each type in turn, as `doctor` does.
+fn summarize(path: &Path) -> Result<()> {
+    let size = fs::metadata(path)
+        .map_err(|e| eyre!("Failed to read {:?}: {e}", path))
+        .exit_code(ExitCode::Environment)?
+        .len();
+    let (fixture, _) = AnyFixture::load(path).exit_code(ExitCode::FixtureInvalid)?;
+    envelope::metric("size", size);
+    envelope::metric("fixtureType", fixture.kind());
+    match &fixture {
+        AnyFixture::Derivation(fixture) => summarize_derivation(path, size, fixture),
+        AnyFixture::Execution(fixture) => summarize_execution(path, size, fixture),
+        AnyFixture::AnchorState(fixture) => summarize_anchor_state(path, size, fixture),
+        AnyFixture::BlockBuilding(fixture) => summarize_block_building(path, size, fixture),
+    }
+    Ok(())
+}
+
+/// Prints a summary of a derivation fixture.
+fn summarize_derivation(path: &Path, size: u64, fixture: &DerivationFixture) {
+    let summary = fixture.summary();
+
+    envelope::human(format_args!(
+        "{}: derivation fixture {}, {size} bytes",
+        path.display(),
+        summary.version
+    ));
+    envelope::human(format_args!(
+        "  chains:    L1 {}, L2 {}",
+        summary.l1_chain_id, summary.l2_chain_id
+    ));
+    let l1_range = summary
+        .l1_range
+        .map_or_else(String::new, |(first, last)| format!(" {first}..={last}"));
+    envelope::human(format_args!(
+        "  L1 blocks: {}{l1_range}, {} transactions ({} bytes), {} blobs ({} bytes)",
+        summary.l1_blocks,
+        summary.transactions,
+        summary.transaction_bytes,
+        summary.blobs,
+        summary.blob_bytes
+    ));
+    let derived = fixture.l2_derived_range();
+    envelope::human(format_args!(
+        "  L2 blocks: {}..={} after cursor {}, {} payloads",
+        derived.start(),
+        derived.end(),
+        summary.l2_range.0,
+        summary.l2_payloads
+    ));
+    let activations = if summary.activations.is_empty() {
+        String::new()
+    } else {
+        format!(", activating {}", summary.activations.join(", "))
+    };
+    envelope::human(format_args!(
+        "  forks:     {}{activations}",
+        summary.forks.join(", ")
+    ));
+
+    envelope::metric("summary", &summary);
+}
+
+/// Prints a summary of an execution fixture.
+fn summarize_execution(path: &Path, size: u64, fixture: &ExecutionFixture) {
+    // Fixtures from before per-block environments only describe a single block.
+    let blocks: Vec<u64> = if fixture.block_environments.is_empty() {
+        vec![fixture.env.current_number.saturating_to()]
+    } else {
+        fixture
+            .block_environments
+            .iter()
+            .map(|env| env.current_number.saturating_to())
+            .collect()
+    };
+    let (first, last) = (blocks[0], blocks[blocks.len() - 1]);
+    let chain_id: Option<u64> = fixture.env.chain_id.map(|id| id.saturating_to());
+
+    envelope::human(format_args!(
+        "{}: execution fixture, {size} bytes",
+        path.display()
+    ));
+    envelope::human(format_args!(
+        "  chain:        {}",
+        chain_id.map_or_else(|| "unknown".to_string(), |id| id.to_string())
+    ));
+    envelope::human(format_args!(
+        "  blocks:       {} {first}..={last}",
+        blocks.len()
+    ));
+    envelope::human(format_args!(
+        "  transactions: {}, {} receipts, {} expectations",
+        fixture.transactions.len(),
+        fixture.result.receipts.len(),
+        fixture.expectations.len()
+    ));
+    envelope::human(format_args!(
+        "  accounts:     {} in the pre-state, {} in the post-state",
+        fixture.alloc.len(),
+        fixture.out_alloc.len()
+    ));
+
+    envelope::metric(
+        "summary",
+        json!({
+            "chainId": chain_id,
+            "blocks": blocks.len(),
+            "blockRange": [first, last],
+            "transactions": fixture.transactions.len(),
+            "receipts": fixture.result.receipts.len(),
+            "expectations": fixture.expectations.len(),
+            "preStateAccounts": fixture.alloc.len(),
+            "postStateAccounts": fixture.out_alloc.len(),
+        }),
+    );
+}
+
+/// Prints a summary of an anchor state fixture.
+fn summarize_anchor_state(path: &Path, size: u64, fixture: &AnchorStateFixture) {
+    envelope::human(format_args!(
+        "{}: anchor state fixture, {size} bytes",
+        path.display()
+    ));
+    envelope::human(format_args!(
+        "  chain:    L2 {}, registry {}",
+        fixture.l2_chain_id, fixture.anchor_state_registry
+    ));
+    envelope::human(format_args!(
+        "  L1 block: {} ({})",
+        fixture.l1_block_number, fixture.l1_block_hash
+    ));
+    for (game_type, anchor) in &fixture.anchors {
+        envelope::human(format_args!(
+            "  anchor:   game type {game_type} at L2 block {}, root {}",
+            anchor.l2_block_number, anchor.root
+        ));
+    }
+
+    envelope::metric(
+        "summary",
+        json!({
+            "l2ChainId": fixture.l2_chain_id,
+            "l1BlockNumber": fixture.l1_block_number,
+            "anchors": fixture.anchors.len(),
+        }),
+    );
+}
+
+/// Prints a summary of a block building fixture.
+fn summarize_block_building(path: &Path, size: u64, fixture: &BlockBuildingFixture) {
+    let mempool_bytes: usize = fixture.mempool.iter().map(|tx| tx.len()).sum();
+    let da_limit = fixture
+        .policy
+        .max_da_bytes
+        .map_or_else(|| "none".to_string(), |limit| format!("{limit} bytes"));
+
+    envelope::human(format_args!(
+        "{}: block building fixture, {size} bytes",
+        path.display()
+    ));
+    envelope::human(format_args!(
+        "  policy:   gas limit {}, DA limit {da_limit}",
+        fixture.policy.gas_limit
+    ));
+    envelope::human(format_args!(
+        "  mempool:  {} transactions ({mempool_bytes} bytes)",
+        fixture.mempool.len()
+    ));
+    envelope::human(format_args!(
+        "  expected: {} included, {} excluded",
+        fixture.expected.transactions.len(),
+        fixture.expected.excluded.len()
+    ));
+
+    envelope::metric(
+        "summary",
+        json!({
+            "gasLimit": fixture.policy.gas_limit,
+            "maxDaBytes": fixture.policy.max_da_bytes,
+            "mempool": fixture.mempool.len(),
+            "mempoolBytes": mempool_bytes,
+            "included": fixture.expected.transactions.len(),
+            "excluded": fixture.expected.excluded.len(),
+        }),
+    );
+}
@@ -0,0 +1,123 @@
+//! Advisory locks that keep parallel `opdn` runs from writing the same output.
+
+use crate::cmd::exit::{ExitCode, WithExitCode};
+use color_eyre::eyre::{eyre, Result};
+use fs4::fs_std::FileExt;
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// An advisory lock on a path, held by this process until it is dropped.
+///
+/// The lock is taken on a hidden sibling of the path, `.<name>.lock`, which holds the PID
+/// of the process holding the lock and is removed when the lock is released. The lock
+/// itself dies with the process, so a lock file left behind by a killed run does not
+/// block later ones.
+#[derive(Debug)]
+pub struct PathLock {
+    /// The lock file, removed on drop.
+    path: PathBuf,
+    /// The locked file. Closing it, after the lock file is removed, releases the lock.
+    _file: File,
+}
+
+impl PathLock {
+    /// Takes the lock on `path` without waiting.
+    ///
+    /// Fails with [ExitCode::Environment] if another process holds the lock.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let lock_path = lock_path(path)?;
+        if let Some(parent) = lock_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).exit_code(ExitCode::Environment)?;
+        }
+
+        loop {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&lock_path)
+                .map_err(|e| eyre!("Failed to open lock file {:?}: {e}", lock_path))
+                .exit_code(ExitCode::Environment)?;
+
+            if let Err(e) = file.try_lock_exclusive() {
+                if e.kind() != fs4::lock_contended_error().kind() {
+                    return Err(ExitCode::Environment
+                        .classify(eyre!("Failed to lock {:?}: {e}", lock_path)));
+                }
+                let holder = fs::read_to_string(&lock_path).unwrap_or_default();
+                let holder = match holder.trim() {
+                    "" => "another process".to_string(),
+                    pid => format!("PID {pid}"),
+                };
+                return Err(ExitCode::Environment.classify(eyre!(
+                    "Resource busy: {:?} is locked by {holder} (lock file {:?})",
+                    path,
+                    lock_path
+                )));
+            }
+
+            // The previous holder removes the lock file before releasing the lock, so a
+            // file opened before that is no longer the lock. Retry with the current one.
+            if !is_current(&file, &lock_path).exit_code(ExitCode::Environment)? {
+                continue;
+            }
+
+            // Record the holder for the error reported to other processes.
+            file.set_len(0)
+                .and_then(|_| write!(file, "{}", process::id()))
+                .exit_code(ExitCode::Environment)?;
+            return Ok(Self {
+                path: lock_path,
+                _file: file,
+            });
+        }
+    }
+}
+
+impl Drop for PathLock {
+    fn drop(&mut self) {
+        // Removed while the lock is still held, so no other process can take a lock on
+        // the file after it is released.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Returns the lock file for `path`, `.<name>.lock` in the same directory.
+fn lock_path(path: &Path) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| eyre!("Cannot lock {:?}, which has no file name", path))
+        .exit_code(ExitCode::Environment)?;
+    let mut lock_name = OsString::from(".");
+    lock_name.push(name);
+    lock_name.push(".lock");
+    Ok(path.with_file_name(lock_name))
+}
+
+/// Returns whether `file` is still the file at `path`, rather than one its previous
+/// holder removed after it was opened.
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let current = match fs::metadata(path) {
+        Ok(current) => current,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    let locked = file.metadata()?;
+    Ok(locked.dev() == current.dev() && locked.ino() == current.ino())
+}
+
+/// Returns whether `file` is still the file at `path`, rather than one its previous
+/// holder removed after it was opened.
+#[cfg(not(unix))]
+fn is_current(_file: &File, path: &Path) -> io::Result<bool> {
+    Ok(path.exists())
+}
@@ -0,0 +1,79 @@
+//! Contains logic to combine derivation fixtures of the same chain into one.
+
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    output::{OutputArgs, TemplateValues},
+};
+use clap::{ArgAction, Parser};
+use color_eyre::{eyre::eyre, Result};
+use op_test_vectors::{derivation::DerivationFixture, format::load};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "merge";
+
+/// CLI arguments for the `merge` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct Merge {
+    /// The fixtures to merge, in JSON or YAML.
+    #[clap(
+        required = true,
+        num_args = 2..,
+        help = "Fixture files to merge (.json, .yaml or .yml)"
+    )]
+    pub fixtures: Vec<PathBuf>,
+    /// The output location for the merged fixture.
+    #[command(flatten)]
+    pub output: OutputArgs,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+impl Merge {
+    /// Runs the merge.
+    ///
+    /// Fixtures are merged in order of their starting L2 cursor, so they can be passed in
+    /// any order as long as together they cover a contiguous range.
+    pub async fn run(&self) -> Result<()> {
+        self.output.precheck()?;
+        let mut fixtures = self
+            .fixtures
+            .iter()
+            .map(|path| {
+                load::<DerivationFixture>(path)
+                    .map(|fixture| (path, fixture))
+                    .map_err(|e| eyre!("Failed to load {:?}: {e}", path))
+                    .exit_code(ExitCode::FixtureInvalid)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        fixtures.sort_by_key(|(_, fixture)| fixture.l2_cursor_start);
+
+        let mut fixtures = fixtures.into_iter();
+        let (first, mut merged) = fixtures
+            .next()
+            .ok_or_else(|| eyre!("No fixtures provided"))?;
+        debug!(target: TARGET, "Starting from {:?}", first);
+        for (path, fixture) in fixtures {
+            debug!(target: TARGET, "Merging {:?}", path);
+            merged = merged
+                .merge(fixture)
+                .map_err(|e| eyre!("Failed to merge {:?}: {e}", path))
+                .exit_code(ExitCode::FixtureInvalid)?;
+        }
+        info!(target: TARGET,
+            "Merged {} fixtures covering L2 blocks [{}, {}]",
+            self.fixtures.len(), merged.l2_cursor_start, merged.l2_cursor_end
+        );
+
+        let values = TemplateValues::from_derivation_fixture(&merged);
+        let path = self.output.write(&values, &merged)?;
+        envelope::metric("merged", self.fixtures.len());
+        envelope::fixture_metrics(&merged);
+        info!(target: TARGET, "Wrote merged fixture to: {:?}", path);
+
+        Ok(())
+    }
+}
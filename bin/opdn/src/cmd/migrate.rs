@@ -0,0 +1,122 @@
+//! Contains the `migrate` subcommand, which upgrades derivation fixtures to the current
+//! schema version.
+
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    lock::PathLock,
+    output::write_fixture,
+    util::fixture_files,
+};
+use clap::{ArgAction, Parser};
+use color_eyre::{eyre::eyre, Result};
+use op_test_vectors::{
+    derivation::DerivationFixture,
+    format::{load, Format, SchemaVersion},
+};
+use serde_json::Value;
+use std::path::PathBuf;
+use tracing::info;
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "migrate";
+
+/// CLI arguments for the `migrate` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct Migrate {
+    /// The fixtures to migrate, or directories to search for them.
+    #[clap(
+        required = true,
+        help = "Fixture files (.json, .yaml or .yml) or directories of them to migrate"
+    )]
+    pub fixtures: Vec<PathBuf>,
+    /// Only report outdated fixtures instead of rewriting them.
+    #[clap(long, help = "Fail if any fixture is outdated instead of rewriting it")]
+    pub check: bool,
+    /// Write fixtures that appear to contain secrets, warning about them instead.
+    #[clap(
+        long,
+        help = "Write fixtures even if they appear to contain secrets, warning instead"
+    )]
+    pub allow_secrets: bool,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+impl Migrate {
+    /// Migrates each fixture older than [SchemaVersion::CURRENT] and writes it back in
+    /// its own format.
+    ///
+    /// Fixtures that fail to load or to migrate, e.g. because they were written by a
+    /// newer `opdn`, fail the command after every fixture has been processed.
+    pub async fn run(&self) -> Result<()> {
+        let mut files = Vec::new();
+        for path in &self.fixtures {
+            if path.is_dir() {
+                files.extend(fixture_files(path).exit_code(ExitCode::Environment)?);
+            } else {
+                files.push(path.clone());
+            }
+        }
+
+        let (mut outdated, mut failed) = (Vec::new(), 0);
+        for path in &files {
+            info!(target: TARGET, "Migrating {:?}", path);
+            // Hold the lock from the load, so the rewrite can't drop a concurrent write.
+            let _lock = (!self.check).then(|| PathLock::acquire(path)).transpose()?;
+            let migrated = load::<Value>(path).and_then(DerivationFixture::migrate);
+            let (fixture, from) = match migrated {
+                Ok(migrated) => migrated,
+                Err(e) => {
+                    envelope::human(format_args!("[fail] {}: {e}", path.display()));
+                    failed += 1;
+                    continue;
+                }
+            };
+            if from == SchemaVersion::CURRENT {
+                continue;
+            }
+
+            if self.check {
+                envelope::human(format_args!(
+                    "[outdated] {}: {from}, current is {}",
+                    path.display(),
+                    SchemaVersion::CURRENT
+                ));
+            } else {
+                write_fixture(path, &fixture, Format::from_path(path), self.allow_secrets)?;
+                envelope::human(format_args!(
+                    "[migrated] {}: {from} -> {}",
+                    path.display(),
+                    SchemaVersion::CURRENT
+                ));
+                envelope::artifact(path);
+            }
+            outdated.push(path.clone());
+        }
+
+        envelope::metric("fixtures", files.len());
+        envelope::metric(if self.check { "outdated" } else { "migrated" }, &outdated);
+        if failed > 0 {
+            return Err(ExitCode::FixtureInvalid.classify(eyre!(
+                "Failed to migrate {failed} of {} fixtures",
+                files.len()
+            )));
+        }
+        if self.check && !outdated.is_empty() {
+            return Err(ExitCode::FixtureInvalid.classify(eyre!(
+                "{} of {} fixtures are older than {}",
+                outdated.len(),
+                files.len(),
+                SchemaVersion::CURRENT
+            )));
+        }
+        envelope::human(format_args!(
+            "All {} fixtures are at {}",
+            files.len(),
+            SchemaVersion::CURRENT
+        ));
+        Ok(())
+    }
+}
@@ -2,20 +2,51 @@
 
 use clap::Parser;
 use color_eyre::eyre::{eyre, Result};
-use tracing::Level;
+use tracing::{level_filters::LevelFilter, Level};
+use tracing_subscriber::{
+    fmt::{self, writer::BoxMakeWriter},
+    layer::SubscriberExt,
+    Layer,
+};
 
+pub mod anchor_state;
 pub mod blobs;
+pub mod convert;
+pub mod dedupe;
+pub mod doctor;
+pub mod envelope;
+pub mod exit;
 pub mod fixtures;
 pub mod from_l1;
 pub mod from_l2;
 pub mod info;
+pub mod lock;
+pub mod merge;
+pub mod migrate;
+pub mod only;
+pub mod output;
+pub mod parse;
+pub mod patch;
+pub mod plan;
+pub mod providers;
+pub mod rollup;
+pub mod rpc;
+pub mod run_derivation;
 pub mod util;
+pub mod validate;
 pub use fixtures::build_fixture_blocks;
 
 /// Main CLI
 #[derive(Parser, Clone, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
+    /// Print a JSON result envelope on stdout, and everything else on stderr.
+    #[arg(
+        long,
+        global = true,
+        help = "Print the result as a single JSON object on stdout, and logs on stderr"
+    )]
+    pub output_json: bool,
     /// Subcommands for the CLI
     #[command(subcommand)]
     pub command: Commands,
@@ -30,6 +61,24 @@ pub enum Commands {
     FromL1(from_l1::FromL1),
     /// Gets the L2 block info including the l1 origin for the l2 block number.
     Info(info::Info),
+    /// Converts a derivation fixture between JSON and YAML.
+    Convert(convert::Convert),
+    /// Checks RPC endpoints, directories and existing fixtures for common problems.
+    Doctor(doctor::Doctor),
+    /// Creates an anchor state fixture from an on-chain anchor state registry.
+    AnchorState(anchor_state::AnchorState),
+    /// Applies field overrides to a derivation fixture.
+    Patch(patch::Patch),
+    /// Combines derivation fixtures of the same chain into one.
+    Merge(merge::Merge),
+    /// Upgrades derivation fixtures to the current schema version.
+    Migrate(migrate::Migrate),
+    /// Finds derivation fixtures that capture the same inputs.
+    Dedupe(dedupe::Dedupe),
+    /// Reports every consistency violation in derivation fixtures.
+    Validate(validate::Validate),
+    /// Runs the derivation pipeline over a derivation fixture and checks what it derives.
+    RunDerivation(run_derivation::RunDerivation),
 }
 
 impl Cli {
@@ -39,21 +88,60 @@ impl Cli {
             Commands::FromL2(cmd) => cmd.v,
             Commands::FromL1(cmd) => cmd.v,
             Commands::Info(cmd) => cmd.v,
+            Commands::Convert(cmd) => cmd.v,
+            Commands::AnchorState(cmd) => cmd.v,
+            Commands::Doctor(cmd) => cmd.v,
+            Commands::Patch(cmd) => cmd.v,
+            Commands::Merge(cmd) => cmd.v,
+            Commands::Migrate(cmd) => cmd.v,
+            Commands::Dedupe(cmd) => cmd.v,
+            Commands::Validate(cmd) => cmd.v,
+            Commands::RunDerivation(cmd) => cmd.v,
+        }
+    }
+
+    /// Returns the name of the subcommand, as passed on the command line.
+    pub fn name(&self) -> &'static str {
+        match &self.command {
+            Commands::FromL2(_) => "from-l2",
+            Commands::FromL1(_) => "from-l1",
+            Commands::Info(_) => "info",
+            Commands::Convert(_) => "convert",
+            Commands::AnchorState(_) => "anchor-state",
+            Commands::Doctor(_) => "doctor",
+            Commands::Patch(_) => "patch",
+            Commands::Merge(_) => "merge",
+            Commands::Migrate(_) => "migrate",
+            Commands::Dedupe(_) => "dedupe",
+            Commands::Validate(_) => "validate",
+            Commands::RunDerivation(_) => "run-derivation",
         }
     }
 
     /// Initializes telemtry for the application.
     pub fn init_telemetry(self) -> Result<Self> {
         color_eyre::install()?;
-        let subscriber = tracing_subscriber::fmt()
-            .with_max_level(match self.v() {
-                0 => Level::ERROR,
-                1 => Level::WARN,
-                2 => Level::INFO,
-                3 => Level::DEBUG,
-                _ => Level::TRACE,
-            })
-            .finish();
+        let level = match self.v() {
+            0 => Level::ERROR,
+            1 => Level::WARN,
+            2 => Level::INFO,
+            3 => Level::DEBUG,
+            _ => Level::TRACE,
+        };
+        // Stdout is reserved for the result envelope when it is printed.
+        let writer = if self.output_json {
+            envelope::enable();
+            BoxMakeWriter::new(std::io::stderr)
+        } else {
+            BoxMakeWriter::new(std::io::stdout)
+        };
+        let subscriber = tracing_subscriber::registry()
+            .with(
+                fmt::layer()
+                    .with_writer(writer)
+                    .with_filter(LevelFilter::from_level(level)),
+            )
+            .with(envelope::Warnings.with_filter(LevelFilter::WARN));
         tracing::subscriber::set_global_default(subscriber).map_err(|e| eyre!(e))?;
         Ok(self)
     }
@@ -64,6 +152,15 @@ impl Cli {
             Commands::FromL2(cmd) => cmd.run().await,
             Commands::FromL1(cmd) => cmd.run().await,
             Commands::Info(cmd) => cmd.run().await,
+            Commands::Convert(cmd) => cmd.run().await,
+            Commands::AnchorState(cmd) => cmd.run().await,
+            Commands::Doctor(cmd) => cmd.run().await,
+            Commands::Patch(cmd) => cmd.run().await,
+            Commands::Merge(cmd) => cmd.run().await,
+            Commands::Migrate(cmd) => cmd.run().await,
+            Commands::Dedupe(cmd) => cmd.run().await,
+            Commands::Validate(cmd) => cmd.run().await,
+            Commands::RunDerivation(cmd) => cmd.run().await,
         }
     }
 }
@@ -0,0 +1,155 @@
+//! Contains the `--only` controls for regenerating components of an existing fixture.
+
+use crate::cmd::exit::{ExitCode, WithExitCode};
+use clap::{Args, ValueEnum};
+use color_eyre::{eyre::eyre, Result};
+use op_test_vectors::{derivation::DerivationFixture, format::load};
+use std::path::PathBuf;
+
+/// A component of a derivation fixture that can be regenerated on its own.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Component {
+    /// The L1 blocks, with their transactions, receipts and blobs.
+    L1Blocks,
+    /// The blobs of the existing L1 blocks.
+    Blobs,
+    /// The payload attributes derived by the pipeline.
+    L2Payloads,
+    /// The reference payloads used for span batch validation.
+    RefPayloads,
+    /// The L2 system configs.
+    SystemConfigs,
+    /// The L2 block infos.
+    BlockInfos,
+}
+
+impl Component {
+    /// Returns whether the component is collected while running the derivation pipeline.
+    pub fn is_derived(self) -> bool {
+        matches!(
+            self,
+            Self::L2Payloads | Self::RefPayloads | Self::SystemConfigs | Self::BlockInfos
+        )
+    }
+}
+
+/// Arguments selecting the components of an existing fixture to regenerate.
+#[derive(Args, Clone, Debug)]
+pub struct OnlyArgs {
+    /// An existing fixture to reuse the components that are not regenerated from.
+    #[clap(
+        long,
+        requires = "only",
+        help = "Existing fixture to reuse the components not selected by --only from"
+    )]
+    pub base: Option<PathBuf>,
+    /// The components to regenerate.
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        requires = "base",
+        help = "Components to regenerate, reusing the rest from --base"
+    )]
+    pub only: Vec<Component>,
+}
+
+impl OnlyArgs {
+    /// Loads the base fixture, if one was given.
+    pub fn load(&self) -> Result<Only> {
+        let base = match &self.base {
+            Some(path) => Some(
+                load::<DerivationFixture>(path)
+                    .map_err(|e| eyre!("Failed to load base fixture {:?}: {e}", path))
+                    .exit_code(ExitCode::FixtureInvalid)?,
+            ),
+            None => None,
+        };
+        Ok(Only {
+            components: self.only.clone(),
+            base,
+        })
+    }
+}
+
+/// The components to regenerate, and the base fixture to reuse the others from.
+///
+/// Without a base fixture, every component is generated.
+#[derive(Debug, Default)]
+pub struct Only {
+    /// The components to regenerate.
+    components: Vec<Component>,
+    /// The fixture to reuse the other components from.
+    base: Option<DerivationFixture>,
+}
+
+impl Only {
+    /// Returns whether `component` is generated.
+    pub fn regenerates(&self, component: Component) -> bool {
+        self.base.is_none() || self.components.contains(&component)
+    }
+
+    /// Returns whether any component collected by the derivation pipeline is generated,
+    /// so the pipeline has to run.
+    pub fn runs_pipeline(&self) -> bool {
+        self.base.is_none() || self.components.iter().any(|c| c.is_derived())
+    }
+
+    /// Returns the base fixture, if components are reused from one.
+    pub fn base(&self) -> Option<&DerivationFixture> {
+        self.base.as_ref()
+    }
+
+    /// Fills every component of `fixture` that was not generated from the base fixture.
+    ///
+    /// Fails if the base fixture is for a different rollup config, or if the pipeline
+    /// ran over a different L2 range than the base fixture covers.
+    pub fn reuse(self, fixture: &mut DerivationFixture) -> Result<()> {
+        let runs_pipeline = self.runs_pipeline();
+        let Self { components, base } = self;
+        let Some(base) = base else {
+            return Ok(());
+        };
+        let reused = |component| !components.contains(&component);
+
+        if base.rollup_config != fixture.rollup_config {
+            return Err(ExitCode::Usage.classify(eyre!(
+                "Base fixture has a different rollup config than chain {}",
+                fixture.rollup_config.l2_chain_id
+            )));
+        }
+        if !runs_pipeline {
+            fixture.l2_cursor_start = base.l2_cursor_start;
+            fixture.l2_cursor_end = base.l2_cursor_end;
+        } else if (fixture.l2_cursor_start, fixture.l2_cursor_end)
+            != (base.l2_cursor_start, base.l2_cursor_end)
+        {
+            return Err(ExitCode::Usage.classify(eyre!(
+                "Base fixture covers L2 blocks [{}, {}], but [{}, {}] was derived",
+                base.l2_cursor_start,
+                base.l2_cursor_end,
+                fixture.l2_cursor_start,
+                fixture.l2_cursor_end
+            )));
+        }
+
+        if reused(Component::L1Blocks) && reused(Component::Blobs) {
+            fixture.l1_blocks = base.l1_blocks;
+            fixture.l1_beacon_config = base.l1_beacon_config;
+        }
+        if reused(Component::L2Payloads) {
+            fixture.l2_payloads = base.l2_payloads;
+        }
+        if reused(Component::RefPayloads) {
+            fixture.ref_payloads = base.ref_payloads;
+        }
+        if reused(Component::SystemConfigs) {
+            fixture.l2_system_configs = base.l2_system_configs;
+        }
+        if reused(Component::BlockInfos) {
+            fixture.l2_block_infos = base.l2_block_infos;
+        }
+        fixture.description = fixture.description.take().or(base.description);
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+//! Output path resolution for generated fixtures.
+
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    lock::PathLock,
+};
+use clap::Args;
+use color_eyre::eyre::{bail, eyre, Result};
+use op_test_vectors::{derivation::DerivationFixture, format::Format, secrets};
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tracing::warn;
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "output";
+
+/// The placeholders supported in output templates.
+const PLACEHOLDERS: [&str; 6] = ["chain", "type", "l1_start", "l1_end", "l2_start", "l2_end"];
+
+/// Output arguments shared by the fixture generation subcommands.
+#[derive(Args, Clone, Debug)]
+pub struct OutputArgs {
+    /// The output file for the test fixture.
+    #[clap(
+        long,
+        help = "Output file for the test fixture",
+        required_unless_present = "output_template",
+        conflicts_with = "output_template"
+    )]
+    pub output: Option<PathBuf>,
+    /// A template for the output file, relative to the current directory.
+    ///
+    /// Supported placeholders are `{chain}`, `{type}`, `{l1_start}`, `{l1_end}`,
+    /// `{l2_start}` and `{l2_end}`.
+    #[clap(
+        long,
+        help = "Output file template, e.g. \"{chain}/{type}/{l2_start}-{l2_end}.json\""
+    )]
+    pub output_template: Option<String>,
+    /// Overwrite the output file if it already exists.
+    #[clap(long, help = "Overwrite the output file if it already exists")]
+    pub force: bool,
+    /// Programs to run on the fixture once it is written, in order.
+    #[clap(
+        long = "post-process",
+        value_name = "PROGRAM",
+        help = "Program to run on the written fixture, given its path; may be repeated"
+    )]
+    pub post_processors: Vec<PathBuf>,
+    /// Write fixtures that appear to contain secrets, warning about them instead.
+    #[clap(
+        long,
+        help = "Write the fixture even if it appears to contain secrets, warning instead"
+    )]
+    pub allow_secrets: bool,
+}
+
+/// The values substituted into an output template.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateValues {
+    /// The L2 chain ID.
+    pub chain: u64,
+    /// The fixture type, e.g. `derivation`.
+    pub fixture_type: &'static str,
+    /// The first L1 block in the fixture.
+    pub l1_start: u64,
+    /// The last L1 block in the fixture.
+    pub l1_end: u64,
+    /// The first L2 block in the fixture, which for derivation fixtures is the first
+    /// block derived after the starting cursor.
+    pub l2_start: u64,
+    /// The last L2 block in the fixture.
+    pub l2_end: u64,
+}
+
+impl TemplateValues {
+    /// Returns the template values describing an existing derivation fixture.
+    pub fn from_derivation_fixture(fixture: &DerivationFixture) -> Self {
+        Self {
+            chain: fixture.rollup_config.l2_chain_id,
+            fixture_type: "derivation",
+            l1_start: fixture.l1_blocks.first().map_or(0, |b| b.header.number),
+            l1_end: fixture.l1_blocks.last().map_or(0, |b| b.header.number),
+            l2_start: *fixture.l2_derived_range().start(),
+            l2_end: fixture.l2_cursor_end,
+        }
+    }
+
+    /// Records the values as metrics of the result envelope.
+    pub fn record(&self) {
+        envelope::metric("chainId", self.chain);
+        envelope::metric("fixtureType", self.fixture_type);
+        envelope::metric("l1Start", self.l1_start);
+        envelope::metric("l1End", self.l1_end);
+        envelope::metric("l2Start", self.l2_start);
+        envelope::metric("l2End", self.l2_end);
+    }
+
+    /// Returns the values as `OPDN_`-prefixed environment variables, e.g. `OPDN_L2_START`.
+    fn env(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        PLACEHOLDERS.into_iter().map(|name| {
+            let value = self.get(name).expect("known placeholder");
+            (format!("OPDN_{}", name.to_ascii_uppercase()), value)
+        })
+    }
+
+    /// Returns the value for the given placeholder name.
+    fn get(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "chain" => self.chain.to_string(),
+            "type" => self.fixture_type.to_string(),
+            "l1_start" => self.l1_start.to_string(),
+            "l1_end" => self.l1_end.to_string(),
+            "l2_start" => self.l2_start.to_string(),
+            "l2_end" => self.l2_end.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+impl OutputArgs {
+    /// Checks that an explicit output path can be written before any work is done.
+    ///
+    /// Templated paths are only known once the fixture is built, so they are checked
+    /// in [OutputArgs::resolve].
+    pub fn precheck(&self) -> Result<()> {
+        match &self.output {
+            Some(output) => self.check_overwrite(output),
+            None => Ok(()),
+        }
+    }
+
+    /// Resolves the output path, rendering the template if one was given.
+    ///
+    /// Fails if the path already exists and `--force` was not passed.
+    pub fn resolve(&self, values: &TemplateValues) -> Result<PathBuf> {
+        let path = match (&self.output, &self.output_template) {
+            (Some(output), _) => output.clone(),
+            (None, Some(template)) => render(template, values)?,
+            (None, None) => bail!("Either --output or --output-template must be provided"),
+        };
+        self.check_overwrite(&path)?;
+        Ok(path)
+    }
+
+    /// Resolves the output path and takes the lock on it, so no other `opdn` run writes
+    /// the same path until the returned lock is dropped.
+    ///
+    /// Fails if another run holds the lock, or if the path already exists and `--force`
+    /// was not passed.
+    pub fn lock(&self, values: &TemplateValues) -> Result<(PathBuf, PathLock)> {
+        let path = self.resolve(values)?;
+        let lock = PathLock::acquire(&path)?;
+        // Another run may have written the path between resolving and locking it.
+        self.check_overwrite(&path)?;
+        Ok((path, lock))
+    }
+
+    /// Fails if `path` already exists and `--force` was not passed.
+    fn check_overwrite(&self, path: &Path) -> Result<()> {
+        if !self.force && path.exists() {
+            return Err(ExitCode::Environment.classify(eyre!(
+                "Output file already exists: {:?}, pass --force to overwrite it",
+                path
+            )));
+        }
+        Ok(())
+    }
+
+    /// Resolves the output path and writes the fixture to it once it has been scanned for
+    /// secrets, in YAML if the path has a `.yaml` or `.yml` extension and as pretty-printed
+    /// JSON otherwise, then runs the post-processors on it, holding the lock on the path
+    /// throughout.
+    ///
+    /// The path and template values are recorded in the result envelope.
+    pub fn write<T: Serialize>(&self, values: &TemplateValues, fixture: &T) -> Result<PathBuf> {
+        let (path, _lock) = self.lock(values)?;
+        write_fixture(&path, fixture, Format::from_path(&path), self.allow_secrets)?;
+        envelope::artifact(&path);
+        values.record();
+        self.post_process(&path, values)?;
+        Ok(path)
+    }
+
+    /// Runs each `--post-process` program on a written fixture in order, stopping at the
+    /// first that fails.
+    ///
+    /// Programs are given the fixture path as their only argument, and the template
+    /// values as `OPDN_*` environment variables, so validators, exporters and uploaders
+    /// can be added without changing `opdn`.
+    pub fn post_process(&self, path: &Path, values: &TemplateValues) -> Result<()> {
+        for program in &self.post_processors {
+            let status = Command::new(program)
+                .arg(path)
+                .envs(values.env())
+                .status()
+                .map_err(|e| {
+                    ExitCode::Environment
+                        .classify(eyre!("Failed to run post-processor {:?}: {e}", program))
+                })?;
+            if !status.success() {
+                return Err(ExitCode::PostProcess.classify(eyre!(
+                    "Post-processor {:?} failed on {:?}: {status}",
+                    program,
+                    path
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Scans a fixture for secrets before it is written, failing if any are found unless
+/// `allow_secrets` is set, in which case each is logged as a warning.
+pub fn check_secrets<T: Serialize>(fixture: &T, allow_secrets: bool) -> Result<()> {
+    let findings = secrets::scan(fixture)?;
+    if findings.is_empty() {
+        return Ok(());
+    }
+    if allow_secrets {
+        for finding in &findings {
+            warn!(target: TARGET, "Fixture contains a possible secret: {}", finding);
+        }
+        return Ok(());
+    }
+    let findings: Vec<_> = findings.iter().map(ToString::to_string).collect();
+    Err(ExitCode::FixtureInvalid.classify(eyre!(
+        "Fixture contains possible secrets: {}; remove them or pass --allow-secrets",
+        findings.join(", ")
+    )))
+}
+
+/// Scans a fixture for secrets, see [check_secrets], then writes it in the given format,
+/// creating any missing parent directories.
+///
+/// Every fixture `opdn` writes goes through this, so none is written unscanned.
+pub fn write_fixture<T: Serialize>(
+    path: &Path,
+    fixture: &T,
+    format: Format,
+    allow_secrets: bool,
+) -> Result<()> {
+    check_secrets(fixture, allow_secrets)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).exit_code(ExitCode::Environment)?;
+    }
+    op_test_vectors::format::save(path, fixture, format).exit_code(ExitCode::Environment)
+}
+
+/// Renders an output template, substituting `{name}` placeholders.
+pub fn render(template: &str, values: &TemplateValues) -> Result<PathBuf> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| eyre!("Unclosed placeholder in output template: {template}"))?;
+        let name = &rest[start + 1..start + end];
+        let value = values
+            .get(name)
+            .ok_or_else(|| eyre!("Unknown placeholder in output template: {{{name}}}"))?;
+        out.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(PathBuf::from(out))
+}
@@ -0,0 +1,100 @@
+//! Typed value parsers for CLI arguments identifying chain data.
+//!
+//! Errors are returned as strings so clap can render them next to the offending argument.
+
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::{hex, Address, FixedBytes, B256};
+use op_test_vectors::quantity::parse_u64;
+
+/// Parses a `0x`-prefixed 32-byte hash.
+pub fn parse_b256(s: &str) -> Result<B256, String> {
+    parse_fixed_bytes(s, "32-byte hash")
+}
+
+/// Parses a `0x`-prefixed address, validating its checksum if it is mixed-case.
+pub fn parse_address(s: &str) -> Result<Address, String> {
+    let address = parse_fixed_bytes::<20>(s, "20-byte address")?;
+    let digits = &s[2..];
+    let mixed_case = digits.chars().any(|c| c.is_ascii_uppercase())
+        && digits.chars().any(|c| c.is_ascii_lowercase());
+    if mixed_case {
+        return Address::parse_checksummed(s, None)
+            .map_err(|_| "invalid address checksum, use all lowercase to skip it".to_string());
+    }
+    Ok(Address::from(address))
+}
+
+/// Parses a decimal or `0x`-prefixed block number, or one of the `latest`, `finalized` or
+/// `safe` tags.
+pub fn parse_block_number_or_tag(s: &str) -> Result<BlockNumberOrTag, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "latest" => Ok(BlockNumberOrTag::Latest),
+        "finalized" => Ok(BlockNumberOrTag::Finalized),
+        "safe" => Ok(BlockNumberOrTag::Safe),
+        _ => parse_u64(s).map(BlockNumberOrTag::Number).map_err(|_| {
+            "expected a block number, or one of latest, finalized or safe".to_string()
+        }),
+    }
+}
+
+/// Parses `0x`-prefixed hex of exactly `N` bytes.
+fn parse_fixed_bytes<const N: usize>(s: &str, expected: &str) -> Result<FixedBytes<N>, String> {
+    let digits = s
+        .strip_prefix("0x")
+        .ok_or_else(|| format!("expected a 0x-prefixed {expected}"))?;
+    let bytes = hex::decode(digits).map_err(|e| format!("invalid {expected}: {e}"))?;
+    FixedBytes::try_from(bytes.as_slice())
+        .map_err(|_| format!("expected a {expected}, got {} bytes", bytes.len()))
+}
+
+/// A block number or tag, offset by a number of blocks, e.g. `finalized-100`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RelativeBlock {
+    /// The block the offset is relative to.
+    pub base: BlockNumberOrTag,
+    /// The number of blocks to add to the base block.
+    pub offset: i64,
+}
+
+impl RelativeBlock {
+    /// Applies the offset to the resolved base block number.
+    pub fn resolve(&self, base: u64) -> Result<u64, String> {
+        base.checked_add_signed(self.offset)
+            .ok_or_else(|| format!("{base}{:+} is out of range", self.offset))
+    }
+}
+
+/// An inclusive range of blocks, e.g. `finalized-100..finalized`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockRange {
+    /// The first block in the range.
+    pub start: RelativeBlock,
+    /// The last block in the range.
+    pub end: RelativeBlock,
+}
+
+/// Parses a block number or tag with an optional `+` or `-` offset.
+pub fn parse_relative_block(s: &str) -> Result<RelativeBlock, String> {
+    let Some(split) = s.find(['+', '-']) else {
+        return parse_block_number_or_tag(s).map(|base| RelativeBlock { base, offset: 0 });
+    };
+    let (base, offset) = s.split_at(split);
+    let offset = offset
+        .parse::<i64>()
+        .map_err(|_| format!("invalid block offset {offset:?}"))?;
+    Ok(RelativeBlock {
+        base: parse_block_number_or_tag(base)?,
+        offset,
+    })
+}
+
+/// Parses a `<start>..<end>` range of relative blocks.
+pub fn parse_block_range(s: &str) -> Result<BlockRange, String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| "expected a range such as finalized-100..finalized".to_string())?;
+    Ok(BlockRange {
+        start: parse_relative_block(start)?,
+        end: parse_relative_block(end)?,
+    })
+}
@@ -0,0 +1,62 @@
+//! Contains logic to derive fixture variants by overriding fields of an existing fixture.
+
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    output::{OutputArgs, TemplateValues},
+};
+use clap::{ArgAction, Parser};
+use color_eyre::Result;
+use op_test_vectors::{
+    derivation::DerivationFixture,
+    format::load,
+    patch::{apply, Patch as FieldPatch},
+};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "patch";
+
+/// CLI arguments for the `patch` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct Patch {
+    /// The fixture to patch, in JSON or YAML.
+    #[clap(long, help = "Fixture file to patch (.json, .yaml or .yml)")]
+    pub fixture: PathBuf,
+    /// The field overrides to apply, in order.
+    #[clap(
+        long = "set",
+        required = true,
+        help = "Field override as <path>=<value>, e.g. rollupConfig.fjord_time=123"
+    )]
+    pub patches: Vec<FieldPatch>,
+    /// The output location for the patched fixture.
+    #[command(flatten)]
+    pub output: OutputArgs,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+impl Patch {
+    /// Runs the patch.
+    ///
+    /// The patched fixture is re-parsed into a [DerivationFixture] before being written,
+    /// so the output is canonical and overrides of unknown fields are rejected.
+    pub async fn run(&self) -> Result<()> {
+        self.output.precheck()?;
+        let fixture: DerivationFixture = load(&self.fixture).exit_code(ExitCode::FixtureInvalid)?;
+        for patch in &self.patches {
+            debug!(target: TARGET, "Setting {} to {}", patch.path(), patch.value);
+        }
+        let fixture = apply(&fixture, &self.patches).exit_code(ExitCode::FixtureInvalid)?;
+
+        let values = TemplateValues::from_derivation_fixture(&fixture);
+        let path = self.output.write(&values, &fixture)?;
+        envelope::metric("patches", self.patches.len());
+        info!(target: TARGET, "Wrote patched fixture to: {:?}", path);
+
+        Ok(())
+    }
+}
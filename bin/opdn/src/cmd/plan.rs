@@ -0,0 +1,44 @@
+//! Dry-run plans for the fixture generation subcommands.
+
+use std::fmt;
+
+/// The resolved inputs of a generation subcommand, printed by `--dry-run` in place of
+/// generating the fixture.
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+    /// The subcommand the plan is for.
+    command: &'static str,
+    /// The resolved inputs, in the order they were added.
+    entries: Vec<(&'static str, String)>,
+}
+
+impl Plan {
+    /// Creates an empty plan for the given subcommand.
+    pub fn new(command: &'static str) -> Self {
+        Self {
+            command,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds a resolved input to the plan.
+    pub fn with(mut self, name: &'static str, value: impl fmt::Display) -> Self {
+        self.entries.push((name, value.to_string()));
+        self
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Dry run of {}, no fixture was generated:", self.command)?;
+        let width = self.entries.iter().map(|(name, _)| name.len()).max();
+        for (name, value) in &self.entries {
+            writeln!(
+                f,
+                "  {name:<width$}  {value}",
+                width = width.unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+//! Providers that serve the data recorded in a derivation fixture to the derivation
+//! pipeline, so it can run without any RPC endpoint.
+
+use crate::cmd::blobs::fixture_block_blob_hashes;
+use alloy_consensus::{Header, Receipt, TxEnvelope};
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::B256;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use color_eyre::eyre::{self, ensure};
+use hashbrown::HashMap;
+use kona_derive::{
+    traits::{BlobProvider, ChainProvider, L2ChainProvider},
+    types::{
+        Blob, BlobProviderError, BlockInfo, IndexedBlobHash, L2BlockInfo,
+        L2ExecutionPayloadEnvelope, OpBlock, RollupConfig, SystemConfig,
+    },
+};
+use op_alloy_consensus::OpTxEnvelope;
+use op_test_vectors::{
+    derivation::{DerivationFixture, FixtureBlock},
+    l2::IntoKona,
+};
+use std::sync::Arc;
+
+/// Serves the L1 blocks of a derivation fixture.
+#[derive(Debug, Clone)]
+pub struct FixtureChainProvider {
+    /// The L1 blocks in order, with their block info.
+    blocks: Arc<Vec<(BlockInfo, FixtureBlock)>>,
+    /// The index of each L1 block in `blocks`, by hash.
+    by_hash: Arc<HashMap<B256, usize>>,
+}
+
+impl FixtureChainProvider {
+    /// Returns a provider of the fixture's L1 blocks, failing if their numbers are not
+    /// consecutive.
+    pub fn new(fixture: &DerivationFixture) -> eyre::Result<Self> {
+        let blocks = fixture
+            .l1_blocks_ordered()?
+            .map(|block| (block_info(&block.header), block.clone()))
+            .collect::<Vec<_>>();
+        let by_hash = blocks
+            .iter()
+            .enumerate()
+            .map(|(i, (info, _))| (info.hash, i))
+            .collect();
+        Ok(Self {
+            blocks: Arc::new(blocks),
+            by_hash: Arc::new(by_hash),
+        })
+    }
+
+    /// Returns the number of the last L1 block, if there are any.
+    pub fn last_block(&self) -> Option<u64> {
+        self.blocks.last().map(|(info, _)| info.number)
+    }
+
+    /// Returns the L1 block with the given hash.
+    fn block(&self, hash: B256) -> anyhow::Result<&(BlockInfo, FixtureBlock)> {
+        self.by_hash
+            .get(&hash)
+            .map(|&i| &self.blocks[i])
+            .ok_or_else(|| anyhow!("L1 block {hash} is not in the fixture"))
+    }
+}
+
+#[async_trait]
+impl ChainProvider for FixtureChainProvider {
+    async fn header_by_hash(&mut self, hash: B256) -> anyhow::Result<Header> {
+        Ok(self.block(hash)?.1.header.clone())
+    }
+
+    async fn block_info_by_number(&mut self, number: u64) -> anyhow::Result<BlockInfo> {
+        let first = self.blocks.first().map_or(0, |(info, _)| info.number);
+        number
+            .checked_sub(first)
+            .and_then(|i| self.blocks.get(i as usize))
+            .map(|(info, _)| *info)
+            .ok_or_else(|| anyhow!("L1 block {number} is not in the fixture"))
+    }
+
+    async fn receipts_by_hash(&mut self, hash: B256) -> anyhow::Result<Vec<Receipt>> {
+        Ok(self.block(hash)?.1.receipts.clone())
+    }
+
+    async fn block_info_and_transactions_by_hash(
+        &mut self,
+        hash: B256,
+    ) -> anyhow::Result<(BlockInfo, Vec<TxEnvelope>)> {
+        let (info, block) = self.block(hash)?;
+        let txs = block
+            .transactions
+            .iter()
+            .map(|tx| TxEnvelope::decode_2718(&mut tx.as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!("Invalid transaction in L1 block {}: {e}", info.number))?;
+        Ok((*info, txs))
+    }
+}
+
+/// Serves the blobs of a derivation fixture's L1 blocks.
+#[derive(Debug, Clone)]
+pub struct FixtureBlobProvider {
+    /// The blobs of each L1 block by block hash, with the versioned hashes they commit to.
+    blobs: Arc<HashMap<B256, Vec<(B256, Blob)>>>,
+}
+
+impl FixtureBlobProvider {
+    /// Returns a provider of the fixture's blobs, pairing the blobs of each L1 block with
+    /// the versioned hashes its batcher transactions commit to.
+    ///
+    /// Fails if a block does not have one blob for each of those hashes.
+    pub fn new(fixture: &DerivationFixture) -> eyre::Result<Self> {
+        let mut blobs = HashMap::new();
+        for block in &fixture.l1_blocks {
+            let hashes = fixture_block_blob_hashes(&fixture.rollup_config, block)?;
+            ensure!(
+                hashes.len() == block.blobs.len(),
+                "L1 block {} has {} blobs, but its batcher transactions commit to {}",
+                block.header.number,
+                block.blobs.len(),
+                hashes.len()
+            );
+            let paired = hashes
+                .iter()
+                .map(|indexed| indexed.hash)
+                .zip(block.blobs.iter().map(|blob| **blob))
+                .collect();
+            blobs.insert(block.header.hash_slow(), paired);
+        }
+        Ok(Self {
+            blobs: Arc::new(blobs),
+        })
+    }
+}
+
+#[async_trait]
+impl BlobProvider for FixtureBlobProvider {
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Blob>, BlobProviderError> {
+        let blobs = self
+            .blobs
+            .get(&block_ref.hash)
+            .map_or(&[][..], Vec::as_slice);
+        blob_hashes
+            .iter()
+            .map(|wanted| {
+                blobs
+                    .iter()
+                    .find(|(hash, _)| *hash == wanted.hash)
+                    .map(|(_, blob)| *blob)
+                    .ok_or_else(|| {
+                        BlobProviderError::Custom(anyhow!(
+                            "Blob {} of L1 block {} is not in the fixture",
+                            wanted.hash,
+                            block_ref.number
+                        ))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Serves the L2 block infos, system configs and payloads recorded in a derivation
+/// fixture.
+#[derive(Debug, Clone)]
+pub struct FixtureL2ChainProvider {
+    /// The fixture.
+    fixture: Arc<DerivationFixture>,
+}
+
+impl FixtureL2ChainProvider {
+    /// Returns a provider of the fixture's L2 data.
+    pub fn new(fixture: Arc<DerivationFixture>) -> Self {
+        Self { fixture }
+    }
+}
+
+#[async_trait]
+impl L2ChainProvider for FixtureL2ChainProvider {
+    async fn l2_block_info_by_number(&mut self, number: u64) -> anyhow::Result<L2BlockInfo> {
+        self.fixture
+            .l2_block_infos
+            .get(&number)
+            .cloned()
+            .map(IntoKona::into_kona)
+            .ok_or_else(|| anyhow!("L2 block info for block {number} is not in the fixture"))
+    }
+
+    /// Rebuilds the payload from the block's reference payload attributes, or its derived
+    /// ones if it has none, and its block info.
+    ///
+    /// Fixtures do not record L2 headers, so the payload only carries what span batch
+    /// validation reads: the block's number, hash, parent hash, timestamp and
+    /// transactions. Its state and receipts roots are zero.
+    async fn payload_by_number(
+        &mut self,
+        number: u64,
+    ) -> anyhow::Result<L2ExecutionPayloadEnvelope> {
+        let info = self
+            .fixture
+            .l2_block_infos
+            .get(&number)
+            .ok_or_else(|| anyhow!("L2 block info for block {number} is not in the fixture"))?;
+        let attributes = self
+            .fixture
+            .ref_payloads
+            .get(&number)
+            .or_else(|| self.fixture.l2_payloads.get(&number))
+            .ok_or_else(|| anyhow!("L2 payload for block {number} is not in the fixture"))?;
+        let body = attributes
+            .transactions
+            .iter()
+            .map(|tx| OpTxEnvelope::decode_2718(&mut tx.as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!("Invalid transaction in L2 block {number}: {e}"))?;
+        let header = Header {
+            parent_hash: info.block_info.parent_hash,
+            beneficiary: attributes.fee_recipient,
+            number,
+            gas_limit: attributes.gas_limit.unwrap_or_default().into(),
+            timestamp: attributes.timestamp,
+            mix_hash: attributes.prev_randao,
+            parent_beacon_block_root: attributes.parent_beacon_block_root,
+            ..Default::default()
+        };
+        let mut payload: L2ExecutionPayloadEnvelope = OpBlock {
+            header,
+            body,
+            ommers: Vec::new(),
+            withdrawals: attributes.withdrawals.clone(),
+        }
+        .into();
+        // The header is incomplete, so it does not hash to the block's hash.
+        payload.execution_payload.block_hash = info.block_info.hash;
+        Ok(payload)
+    }
+
+    async fn system_config_by_number(
+        &mut self,
+        number: u64,
+        _: Arc<RollupConfig>,
+    ) -> anyhow::Result<SystemConfig> {
+        self.fixture
+            .system_config_at(number)
+            .cloned()
+            .map(IntoKona::into_kona)
+            .ok_or_else(|| anyhow!("No system config for L2 block {number} in the fixture"))
+    }
+}
+
+/// Returns the block info of an L1 block.
+fn block_info(header: &Header) -> BlockInfo {
+    BlockInfo {
+        hash: header.hash_slow(),
+        number: header.number,
+        parent_hash: header.parent_hash,
+        timestamp: header.timestamp,
+    }
+}
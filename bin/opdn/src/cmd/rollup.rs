@@ -0,0 +1,45 @@
+//! Contains the `--rollup-override` controls for generating fixtures against a modified
+//! superchain registry config, e.g. to schedule a hardfork the registry doesn't have yet.
+
+use crate::cmd::exit::{ExitCode, WithExitCode};
+use clap::Args;
+use color_eyre::{eyre::eyre, Result};
+use kona_derive::types::RollupConfig;
+use op_test_vectors::patch::{apply, Patch};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use superchain_registry::ROLLUP_CONFIGS;
+
+/// Arguments overriding fields of the superchain registry's rollup config.
+#[derive(Args, Clone, Debug)]
+pub struct RollupArgs {
+    /// The rollup config field overrides to apply, in order.
+    #[clap(
+        long = "rollup-override",
+        help = "Rollup config override as <path>=<value>, e.g. fjord_time=1720627201"
+    )]
+    pub overrides: Vec<Patch>,
+}
+
+impl RollupArgs {
+    /// Returns the superchain registry's rollup config for the L2 chain, with the
+    /// overrides applied.
+    ///
+    /// Overrides of fields the rollup config doesn't have are rejected.
+    pub fn config(&self, l2_chain_id: u64) -> Result<RollupConfig> {
+        let cfg = ROLLUP_CONFIGS
+            .get(&l2_chain_id)
+            .ok_or_else(|| eyre!("No rollup config found for L2 chain ID: {}", l2_chain_id))?;
+        apply(cfg, &self.overrides)
+            .map_err(|e| eyre!("Invalid rollup config override: {e}"))
+            .exit_code(ExitCode::Usage)
+    }
+
+    /// Returns the overrides to record in the fixture, by dot-separated path.
+    pub fn recorded(&self) -> BTreeMap<String, Value> {
+        self.overrides
+            .iter()
+            .map(|patch| (patch.path(), patch.value.clone()))
+            .collect()
+    }
+}
@@ -0,0 +1,142 @@
+//! Minimal JSON-RPC helpers for calls not covered by the kona providers.
+
+use crate::cmd::{
+    exit::{ExitCode, WithExitCode},
+    parse::RelativeBlock,
+};
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::{keccak256, Bytes, B256, U64};
+use color_eyre::{
+    eyre::{ensure, eyre, Report},
+    Result,
+};
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+/// Sends a JSON-RPC request and deserializes its result.
+pub async fn request<T: DeserializeOwned>(
+    client: &Client,
+    url: &Url,
+    method: &str,
+    params: Value,
+) -> Result<T> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let response = client
+        .post(url.clone())
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(&request)?)
+        .send()
+        .await
+        .map_err(|e| transport_error(e, format!("{method} request failed")))?
+        .bytes()
+        .await
+        .map_err(|e| transport_error(e, format!("{method} response failed")))?;
+    let mut response: Value = serde_json::from_slice(&response).exit_code(ExitCode::Environment)?;
+    if let Some(error) = response.get("error") {
+        return Err(ExitCode::Environment.classify(eyre!("{method} failed: {error}")));
+    }
+    serde_json::from_value(response["result"].take()).map_err(|e| eyre!("{method} failed: {e}"))
+}
+
+/// Describes a failed HTTP request, classified as a timeout or an environment error.
+fn transport_error(error: reqwest::Error, context: String) -> Report {
+    let code = if error.is_timeout() {
+        ExitCode::Timeout
+    } else {
+        ExitCode::Environment
+    };
+    code.classify(eyre!("{context}: {error}"))
+}
+
+/// Fetches the chain ID of the node at `url`, which also checks that it is reachable.
+pub async fn chain_id(client: &Client, url: &Url) -> Result<u64> {
+    request::<U64>(client, url, "eth_chainId", json!([]))
+        .await
+        .map(|id| id.to())
+        .map_err(|e| ExitCode::of(&e).classify(eyre!("RPC at {url} is unreachable: {e}")))
+}
+
+/// Fetches the EIP-2718 encoded bytes of a transaction, verifying they hash to `hash`.
+pub async fn raw_transaction(client: &Client, url: &Url, hash: B256) -> Result<Bytes> {
+    let raw: Option<Bytes> =
+        request(client, url, "eth_getRawTransactionByHash", json!([hash])).await?;
+    let raw = raw.ok_or_else(|| eyre!("Transaction {hash} not found"))?;
+    ensure!(
+        keccak256(&raw) == hash,
+        "Raw transaction bytes for {hash} hash to {}",
+        keccak256(&raw)
+    );
+    Ok(raw)
+}
+
+/// Resolves a block number or tag to a block number.
+pub async fn block_number(client: &Client, url: &Url, block: BlockNumberOrTag) -> Result<u64> {
+    if let BlockNumberOrTag::Number(number) = block {
+        return Ok(number);
+    }
+    let header: Option<Value> = request(
+        client,
+        url,
+        "eth_getBlockByNumber",
+        json!([block.to_string(), false]),
+    )
+    .await?;
+    let header = header.ok_or_else(|| eyre!("No {block} block found"))?;
+    let number: U64 = serde_json::from_value(header["number"].clone())
+        .map_err(|e| eyre!("Missing number for {block} block: {e}"))?;
+    Ok(number.to())
+}
+
+/// A reference to a block in the rollup node's sync status.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct SyncStatusBlock {
+    /// The block number.
+    pub number: u64,
+}
+
+/// The L2 heads reported by the rollup node's `optimism_syncStatus`.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct SyncStatus {
+    /// The unsafe L2 head.
+    pub unsafe_l2: SyncStatusBlock,
+    /// The safe L2 head.
+    pub safe_l2: SyncStatusBlock,
+    /// The finalized L2 head.
+    pub finalized_l2: SyncStatusBlock,
+}
+
+impl SyncStatus {
+    /// Returns the L2 block number for a tag, or [None] for block numbers.
+    pub fn l2_block_number(&self, block: BlockNumberOrTag) -> Option<u64> {
+        match block {
+            BlockNumberOrTag::Latest => Some(self.unsafe_l2.number),
+            BlockNumberOrTag::Safe => Some(self.safe_l2.number),
+            BlockNumberOrTag::Finalized => Some(self.finalized_l2.number),
+            _ => None,
+        }
+    }
+}
+
+/// Fetches the rollup node's sync status.
+pub async fn sync_status(client: &Client, rollup_url: &Url) -> Result<SyncStatus> {
+    request(client, rollup_url, "optimism_syncStatus", json!([])).await
+}
+
+/// Resolves a relative block to a block number.
+///
+/// Tags are read from `status` when given, so every endpoint of a range is resolved
+/// against the same snapshot of the rollup node's heads. Otherwise they are resolved
+/// through the execution client at `url`.
+pub async fn resolve_block(
+    client: &Client,
+    url: &Url,
+    status: Option<&SyncStatus>,
+    block: RelativeBlock,
+) -> Result<u64> {
+    let base = match status.and_then(|s| s.l2_block_number(block.base)) {
+        Some(number) => number,
+        None => block_number(client, url, block.base).await?,
+    };
+    block.resolve(base).map_err(|e| eyre!(e))
+}
@@ -0,0 +1,212 @@
+//! Contains the `run-derivation` subcommand, which runs the derivation pipeline over a
+//! derivation fixture and checks the payloads it derives against the fixture's.
+
+use crate::cmd::{
+    envelope,
+    exit::{ExitCode, WithExitCode},
+    providers::{FixtureBlobProvider, FixtureChainProvider, FixtureL2ChainProvider},
+};
+use clap::{ArgAction, Parser};
+use color_eyre::{eyre::eyre, Result};
+use hashbrown::HashMap;
+use kona_derive::{online::*, pipeline::PipelineBuilder, types::StageError};
+use op_test_vectors::{
+    derivation::DerivationFixture,
+    format::load,
+    l2::{IntoFixture, PayloadAttributes},
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::{path::PathBuf, sync::Arc};
+use tracing::{debug, error, info, trace, warn};
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "run-derivation";
+
+/// The number of steps in a row that may fail, other than for a lack of data, before the
+/// pipeline is considered stuck.
+const MAX_FAILED_STEPS: usize = 64;
+
+/// CLI arguments for the `run-derivation` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct RunDerivation {
+    /// The derivation fixture to run.
+    #[clap(help = "Derivation fixture file (.json, .yaml or .yml) to run")]
+    pub fixture: PathBuf,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+/// An L2 block that did not derive as the fixture records it, as reported in the
+/// `mismatches` metric.
+#[derive(Serialize, Debug)]
+struct Mismatch {
+    /// The L2 block number.
+    block: u64,
+    /// How the derived block differs from the fixture's.
+    message: String,
+}
+
+impl RunDerivation {
+    /// Runs the derivation pipeline over the fixture's L1 blocks, serving every input from
+    /// the fixture, and compares the payload attributes derived for each L2 block between
+    /// the cursors with the fixture's `l2Payloads`, printing each block as it is checked.
+    pub async fn run(&self) -> Result<()> {
+        let fixture =
+            Arc::new(load::<DerivationFixture>(&self.fixture).exit_code(ExitCode::FixtureInvalid)?);
+        let derived = derive(Arc::clone(&fixture)).await?;
+
+        let mut mismatches = Vec::new();
+        for number in fixture.l2_derived_range() {
+            let message = match (fixture.l2_payloads.get(&number), derived.get(&number)) {
+                (Some(expected), Some(derived)) => {
+                    let fields = differing_fields(expected, derived)?;
+                    if fields.is_empty() {
+                        envelope::human(format_args!("[ok] L2 block {number}"));
+                        continue;
+                    }
+                    format!("derived payload differs in {}", fields.join(", "))
+                }
+                (Some(_), None) => "not derived".to_string(),
+                (None, Some(_)) => "derived, but the fixture has no payload for it".to_string(),
+                (None, None) => "not derived, and the fixture has no payload for it".to_string(),
+            };
+            envelope::human(format_args!("[fail] L2 block {number}: {message}"));
+            mismatches.push(Mismatch {
+                block: number,
+                message,
+            });
+        }
+
+        let blocks = fixture.l2_derived_range().count();
+        envelope::metric("blocks", blocks);
+        envelope::metric("mismatches", &mismatches);
+        if !mismatches.is_empty() {
+            return Err(eyre!(
+                "{} of {} L2 blocks did not derive as the fixture records",
+                mismatches.len(),
+                blocks
+            ));
+        }
+        envelope::human(format_args!("All {blocks} L2 blocks derived as recorded"));
+        Ok(())
+    }
+}
+
+/// Runs the pipeline from the fixture's starting cursor until the cursor reaches its
+/// ending cursor or the pipeline has read every L1 block, returning the payload attributes
+/// derived for each L2 block.
+///
+/// As when the fixture was generated, the cursor follows the fixture's L2 block infos
+/// rather than the derived blocks.
+async fn derive(fixture: Arc<DerivationFixture>) -> Result<HashMap<u64, PayloadAttributes>> {
+    let cfg = Arc::new(fixture.rollup_config.clone());
+    let mut l1_provider =
+        FixtureChainProvider::new(&fixture).exit_code(ExitCode::FixtureInvalid)?;
+    let blob_provider = FixtureBlobProvider::new(&fixture).exit_code(ExitCode::FixtureInvalid)?;
+    let mut l2_provider = FixtureL2ChainProvider::new(Arc::clone(&fixture));
+    let attributes =
+        StatefulAttributesBuilder::new(cfg.clone(), l2_provider.clone(), l1_provider.clone());
+    let dap = EthereumDataSource::new(l1_provider.clone(), blob_provider, &cfg);
+
+    let mut l2_cursor = l2_provider
+        .l2_block_info_by_number(fixture.l2_cursor_start)
+        .await
+        .map_err(|e| eyre!("Missing the starting cursor: {e}"))
+        .exit_code(ExitCode::FixtureInvalid)?;
+    let l1_origin = l1_provider
+        .block_info_by_number(l2_cursor.l1_origin.number)
+        .await
+        .map_err(|e| eyre!("Missing the L1 origin of the starting cursor: {e}"))
+        .exit_code(ExitCode::FixtureInvalid)?;
+    let last_l1_block = l1_provider.last_block().unwrap_or_default();
+    let mut pipeline = PipelineBuilder::new()
+        .rollup_config(cfg)
+        .dap_source(dap)
+        .l2_chain_provider(l2_provider.clone())
+        .chain_provider(l1_provider)
+        .builder(attributes)
+        .origin(l1_origin)
+        .build();
+
+    let mut derived = HashMap::new();
+    let mut failed_steps = 0;
+    while l2_cursor.block_info.number < fixture.l2_cursor_end {
+        let failed = match pipeline.step(l2_cursor).await {
+            StepResult::PreparedAttributes => {
+                trace!(target: TARGET, "Prepared attributes");
+                false
+            }
+            StepResult::AdvancedOrigin => {
+                trace!(target: TARGET, "Advanced origin");
+                false
+            }
+            StepResult::OriginAdvanceErr(e) => {
+                // Past the last L1 block there is nothing left to derive from.
+                if pipeline.origin().is_some_and(|o| o.number >= last_l1_block) {
+                    debug!(target: TARGET, "Read every L1 block in the fixture");
+                    break;
+                }
+                warn!(target: TARGET, "Could not advance origin: {:?}", e);
+                true
+            }
+            StepResult::StepFailed(StageError::NotEnoughData) => {
+                debug!(target: TARGET, "Not enough data to step derivation pipeline");
+                false
+            }
+            StepResult::StepFailed(e) => {
+                error!(target: TARGET, "Error stepping derivation pipeline: {:?}", e);
+                true
+            }
+        };
+        failed_steps = if failed { failed_steps + 1 } else { 0 };
+        if failed_steps >= MAX_FAILED_STEPS {
+            return Err(eyre!(
+                "Derivation pipeline failed {MAX_FAILED_STEPS} steps in a row from L2 block {}",
+                l2_cursor.block_info.number
+            ));
+        }
+
+        // Get the attributes if there are some available.
+        let Some(attributes) = pipeline.next() else {
+            continue;
+        };
+        let number = attributes.parent.block_info.number + 1;
+        info!(target: TARGET, "Derived L2 block {number}");
+        derived.insert(number, attributes.attributes.into_fixture());
+
+        l2_cursor = l2_provider
+            .l2_block_info_by_number(l2_cursor.block_info.number + 1)
+            .await
+            .map_err(|e| eyre!(e))
+            .exit_code(ExitCode::FixtureInvalid)?;
+    }
+    Ok(derived)
+}
+
+/// Returns the serialized names of the fields in which two payload attributes differ,
+/// in order.
+fn differing_fields(
+    expected: &PayloadAttributes,
+    derived: &PayloadAttributes,
+) -> Result<Vec<String>> {
+    if expected == derived {
+        return Ok(Vec::new());
+    }
+    let (Value::Object(expected), Value::Object(derived)) = (
+        serde_json::to_value(expected)?,
+        serde_json::to_value(derived)?,
+    ) else {
+        return Err(eyre!("Payload attributes do not serialize to objects"));
+    };
+    let mut fields = expected
+        .keys()
+        .chain(derived.keys())
+        .filter(|field| expected.get(*field) != derived.get(*field))
+        .cloned()
+        .collect::<Vec<_>>();
+    fields.sort();
+    fields.dedup();
+    Ok(fields)
+}
@@ -1,22 +1,98 @@
 //! Utilities
 
-use kona_derive::types::{L2ExecutionPayloadEnvelope, L2PayloadAttributes, RawTransaction};
+use color_eyre::{eyre::eyre, Result};
+use kona_derive::types::L2ExecutionPayloadEnvelope;
+use op_test_vectors::{
+    anchor_state::AnchorStateFixture,
+    building::BlockBuildingFixture,
+    derivation::DerivationFixture,
+    execution::ExecutionFixture,
+    format::{load_with, LoadMode},
+    l2::PayloadAttributes,
+};
+use std::path::{Path, PathBuf};
 
-/// Converts an [L2ExecutionPayloadEnvelope] to an [L2PayloadAttributes].
-pub fn to_payload_attributes(payload: L2ExecutionPayloadEnvelope) -> L2PayloadAttributes {
-    L2PayloadAttributes {
+/// A fixture of any known type.
+#[derive(Debug)]
+pub enum AnyFixture {
+    /// A derivation fixture.
+    Derivation(Box<DerivationFixture>),
+    /// An execution fixture.
+    Execution(Box<ExecutionFixture>),
+    /// An anchor state fixture.
+    AnchorState(AnchorStateFixture),
+    /// A block building fixture.
+    BlockBuilding(BlockBuildingFixture),
+}
+
+impl AnyFixture {
+    /// Loads a fixture as each known fixture type in turn, returning the first that
+    /// matches along with the paths of any unknown fields.
+    ///
+    /// Fixtures are most often derivation fixtures, so if none matches, the error is the
+    /// one loading it as a derivation fixture.
+    pub fn load(path: &Path) -> Result<(Self, Vec<String>)> {
+        let derivation = match load_with::<DerivationFixture>(path, LoadMode::Lenient) {
+            Ok((fixture, unknown)) => return Ok((Self::Derivation(Box::new(fixture)), unknown)),
+            Err(e) => e,
+        };
+        if let Ok((fixture, unknown)) = load_with::<ExecutionFixture>(path, LoadMode::Lenient) {
+            return Ok((Self::Execution(Box::new(fixture)), unknown));
+        }
+        if let Ok((fixture, unknown)) = load_with(path, LoadMode::Lenient) {
+            return Ok((Self::AnchorState(fixture), unknown));
+        }
+        if let Ok((fixture, unknown)) = load_with(path, LoadMode::Lenient) {
+            return Ok((Self::BlockBuilding(fixture), unknown));
+        }
+        Err(eyre!("not a known fixture type: {derivation}"))
+    }
+
+    /// Returns the name of the fixture type, e.g. `derivation fixture`.
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Derivation(_) => "derivation fixture",
+            Self::Execution(_) => "execution fixture",
+            Self::AnchorState(_) => "anchor state fixture",
+            Self::BlockBuilding(_) => "block building fixture",
+        }
+    }
+}
+
+/// Converts an [L2ExecutionPayloadEnvelope] to the [PayloadAttributes] that rebuild it.
+pub fn to_payload_attributes(payload: L2ExecutionPayloadEnvelope) -> PayloadAttributes {
+    PayloadAttributes {
         timestamp: payload.execution_payload.timestamp,
         prev_randao: payload.execution_payload.prev_randao,
         fee_recipient: payload.execution_payload.fee_recipient,
         withdrawals: payload.execution_payload.withdrawals.clone(),
         parent_beacon_block_root: payload.parent_beacon_block_root,
         gas_limit: Some(payload.execution_payload.gas_limit as u64),
-        transactions: payload
-            .execution_payload
-            .transactions
-            .into_iter()
-            .map(RawTransaction)
-            .collect(),
+        transactions: payload.execution_payload.transactions,
         no_tx_pool: true,
     }
 }
+
+/// Recursively collects the JSON and YAML files in a directory, sorted by path.
+pub fn fixture_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(|e| eyre!("Failed to read {:?}: {e}", dir))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if matches!(
+                path.extension()
+                    .and_then(|e| e.to_str())
+                    .map(str::to_ascii_lowercase)
+                    .as_deref(),
+                Some("json" | "yaml" | "yml")
+            ) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
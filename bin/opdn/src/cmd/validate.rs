@@ -0,0 +1,149 @@
+//! Contains the `validate` subcommand, which reports every consistency violation in
+//! derivation fixtures.
+
+use crate::cmd::{
+    blobs, envelope,
+    exit::{ExitCode, WithExitCode},
+    lock::PathLock,
+    output::write_fixture,
+    util::fixture_files,
+};
+use clap::{ArgAction, Parser};
+use color_eyre::{eyre::eyre, Result};
+use op_test_vectors::{
+    derivation::{DerivationFixture, Violation},
+    format::{load, Format},
+};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// The logging target to use for [tracing].
+const TARGET: &str = "validate";
+
+/// CLI arguments for the `validate` subcommand of `opdn`.
+#[derive(Parser, Clone, Debug)]
+pub struct Validate {
+    /// The fixtures to validate, or directories to search for them.
+    #[clap(
+        required = true,
+        help = "Fixture files (.json, .yaml or .yml) or directories of them to validate"
+    )]
+    pub fixtures: Vec<PathBuf>,
+    /// Apply deterministic repairs and write the fixtures back before validating them.
+    #[clap(
+        long,
+        help = "Repair what needs nothing beyond the fixture itself, e.g. L1 block order, and write it back"
+    )]
+    pub fix: bool,
+    /// Write fixtures that appear to contain secrets, warning about them instead.
+    #[clap(
+        long,
+        help = "Write fixtures even if they appear to contain secrets, warning instead"
+    )]
+    pub allow_secrets: bool,
+    /// Verbosity level (0-4)
+    #[arg(long, short, help = "Verbosity level (0-4)", action = ArgAction::Count)]
+    pub v: u8,
+}
+
+/// A violation found in a fixture, as reported in the `violations` metric.
+#[derive(Serialize, Debug)]
+struct Reported {
+    /// The fixture the violation was found in.
+    fixture: PathBuf,
+    /// The violation.
+    #[serde(flatten)]
+    violation: Violation,
+}
+
+impl Validate {
+    /// Runs every check on each fixture, printing each violation as it is found.
+    ///
+    /// On top of [DerivationFixture::validate], the blobs of each L1 block are checked
+    /// against the versioned hashes their batcher transactions commit to. Fixtures that
+    /// fail to load are reported as a violation of the `load` check. With `--fix`, the
+    /// repairs of [DerivationFixture::repair] are applied and printed first, and only
+    /// the violations left afterwards are reported.
+    pub async fn run(&self) -> Result<()> {
+        let mut files = Vec::new();
+        for path in &self.fixtures {
+            if path.is_dir() {
+                files.extend(fixture_files(path).exit_code(ExitCode::Environment)?);
+            } else {
+                files.push(path.clone());
+            }
+        }
+
+        let (mut reported, mut repaired) = (Vec::new(), 0);
+        for path in &files {
+            info!(target: TARGET, "Validating {:?}", path);
+            // Hold the lock from the load, so a repair can't overwrite a concurrent write.
+            let _lock = self.fix.then(|| PathLock::acquire(path)).transpose()?;
+            let violations = match load::<DerivationFixture>(path) {
+                Ok(mut fixture) => {
+                    if self.fix && repair(path, &mut fixture, self.allow_secrets)? {
+                        repaired += 1;
+                    }
+                    validate(&fixture)
+                }
+                Err(e) => vec![Violation {
+                    check: "load",
+                    message: e.to_string(),
+                }],
+            };
+            for violation in violations {
+                envelope::human(format_args!("[fail] {}: {violation}", path.display()));
+                reported.push(Reported {
+                    fixture: path.clone(),
+                    violation,
+                });
+            }
+        }
+
+        envelope::metric("fixtures", files.len());
+        envelope::metric("violations", &reported);
+        if self.fix {
+            envelope::metric("repaired", repaired);
+        }
+        if !reported.is_empty() {
+            return Err(ExitCode::FixtureInvalid.classify(eyre!(
+                "Found {} violations in {} fixtures",
+                reported.len(),
+                files.len()
+            )));
+        }
+        envelope::human(format_args!("All {} fixtures are valid", files.len()));
+        Ok(())
+    }
+}
+
+/// Repairs a fixture and writes it back if anything changed, returning whether it did.
+///
+/// The caller must hold the [PathLock] on `path`.
+fn repair(path: &Path, fixture: &mut DerivationFixture, allow_secrets: bool) -> Result<bool> {
+    let repairs = fixture.repair();
+    if repairs.is_empty() {
+        return Ok(false);
+    }
+    for repair in &repairs {
+        envelope::human(format_args!("[fixed] {}: {repair}", path.display()));
+    }
+    write_fixture(path, fixture, Format::from_path(path), allow_secrets)?;
+    envelope::artifact(path);
+    Ok(true)
+}
+
+/// Runs the fixture's own checks and the blob commitment check.
+fn validate(fixture: &DerivationFixture) -> Vec<Violation> {
+    let mut violations = fixture.validate();
+    for block in &fixture.l1_blocks {
+        if let Err(e) = blobs::verify_fixture_block(&fixture.rollup_config, block) {
+            violations.push(Violation {
+                check: "blobs",
+                message: e.to_string(),
+            });
+        }
+    }
+    violations
+}
@@ -1,6 +1,30 @@
 use clap::Parser;
+use opdn::cmd::{envelope::Envelope, exit::ExitCode};
+use std::{process, time::Instant};
 
 #[tokio::main]
-async fn main() -> color_eyre::Result<()> {
-    opdn::Cli::parse().init_telemetry()?.run().await
+async fn main() -> process::ExitCode {
+    let start = Instant::now();
+    let cli = match opdn::Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return ExitCode::of_clap(&e).into();
+        }
+    };
+    let (command, output_json) = (cli.name(), cli.output_json);
+    let result = match cli.init_telemetry() {
+        Ok(cli) => cli.run().await,
+        Err(e) => Err(e),
+    };
+    if output_json {
+        println!("{}", Envelope::new(command, &result, start.elapsed()));
+    }
+    match result {
+        Ok(()) => ExitCode::Success.into(),
+        Err(report) => {
+            eprintln!("Error: {report:?}");
+            ExitCode::of(&report).into()
+        }
+    }
 }
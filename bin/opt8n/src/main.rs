@@ -49,6 +49,11 @@ pub struct Opt8nArgs {
     pub output: PathBuf,
     #[clap(long, help = "Path to genesis state")]
     pub genesis: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Write the fixture even if it appears to contain secrets, warning instead"
+    )]
+    pub allow_secrets: bool,
 }
 
 #[tokio::main]
@@ -69,6 +74,7 @@ async fn main() -> eyre::Result<()> {
         Some(node_config),
         opt8n_args.output.clone(),
         opt8n_args.genesis.clone(),
+        opt8n_args.allow_secrets,
     )
     .await?;
 
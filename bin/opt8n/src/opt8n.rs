@@ -17,7 +17,13 @@ use std::{
 use clap::{CommandFactory, FromArgMatches, Parser};
 use color_eyre::eyre::{ensure, eyre, Result};
 use futures::StreamExt;
-use op_test_vectors::execution::{ExecutionFixture, ExecutionReceipt, ExecutionResult};
+use op_test_vectors::{
+    execution::{
+        environment, execution_receipt, payload_attributes, ExecutionFixture, ExecutionReceipt,
+        ExecutionResult, L1CostFunction, L1FeeEnvironment, L1_BLOCK_ADDRESS,
+    },
+    secrets,
+};
 use revm::{
     db::{AlloyDB, CacheDB},
     primitives::{BlobExcessGasAndPrice, BlockEnv, CfgEnv, Env, SpecId, U256},
@@ -32,6 +38,8 @@ pub struct Opt8n {
     pub execution_fixture: ExecutionFixture,
     pub node_config: NodeConfig,
     pub output_file: PathBuf,
+    /// Whether to write fixtures that appear to contain secrets, warning about them.
+    pub allow_secrets: bool,
 }
 
 impl Opt8n {
@@ -39,6 +47,7 @@ impl Opt8n {
         node_config: Option<NodeConfig>,
         output_file: PathBuf,
         genesis: Option<PathBuf>,
+        allow_secrets: bool,
     ) -> Result<Self> {
         let genesis = if let Some(genesis) = genesis.as_ref() {
             serde_json::from_reader(File::open(genesis)?)?
@@ -61,18 +70,30 @@ impl Opt8n {
             execution_fixture: ExecutionFixture::default(),
             node_config,
             output_file,
+            allow_secrets,
         })
     }
 
     /// Listens for commands, and new blocks from the block stream.
+    ///
+    /// Every mined block is added to the execution fixture, which is rewritten after each
+    /// one. On `exit`, transactions sent since the last `dump` are mined first, so the
+    /// fixture covers the whole session.
     pub async fn repl(&mut self) -> Result<()> {
         let mut new_blocks = self.eth_api.backend.new_block_notifications();
+        let mut exiting = false;
 
         loop {
             tokio::select! {
-                command = self.receive_command() => {
+                command = self.receive_command(), if !exiting => {
                     match command {
-                        Ok(ReplCommand::Exit) => break,
+                        Ok(ReplCommand::Exit) => {
+                            if !self.has_pending_transactions().await? {
+                                break;
+                            }
+                            exiting = true;
+                            self.mine_block().await;
+                        }
                         Ok(command) => self.execute(command).await?,
                         Err(e) => eprintln!("Error: {:?}", e),
                     }
@@ -82,8 +103,12 @@ impl Opt8n {
                     if let Some(new_block) = new_block {
                         if let Some(block) = self.eth_api.backend.get_block_by_hash(new_block.hash) {
                             self.generate_execution_fixture(block).await?;
+                            println!("Wrote execution fixture to {}", self.output_file.display());
                         }
                     }
+                    if exiting {
+                        break;
+                    }
                 }
             }
         }
@@ -181,7 +206,12 @@ impl Opt8n {
     async fn execute(&mut self, command: ReplCommand) -> Result<()> {
         match command {
             ReplCommand::Dump => {
-                self.mine_block().await;
+                // An empty block would leave the state unchanged, which isn't a fixture.
+                if self.has_pending_transactions().await? {
+                    self.mine_block().await;
+                } else {
+                    eprintln!("No pending transactions to dump");
+                }
             }
             ReplCommand::Anvil { mut args } => {
                 args.insert(0, "anvil".to_string());
@@ -250,6 +280,25 @@ impl Opt8n {
         Ok(())
     }
 
+    /// Reads the L1 fee parameters the block's transactions were charged with from the
+    /// `L1Block` predeploy, in the state the block was built on.
+    pub fn capture_l1_fee(&self, block: &Block) -> Result<L1FeeEnvironment> {
+        let db = AlloyDB::new(
+            self.node_handle.http_provider(),
+            BlockId::from(block.header.number - 1),
+        )
+        .ok_or_else(|| eyre!("Failed to create AlloyDB"))?;
+        let spec_id = SpecId::from(self.node_config.hardfork.unwrap_or_default());
+        L1FeeEnvironment::from_storage(l1_cost_function(spec_id), |slot| {
+            db.storage_ref(L1_BLOCK_ADDRESS, slot).map_err(|e| eyre!(e))
+        })
+    }
+
+    /// Returns whether any transactions are waiting to be mined.
+    async fn has_pending_transactions(&self) -> Result<bool> {
+        Ok(!self.eth_api.txpool_content().await?.pending.is_empty())
+    }
+
     pub async fn mine_block(&mut self) {
         self.eth_api.mine_one().await;
     }
@@ -257,6 +306,7 @@ impl Opt8n {
     /// Generates an execution fixture from a block.
     pub async fn generate_execution_fixture(&mut self, block: Block) -> Result<()> {
         self.capture_pre_post_alloc(&block)?;
+        let l1_fee = self.capture_l1_fee(&block)?;
 
         // Append block transactions and receipts to the execution fixture
         let mut receipts: Vec<ExecutionReceipt> = Vec::with_capacity(block.transactions.len());
@@ -267,7 +317,7 @@ impl Opt8n {
                 .transaction_receipt(tx.transaction.hash())
                 .await?
             {
-                receipts.push(receipt.try_into()?);
+                receipts.push(execution_receipt(receipt)?);
             }
             self.execution_fixture
                 .transactions
@@ -283,7 +333,17 @@ impl Opt8n {
             receipts,
         };
 
-        self.execution_fixture.env = block.into();
+        let mut env = environment(&block);
+        env.chain_id = Some(U256::from(self.eth_api.chain_id()));
+        env.l1_fee = Some(l1_fee);
+        // The pre-state is that of the first block, so its environment is the fixture's.
+        if self.execution_fixture.block_environments.is_empty() {
+            self.execution_fixture.env = env.clone();
+        }
+        self.execution_fixture.block_environments.push(env);
+        self.execution_fixture
+            .payload_attributes
+            .push(payload_attributes(&block));
         self.execution_fixture.result = execution_result;
 
         // Ensure pre and post states are different
@@ -292,10 +352,27 @@ impl Opt8n {
             "Pre and post state are the same"
         );
 
-        // Output the execution fixture to file
+        self.write_fixture()
+    }
+
+    /// Writes the execution fixture to the output file once it has been scanned for
+    /// secrets, failing if any are found unless `--allow-secrets` was passed.
+    fn write_fixture(&self) -> Result<()> {
+        let findings = secrets::scan(&self.execution_fixture)?;
+        if !findings.is_empty() {
+            let findings: Vec<_> = findings.iter().map(ToString::to_string).collect();
+            ensure!(
+                self.allow_secrets,
+                "Execution fixture contains possible secrets: {}; remove them or pass --allow-secrets",
+                findings.join(", ")
+            );
+            for finding in &findings {
+                eprintln!("Warning: execution fixture contains a possible secret: {finding}");
+            }
+        }
+
         let file = fs::File::create(&self.output_file)?;
         serde_json::to_writer_pretty(file, &self.execution_fixture)?;
-
         Ok(())
     }
 }
@@ -337,6 +414,19 @@ where
     evm
 }
 
+/// Returns the L1 cost function the optimism handler applies under `spec_id`.
+fn l1_cost_function(spec_id: SpecId) -> L1CostFunction {
+    if spec_id.is_enabled_in(SpecId::FJORD) {
+        L1CostFunction::Fjord
+    } else if spec_id.is_enabled_in(SpecId::ECOTONE) {
+        L1CostFunction::Ecotone
+    } else if spec_id.is_enabled_in(SpecId::REGOLITH) {
+        L1CostFunction::Regolith
+    } else {
+        L1CostFunction::Bedrock
+    }
+}
+
 #[derive(Parser, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[clap(rename_all = "snake_case", infer_subcommands = true, multicall = true)]
 pub enum ReplCommand {
@@ -350,10 +440,12 @@ pub enum ReplCommand {
         #[arg(index = 1, allow_hyphen_values = true)]
         args: Vec<String>,
     },
+    /// Mines the pending transactions and writes the execution fixture.
     Dump,
     RpcEndpoint,
     // TODO: implement clear
     // TODO: implement reset
+    /// Mines any pending transactions into the execution fixture, and exits.
     #[command(visible_alias = "e")]
     Exit,
 }
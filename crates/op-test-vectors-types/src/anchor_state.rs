@@ -0,0 +1,103 @@
+//! Module containing the anchor state fixture.
+//!
+//! The anchor state registry records, per dispute game type, the latest finalized output
+//! root that new games build on. Capturing its contents lets challenger and proposer
+//! software be tested against realistic anchor states.
+
+use alloy_primitives::{Address, B256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The game type of permissionless cannon games.
+pub const CANNON_GAME_TYPE: u32 = 0;
+
+/// The game type of permissioned cannon games.
+pub const PERMISSIONED_CANNON_GAME_TYPE: u32 = 1;
+
+/// The anchor state fixture captures the contents of an anchor state registry at an
+/// L1 block.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorStateFixture {
+    /// A description of the scenario the fixture covers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether the fixture is deprecated, so fixture runs skip it by default.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// The path of the fixture that supersedes this one, relative to this fixture.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<String>,
+    /// The L2 chain ID.
+    #[serde(with = "crate::quantity")]
+    pub l2_chain_id: u64,
+    /// The address of the anchor state registry on L1.
+    pub anchor_state_registry: Address,
+    /// The L1 block number the registry was read at.
+    #[serde(with = "crate::quantity")]
+    pub l1_block_number: u64,
+    /// The hash of the L1 block the registry was read at.
+    pub l1_block_hash: B256,
+    /// A map of game type to the anchor output root for that game type.
+    #[serde(deserialize_with = "crate::quantity::map::deserialize")]
+    pub anchors: BTreeMap<u32, OutputRoot>,
+}
+
+impl AnchorStateFixture {
+    /// Returns the anchor output root for the given game type.
+    pub fn anchor(&self, game_type: u32) -> Option<&OutputRoot> {
+        self.anchors.get(&game_type)
+    }
+}
+
+/// An output root and the L2 block number it commits to.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputRoot {
+    /// The output root.
+    pub root: B256,
+    /// The L2 block number of the output root.
+    #[serde(with = "crate::quantity")]
+    pub l2_block_number: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    #[test]
+    fn test_anchor_state_fixture() {
+        let fixture_str = r#"{
+            "l2ChainId": 10,
+            "anchorStateRegistry": "0x18dac71c228d1c32c99489b7323d441e1175e443",
+            "l1BlockNumber": "0x1400000",
+            "l1BlockHash": "0x1111111111111111111111111111111111111111111111111111111111111111",
+            "anchors": {
+                "0": {
+                    "root": "0x2222222222222222222222222222222222222222222222222222222222222222",
+                    "l2BlockNumber": 120000000
+                }
+            }
+        }"#;
+        let fixture: AnchorStateFixture = serde_json::from_str(fixture_str).unwrap();
+        assert_eq!(fixture.l2_chain_id, 10);
+        assert_eq!(
+            fixture.anchor_state_registry,
+            address!("18dac71c228d1c32c99489b7323d441e1175e443")
+        );
+        assert_eq!(fixture.l1_block_number, 0x1400000);
+        assert_eq!(
+            fixture.anchor(CANNON_GAME_TYPE),
+            Some(&OutputRoot {
+                root: b256!("2222222222222222222222222222222222222222222222222222222222222222"),
+                l2_block_number: 120_000_000,
+            })
+        );
+        assert!(fixture.anchor(PERMISSIONED_CANNON_GAME_TYPE).is_none());
+
+        let serialized = serde_json::to_string(&fixture).unwrap();
+        let roundtrip: AnchorStateFixture = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(roundtrip, fixture);
+    }
+}
@@ -0,0 +1,173 @@
+//! Module containing the block building fixture.
+//!
+//! A block building fixture captures a sequencer's mempool and building policy, along
+//! with the block it is expected to build from them, so block builder implementations
+//! can be tested against the same inputs.
+
+use alloy_primitives::{keccak256, Bytes, B256};
+use color_eyre::eyre::{self, bail, ensure};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The block building fixture is the top-level object that contains everything needed
+/// to run a block building test.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockBuildingFixture {
+    /// A description of the scenario the fixture covers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The policy the sequencer builds the block with.
+    pub policy: SequencerPolicy,
+    /// The EIP-2718 encoded transactions in the mempool, in arrival order.
+    pub mempool: Vec<Bytes>,
+    /// The block the sequencer is expected to build.
+    pub expected: ExpectedBlock,
+}
+
+/// The limits a sequencer applies when selecting transactions for a block.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SequencerPolicy {
+    /// The block gas limit.
+    #[serde(with = "crate::quantity")]
+    pub gas_limit: u64,
+    /// The maximum total size of the encoded transactions in the block, which bounds
+    /// the data the batcher has to post to L1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_da_bytes: Option<u64>,
+}
+
+/// The expected outcome of building a block.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedBlock {
+    /// The hashes of the mempool transactions included in the block, in block order.
+    pub transactions: Vec<B256>,
+    /// The mempool transactions left out of the block, and why.
+    pub excluded: Vec<ExcludedTransaction>,
+}
+
+/// A mempool transaction the sequencer is expected to leave out of the block.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedTransaction {
+    /// The transaction hash.
+    pub hash: B256,
+    /// Why the transaction is left out.
+    pub reason: ExclusionReason,
+}
+
+/// The reason a mempool transaction is left out of a block.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExclusionReason {
+    /// The transaction doesn't fit in the remaining block gas.
+    GasLimit,
+    /// The transaction doesn't fit in the remaining data availability budget.
+    DaLimit,
+    /// The transaction is invalid against the parent state, e.g. a bad nonce.
+    Invalid,
+}
+
+impl BlockBuildingFixture {
+    /// Validates that every mempool transaction is either included or excluded exactly
+    /// once, and that the included transactions fit the data availability budget.
+    pub fn validate(&self) -> eyre::Result<()> {
+        let sizes = self
+            .mempool
+            .iter()
+            .map(|tx| (keccak256(tx), tx.len() as u64))
+            .collect::<HashMap<_, _>>();
+        ensure!(
+            sizes.len() == self.mempool.len(),
+            "Mempool contains duplicate transactions"
+        );
+
+        let mut seen = HashSet::new();
+        let excluded = self.expected.excluded.iter().map(|tx| &tx.hash);
+        for hash in self.expected.transactions.iter().chain(excluded) {
+            ensure!(
+                sizes.contains_key(hash),
+                "Transaction {hash} is not in the mempool"
+            );
+            ensure!(
+                seen.insert(hash),
+                "Transaction {hash} is listed more than once"
+            );
+        }
+        if let Some(hash) = sizes.keys().find(|hash| !seen.contains(hash)) {
+            bail!("Mempool transaction {hash} is neither included nor excluded");
+        }
+
+        if let Some(max_da_bytes) = self.policy.max_da_bytes {
+            let da_bytes = self
+                .expected
+                .transactions
+                .iter()
+                .map(|hash| sizes[hash])
+                .sum::<u64>();
+            ensure!(
+                da_bytes <= max_da_bytes,
+                "Included transactions use {da_bytes} bytes, over the {max_da_bytes} byte budget"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::bytes;
+
+    fn fixture() -> BlockBuildingFixture {
+        let mempool = vec![bytes!("02aa"), bytes!("02bbbb"), bytes!("02cccccc")];
+        BlockBuildingFixture {
+            description: Some("DA budget excludes the largest transaction".to_string()),
+            policy: SequencerPolicy {
+                gas_limit: 30_000_000,
+                max_da_bytes: Some(5),
+            },
+            expected: ExpectedBlock {
+                transactions: vec![keccak256(&mempool[1]), keccak256(&mempool[0])],
+                excluded: vec![ExcludedTransaction {
+                    hash: keccak256(&mempool[2]),
+                    reason: ExclusionReason::DaLimit,
+                }],
+            },
+            mempool,
+        }
+    }
+
+    #[test]
+    fn test_block_building_fixture_roundtrip() {
+        let fixture = fixture();
+        let serialized = serde_json::to_string(&fixture).unwrap();
+        assert!(serialized.contains(r#""reason":"daLimit""#));
+        let roundtrip: BlockBuildingFixture = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(roundtrip, fixture);
+    }
+
+    #[test]
+    fn test_validate_block_building_fixture() {
+        fixture().validate().unwrap();
+
+        let mut over_budget = fixture();
+        over_budget.policy.max_da_bytes = Some(4);
+        assert!(over_budget.validate().is_err());
+
+        let mut missing = fixture();
+        missing.expected.excluded.clear();
+        assert!(missing.validate().is_err());
+
+        let mut duplicate = fixture();
+        let hash = duplicate.expected.transactions[0];
+        duplicate.expected.transactions.push(hash);
+        assert!(duplicate.validate().is_err());
+
+        let mut unknown = fixture();
+        unknown.expected.transactions.push(B256::ZERO);
+        assert!(unknown.validate().is_err());
+    }
+}
@@ -0,0 +1,500 @@
+//! Module containing types for cannon's MIPS VM state, memory proofs and step witnesses.
+
+use alloy_primitives::{keccak256, Bytes, B256};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use color_eyre::eyre::{self, ensure, eyre};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Read, Write},
+};
+
+/// The number of address bits used to index into a memory page.
+pub const PAGE_ADDR_SIZE: usize = 12;
+/// The size of a memory page in bytes.
+pub const PAGE_SIZE: usize = 1 << PAGE_ADDR_SIZE;
+/// The size of an encoded [StateWitness] in bytes.
+pub const STATE_WITNESS_SIZE: usize = 226;
+/// The depth of the memory merkle tree, using 32 byte leaves over a 32 bit address space.
+pub const MEMORY_TREE_DEPTH: usize = 27;
+/// The size of an encoded [MemoryProof] in bytes: the leaf followed by its sibling hashes.
+pub const MEMORY_PROOF_SIZE: usize = (MEMORY_TREE_DEPTH + 1) * 32;
+
+/// The depth of the merkle tree of a single page.
+const PAGE_TREE_DEPTH: usize = 7;
+
+/// The cannon state is the JSON-serialized state of the MIPS VM, as produced by
+/// `cannon load-elf` and `cannon run`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CannonState {
+    /// The allocated memory pages.
+    pub memory: Vec<PageEntry>,
+    /// The key of the preimage currently being read.
+    pub preimage_key: B256,
+    /// The offset into the preimage currently being read.
+    pub preimage_offset: u32,
+    /// The program counter.
+    pub pc: u32,
+    /// The next program counter, used for branch delay slots.
+    #[serde(rename = "nextPC")]
+    pub next_pc: u32,
+    /// The LO special register.
+    pub lo: u32,
+    /// The HI special register.
+    pub hi: u32,
+    /// The heap pointer.
+    pub heap: u32,
+    /// The exit code of the program, only meaningful once exited.
+    #[serde(rename = "exit")]
+    pub exit_code: u8,
+    /// Whether the program has exited.
+    pub exited: bool,
+    /// The number of steps executed.
+    pub step: u64,
+    /// The general purpose registers.
+    pub registers: [u32; 32],
+    /// The last hint sent to the host, if it was not yet fully written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_hint: Option<Bytes>,
+}
+
+impl CannonState {
+    /// Computes the merkle root of the VM memory.
+    pub fn memory_root(&self) -> B256 {
+        let zero_hashes = zero_hashes();
+        let mut level = self
+            .memory
+            .iter()
+            .map(|entry| (entry.index, entry.data.merkle_root()))
+            .collect::<BTreeMap<u32, B256>>();
+
+        for &empty in &zero_hashes[PAGE_TREE_DEPTH..MEMORY_TREE_DEPTH] {
+            let mut next = BTreeMap::new();
+            for (&index, &hash) in &level {
+                let parent = index >> 1;
+                // Left siblings are visited first, so the parent may already be hashed.
+                if next.contains_key(&parent) {
+                    continue;
+                }
+                let node = if index & 1 == 0 {
+                    hash_pair(hash, level.get(&(index | 1)).copied().unwrap_or(empty))
+                } else {
+                    hash_pair(empty, hash)
+                };
+                next.insert(parent, node);
+            }
+            level = next;
+        }
+
+        level
+            .get(&0)
+            .copied()
+            .unwrap_or(zero_hashes[MEMORY_TREE_DEPTH])
+    }
+
+    /// Returns the number of allocated memory pages.
+    pub fn page_count(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Returns the [StateWitness] for this state.
+    pub fn witness(&self) -> StateWitness {
+        StateWitness {
+            mem_root: self.memory_root(),
+            preimage_key: self.preimage_key,
+            preimage_offset: self.preimage_offset,
+            pc: self.pc,
+            next_pc: self.next_pc,
+            lo: self.lo,
+            hi: self.hi,
+            heap: self.heap,
+            exit_code: self.exit_code,
+            exited: self.exited,
+            step: self.step,
+            registers: self.registers,
+        }
+    }
+}
+
+/// A single allocated memory page and its index.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PageEntry {
+    /// The page index, i.e. the address of the page shifted right by [PAGE_ADDR_SIZE].
+    pub index: u32,
+    /// The page contents.
+    pub data: Page,
+}
+
+/// A memory page.
+///
+/// Pages are serialized the same way cannon serializes them: zlib compressed and base64
+/// encoded.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Page(pub Box<[u8; PAGE_SIZE]>);
+
+impl Page {
+    /// Computes the merkle root of the page.
+    pub fn merkle_root(&self) -> B256 {
+        let mut nodes = self
+            .0
+            .chunks_exact(32)
+            .map(B256::from_slice)
+            .collect::<Vec<_>>();
+        while nodes.len() > 1 {
+            nodes = nodes
+                .chunks_exact(2)
+                .map(|pair| hash_pair(pair[0], pair[1]))
+                .collect();
+        }
+        nodes[0]
+    }
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self(Box::new([0; PAGE_SIZE]))
+    }
+}
+
+impl fmt::Debug for Page {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let non_zero = self.0.iter().filter(|b| **b != 0).count();
+        f.debug_struct("Page")
+            .field("non_zero_bytes", &non_zero)
+            .finish()
+    }
+}
+
+impl Serialize for Page {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(self.0.as_slice())
+            .map_err(serde::ser::Error::custom)?;
+        let compressed = encoder.finish().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&STANDARD.encode(compressed))
+    }
+}
+
+impl<'de> Deserialize<'de> for Page {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let compressed = STANDARD.decode(encoded).map_err(serde::de::Error::custom)?;
+        let mut data = Vec::with_capacity(PAGE_SIZE);
+        ZlibDecoder::new(compressed.as_slice())
+            .read_to_end(&mut data)
+            .map_err(serde::de::Error::custom)?;
+        let data: [u8; PAGE_SIZE] = data.try_into().map_err(|data: Vec<u8>| {
+            serde::de::Error::custom(format!("invalid page size: {}", data.len()))
+        })?;
+        Ok(Self(Box::new(data)))
+    }
+}
+
+/// The status of the VM, encoded in the first byte of the state hash.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VmStatus {
+    /// The program exited with code 0.
+    Valid = 0,
+    /// The program exited with code 1.
+    Invalid = 1,
+    /// The program exited with any other code.
+    Panic = 2,
+    /// The program has not exited.
+    Unfinished = 3,
+}
+
+/// The packed state witness committed to on-chain by the MIPS contract.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StateWitness {
+    /// The merkle root of the VM memory.
+    pub mem_root: B256,
+    /// The key of the preimage currently being read.
+    pub preimage_key: B256,
+    /// The offset into the preimage currently being read.
+    pub preimage_offset: u32,
+    /// The program counter.
+    pub pc: u32,
+    /// The next program counter.
+    pub next_pc: u32,
+    /// The LO special register.
+    pub lo: u32,
+    /// The HI special register.
+    pub hi: u32,
+    /// The heap pointer.
+    pub heap: u32,
+    /// The exit code of the program.
+    pub exit_code: u8,
+    /// Whether the program has exited.
+    pub exited: bool,
+    /// The number of steps executed.
+    pub step: u64,
+    /// The general purpose registers.
+    pub registers: [u32; 32],
+}
+
+impl StateWitness {
+    /// Encodes the witness into its packed [STATE_WITNESS_SIZE] byte representation.
+    pub fn encode(&self) -> Bytes {
+        let mut out = Vec::with_capacity(STATE_WITNESS_SIZE);
+        out.extend_from_slice(self.mem_root.as_slice());
+        out.extend_from_slice(self.preimage_key.as_slice());
+        for word in [
+            self.preimage_offset,
+            self.pc,
+            self.next_pc,
+            self.lo,
+            self.hi,
+            self.heap,
+        ] {
+            out.extend_from_slice(&word.to_be_bytes());
+        }
+        out.push(self.exit_code);
+        out.push(self.exited as u8);
+        out.extend_from_slice(&self.step.to_be_bytes());
+        for register in self.registers {
+            out.extend_from_slice(&register.to_be_bytes());
+        }
+        out.into()
+    }
+
+    /// Decodes a packed state witness.
+    pub fn decode(data: &[u8]) -> eyre::Result<Self> {
+        ensure!(
+            data.len() == STATE_WITNESS_SIZE,
+            "invalid state witness size: {}",
+            data.len()
+        );
+        let word = |offset: usize| {
+            u32::from_be_bytes(data[offset..offset + 4].try_into().expect("4 byte slice"))
+        };
+        let mut registers = [0u32; 32];
+        for (i, register) in registers.iter_mut().enumerate() {
+            *register = word(98 + i * 4);
+        }
+        Ok(Self {
+            mem_root: B256::from_slice(&data[..32]),
+            preimage_key: B256::from_slice(&data[32..64]),
+            preimage_offset: word(64),
+            pc: word(68),
+            next_pc: word(72),
+            lo: word(76),
+            hi: word(80),
+            heap: word(84),
+            exit_code: data[88],
+            exited: match data[89] {
+                0 => false,
+                1 => true,
+                b => return Err(eyre!("invalid exited flag: {b}")),
+            },
+            step: u64::from_be_bytes(data[90..98].try_into().expect("8 byte slice")),
+            registers,
+        })
+    }
+
+    /// Returns the [VmStatus] of the witness.
+    pub fn status(&self) -> VmStatus {
+        match (self.exited, self.exit_code) {
+            (false, _) => VmStatus::Unfinished,
+            (true, 0) => VmStatus::Valid,
+            (true, 1) => VmStatus::Invalid,
+            (true, _) => VmStatus::Panic,
+        }
+    }
+
+    /// Computes the state hash: the keccak256 hash of the witness with the first byte
+    /// replaced by the [VmStatus].
+    pub fn state_hash(&self) -> B256 {
+        let mut hash = keccak256(self.encode());
+        hash.0[0] = self.status() as u8;
+        hash
+    }
+}
+
+/// A merkle proof of a 32 byte leaf in VM memory.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MemoryProof {
+    /// The proven leaf.
+    pub leaf: B256,
+    /// The sibling hashes from the bottom of the tree to the top.
+    pub siblings: [B256; MEMORY_TREE_DEPTH],
+}
+
+impl MemoryProof {
+    /// Decodes a [MEMORY_PROOF_SIZE] byte memory proof.
+    pub fn decode(data: &[u8]) -> eyre::Result<Self> {
+        ensure!(
+            data.len() == MEMORY_PROOF_SIZE,
+            "invalid memory proof size: {}",
+            data.len()
+        );
+        let mut siblings = [B256::ZERO; MEMORY_TREE_DEPTH];
+        for (sibling, chunk) in siblings.iter_mut().zip(data[32..].chunks_exact(32)) {
+            *sibling = B256::from_slice(chunk);
+        }
+        Ok(Self {
+            leaf: B256::from_slice(&data[..32]),
+            siblings,
+        })
+    }
+
+    /// Computes the memory root implied by the proof for the leaf containing `address`.
+    pub fn root(&self, address: u32) -> B256 {
+        let index = address >> 5;
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(self.leaf, |node, (depth, sibling)| {
+                if (index >> depth) & 1 == 1 {
+                    hash_pair(*sibling, node)
+                } else {
+                    hash_pair(node, *sibling)
+                }
+            })
+    }
+
+    /// Returns whether the proof is valid against the given memory root.
+    pub fn verify(&self, address: u32, mem_root: B256) -> bool {
+        self.root(address) == mem_root
+    }
+}
+
+/// A single step proof, as written by `cannon run --proof-at`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct StepProof {
+    /// The step the proof is for.
+    pub step: u64,
+    /// The state hash before the step.
+    pub pre: B256,
+    /// The state hash after the step.
+    pub post: B256,
+    /// The packed state witness before the step.
+    pub state_data: Bytes,
+    /// The concatenated memory proofs needed to execute the step.
+    pub proof_data: Bytes,
+    /// The preimage key read during the step, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oracle_key: Option<Bytes>,
+    /// The preimage value read during the step, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oracle_value: Option<Bytes>,
+    /// The offset into the preimage read during the step, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oracle_offset: Option<u32>,
+}
+
+impl StepProof {
+    /// Decodes the pre-state witness of the step.
+    pub fn witness(&self) -> eyre::Result<StateWitness> {
+        StateWitness::decode(&self.state_data)
+    }
+
+    /// Decodes the memory proofs of the step.
+    pub fn memory_proofs(&self) -> eyre::Result<Vec<MemoryProof>> {
+        let chunks = self.proof_data.chunks_exact(MEMORY_PROOF_SIZE);
+        ensure!(
+            chunks.remainder().is_empty(),
+            "invalid proof data size: {}",
+            self.proof_data.len()
+        );
+        chunks.map(MemoryProof::decode).collect()
+    }
+}
+
+/// Hashes two merkle tree nodes together.
+fn hash_pair(left: B256, right: B256) -> B256 {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left.as_slice());
+    data[32..].copy_from_slice(right.as_slice());
+    keccak256(data)
+}
+
+/// Returns the roots of all-zero subtrees, indexed by their depth.
+fn zero_hashes() -> [B256; MEMORY_TREE_DEPTH + 1] {
+    let mut out = [B256::ZERO; MEMORY_TREE_DEPTH + 1];
+    for i in 1..out.len() {
+        out[i] = hash_pair(out[i - 1], out[i - 1]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+
+    #[test]
+    fn test_cannon_state() {
+        let state_str = include_str!("./testdata/cannon_state.json");
+        let state: CannonState = serde_json::from_str(state_str).unwrap();
+        assert_eq!(state.pc, 4);
+        assert_eq!(state.next_pc, 8);
+        assert_eq!(state.heap, 0x20000000);
+        assert_eq!(state.step, 2);
+        assert_eq!(state.registers[29], 0x7fffd000);
+        assert_eq!(state.page_count(), 1);
+        assert_eq!(state.memory[0].data.0[..4], [0xde, 0xad, 0xbe, 0xef]);
+
+        let serialized = serde_json::to_string(&state).unwrap();
+        let roundtrip: CannonState = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(state, roundtrip);
+    }
+
+    #[test]
+    fn test_empty_memory_root() {
+        let state = CannonState::default();
+        assert_eq!(state.memory_root(), zero_hashes()[MEMORY_TREE_DEPTH]);
+
+        // An allocated page of zeroes does not change the root.
+        let state = CannonState {
+            memory: vec![PageEntry {
+                index: 7,
+                data: Page::default(),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(state.memory_root(), zero_hashes()[MEMORY_TREE_DEPTH]);
+    }
+
+    #[test]
+    fn test_state_witness_roundtrip() {
+        let state_str = include_str!("./testdata/cannon_state.json");
+        let state: CannonState = serde_json::from_str(state_str).unwrap();
+        let witness = state.witness();
+        let encoded = witness.encode();
+        assert_eq!(encoded.len(), STATE_WITNESS_SIZE);
+        assert_eq!(StateWitness::decode(&encoded).unwrap(), witness);
+        assert!(StateWitness::decode(&encoded[1..]).is_err());
+    }
+
+    #[test]
+    fn test_state_hash_status() {
+        let mut witness = StateWitness::default();
+        assert_eq!(witness.state_hash()[0], VmStatus::Unfinished as u8);
+        witness.exited = true;
+        assert_eq!(witness.state_hash()[0], VmStatus::Valid as u8);
+        witness.exit_code = 1;
+        assert_eq!(witness.state_hash()[0], VmStatus::Invalid as u8);
+        witness.exit_code = 2;
+        assert_eq!(witness.state_hash()[0], VmStatus::Panic as u8);
+    }
+
+    #[test]
+    fn test_memory_proof_verify() {
+        let zero_hashes = zero_hashes();
+        let mut data = vec![0u8; 32];
+        for sibling in &zero_hashes[..MEMORY_TREE_DEPTH] {
+            data.extend_from_slice(sibling.as_slice());
+        }
+        let proof = MemoryProof::decode(&data).unwrap();
+        assert!(proof.verify(0x1000, zero_hashes[MEMORY_TREE_DEPTH]));
+        assert!(!proof.verify(
+            0x1000,
+            b256!("1111111111111111111111111111111111111111111111111111111111111111")
+        ));
+    }
+}
@@ -0,0 +1,1955 @@
+//! Module containing the derivation test fixture.
+
+use crate::{
+    anchor_state::OutputRoot,
+    hint::Hint,
+    l2::{l1_info, L2BlockInfo, L2Output, PayloadAttributes, SystemConfig},
+    version::SchemaVersion,
+};
+use alloy_consensus::{Eip658Value, Header, Receipt, TxEnvelope};
+use alloy_eips::{
+    eip2718::{Decodable2718, Encodable2718},
+    eip4844::Blob,
+};
+use alloy_primitives::{Bloom, Bytes, B256};
+use alloy_rlp::{self as rlp, Encodable};
+use alloy_trie::{HashBuilder, Nibbles};
+use color_eyre::eyre::{self, ensure, eyre};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fmt,
+    ops::{Range, RangeInclusive},
+};
+use superchain_primitives::RollupConfig;
+
+/// The derivation fixture is the top-level object that contains
+/// everything needed to run a derivation test.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivationFixture {
+    /// The schema version the fixture is in, see [DerivationFixture::migrate].
+    #[serde(default = "SchemaVersion::unversioned")]
+    pub version: SchemaVersion,
+    /// A description of the scenario the fixture covers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether the fixture is deprecated, so fixture runs skip it by default.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// The path of the fixture that supersedes this one, relative to this fixture.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<String>,
+    /// The rollup config.
+    pub rollup_config: RollupConfig,
+    /// The fields of the rollup config that override the superchain registry's config
+    /// of its L2 chain, by dot-separated path, e.g. a hardfork time the registry doesn't
+    /// have yet.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rollup_config_overrides: BTreeMap<String, Value>,
+    /// A list of L1 Blocks to derive from.
+    pub l1_blocks: Vec<FixtureBlock>,
+    /// A map of L2 block number to l2 payload attributes.
+    #[serde(deserialize_with = "crate::quantity::map::deserialize")]
+    pub l2_payloads: HashMap<u64, PayloadAttributes>,
+    /// A map of l2 block number to reference payloads.
+    /// These are used for span batch validation.
+    #[serde(deserialize_with = "crate::quantity::map::deserialize")]
+    pub ref_payloads: HashMap<u64, PayloadAttributes>,
+    /// A map of L2 block numbers to system configs.
+    #[serde(deserialize_with = "crate::quantity::map::deserialize")]
+    pub l2_system_configs: HashMap<u64, SystemConfig>,
+    /// L2 block numbers mapped to their block info.
+    #[serde(deserialize_with = "crate::quantity::map::deserialize")]
+    pub l2_block_infos: HashMap<u64, L2BlockInfo>,
+    /// The safe head derivation starts from, exclusive: the first derived block is the
+    /// one after it.
+    ///
+    /// For example, if the starting L2 cursor is 1 and the ending L2 cursor is 3, the
+    /// L2 blocks derived are 2 and 3, i.e. the range (1, 3].
+    #[serde(with = "crate::quantity")]
+    pub l2_cursor_start: u64,
+    /// The last L2 block derived, inclusive.
+    #[serde(with = "crate::quantity")]
+    pub l2_cursor_end: u64,
+    /// The beacon chain config used to map L1 block timestamps to the slots
+    /// that blobs were fetched from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_beacon_config: Option<BeaconConfig>,
+}
+
+/// The migrations of the derivation fixture schema, where the migration at index `i`
+/// upgrades a fixture's JSON from version `i` to version `i + 1`.
+///
+/// A change to the serialized form of a [DerivationFixture] bumps
+/// [SchemaVersion::CURRENT] and adds the migration from the previous version here.
+const MIGRATIONS: [fn(&mut Value) -> eyre::Result<()>; 1] = [
+    // Unversioned fixtures load as version 1 unchanged, only the version is added.
+    |_| Ok(()),
+];
+
+impl DerivationFixture {
+    /// Upgrades a fixture's JSON to the current schema version, returning the fixture and
+    /// the version it was in.
+    ///
+    /// Fails if the fixture is in a newer version than this crate supports.
+    pub fn migrate(mut value: Value) -> eyre::Result<(Self, SchemaVersion)> {
+        let from = match value.get("version") {
+            Some(version) => serde_json::from_value(version.clone())
+                .map_err(|e| eyre!("Invalid fixture version: {e}"))?,
+            None => SchemaVersion::UNVERSIONED,
+        };
+        ensure!(
+            from <= SchemaVersion::CURRENT,
+            "Fixture version {from} is newer than the supported {}",
+            SchemaVersion::CURRENT
+        );
+        for migration in &MIGRATIONS[from.0 as usize..] {
+            migration(&mut value)?;
+        }
+        let mut fixture: Self = serde_json::from_value(value).map_err(|e| eyre!(e))?;
+        fixture.version = SchemaVersion::CURRENT;
+        Ok((fixture, from))
+    }
+
+    /// Returns the L1 blocks in order, failing if their numbers are not consecutive.
+    pub fn l1_blocks_ordered(&self) -> eyre::Result<std::slice::Iter<'_, FixtureBlock>> {
+        for pair in self.l1_blocks.windows(2) {
+            let (parent, block) = (pair[0].header.number, pair[1].header.number);
+            ensure!(
+                parent.checked_add(1) == Some(block),
+                "L1 block {block} does not follow L1 block {parent}"
+            );
+        }
+        Ok(self.l1_blocks.iter())
+    }
+
+    /// Validates that the L1 blocks form a chain, each following the previous block by
+    /// number and building on its hash.
+    pub fn validate_l1_chain(&self) -> eyre::Result<()> {
+        for pair in self.l1_blocks_ordered()?.as_slice().windows(2) {
+            let (parent, block) = (&pair[0].header, &pair[1].header);
+            ensure!(
+                block.parent_hash == parent.hash_slow(),
+                "L1 block {} does not build on the hash of L1 block {}",
+                block.number,
+                parent.number
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the L2 blocks derived between the cursors, `(l2_cursor_start,
+    /// l2_cursor_end]`.
+    pub fn l2_derived_range(&self) -> RangeInclusive<u64> {
+        self.l2_cursor_start.saturating_add(1)..=self.l2_cursor_end
+    }
+
+    /// Validates that the cursor range is not reversed, and that every derived payload
+    /// is for a block within it.
+    ///
+    /// The starting cursor is the safe head derivation starts from, so a payload for it
+    /// is outside the range.
+    pub fn validate_cursors(&self) -> eyre::Result<()> {
+        let (start, end) = (self.l2_cursor_start, self.l2_cursor_end);
+        ensure!(
+            start <= end,
+            "L2 cursor start {start} is after L2 cursor end {end}"
+        );
+        let range = self.l2_derived_range();
+        let outside = self.l2_payloads.keys().filter(|n| !range.contains(n)).min();
+        if let Some(number) = outside {
+            return Err(eyre!(
+                "L2 payload for block {number} is outside the cursor range ({start}, {end}]"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Runs every consistency check on the fixture, returning each violation found rather
+    /// than stopping at the first.
+    ///
+    /// The checks are independent of each other, so a single defect may be reported by
+    /// more than one of them.
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut check = |check, result: eyre::Result<()>| {
+            if let Err(e) = result {
+                violations.push(Violation {
+                    check,
+                    message: e.to_string(),
+                });
+            }
+        };
+        check("cursors", self.validate_cursors());
+        check("l1-chain", self.validate_l1_chain());
+        for block in &self.l1_blocks {
+            check("roots", block.validate_roots());
+            check("transactions", block.validate_transactions());
+        }
+        check("blob-slots", self.validate_blob_slots());
+        check("forks", self.validate_forks());
+        check("l2-block-infos", self.validate_l2_block_infos());
+        check("l1-origins", self.validate_l1_origins());
+        violations
+    }
+
+    /// Applies the deterministic repairs for violations [DerivationFixture::validate]
+    /// reports, returning a description of each change.
+    ///
+    /// L1 blocks are sorted by number and exact duplicates dropped. Headers are never
+    /// changed: they are committed to by the L1 chain and referenced by the L2 data, so a
+    /// root that doesn't match a block's bodies means the bodies are wrong, and the block
+    /// is left for regeneration along with anything else that needs the original data.
+    pub fn repair(&mut self) -> Vec<String> {
+        let mut repairs = Vec::new();
+        if !self
+            .l1_blocks
+            .windows(2)
+            .all(|pair| pair[0].header.number <= pair[1].header.number)
+        {
+            self.l1_blocks.sort_by_key(|b| b.header.number);
+            repairs.push("Sorted L1 blocks by number".to_string());
+        }
+        let before = self.l1_blocks.len();
+        self.l1_blocks.dedup_by(|b, a| a.same_data(b));
+        if self.l1_blocks.len() < before {
+            repairs.push(format!(
+                "Removed {} duplicate L1 blocks",
+                before - self.l1_blocks.len()
+            ));
+        }
+        repairs
+    }
+
+    /// Computes the output root of every derived L2 block without a rollup node, from the
+    /// output `execute` returns for each block's payload attributes.
+    ///
+    /// Blocks build on each other, so `execute` is called in block order, and executing
+    /// them is left to the caller since the fixture holds no L2 state. Where the fixture
+    /// records an [L2BlockInfo] for a block, the executed block hash must match it.
+    pub fn output_roots(
+        &self,
+        mut execute: impl FnMut(u64, &PayloadAttributes) -> eyre::Result<L2Output>,
+    ) -> eyre::Result<Vec<OutputRoot>> {
+        let mut numbers = self.l2_payloads.keys().copied().collect::<Vec<_>>();
+        numbers.sort_unstable();
+        numbers
+            .into_iter()
+            .map(|number| {
+                let output = execute(number, &self.l2_payloads[&number])
+                    .map_err(|e| eyre!("Failed to execute L2 block {number}: {e}"))?;
+                if let Some(info) = self.l2_block_infos.get(&number) {
+                    ensure!(
+                        output.block_hash == info.block_info.hash,
+                        "Executed L2 block {number} has hash {}, but the fixture records {}",
+                        output.block_hash,
+                        info.block_info.hash
+                    );
+                }
+                Ok(OutputRoot {
+                    root: output.root(),
+                    l2_block_number: number,
+                })
+            })
+            .collect()
+    }
+
+    /// Validates that the fixture holds the data a hint asks the host for.
+    ///
+    /// L1 hints must name an L1 block of the fixture, or for blobs a block at the hinted
+    /// timestamp carrying blobs, and L2 block hints must name a block with a recorded
+    /// [L2BlockInfo]. Code, state and output hints are never served by derivation
+    /// fixtures.
+    pub fn check_hint(&self, hint: &Hint) -> eyre::Result<()> {
+        let served = match hint {
+            Hint::L1BlockHeader(hash) | Hint::L1Transactions(hash) | Hint::L1Receipts(hash) => {
+                self.l1_blocks.iter().any(|b| b.header.hash_slow() == *hash)
+            }
+            Hint::L1Blob { timestamp, .. } => self
+                .l1_blocks
+                .iter()
+                .any(|b| b.header.timestamp == *timestamp && !b.blobs.is_empty()),
+            Hint::L2BlockHeader(hash) | Hint::L2Transactions(hash) => self
+                .l2_block_infos
+                .values()
+                .any(|info| info.block_info.hash == *hash),
+            Hint::L1Precompile { .. }
+            | Hint::L2Code(_)
+            | Hint::L2StateNode(_)
+            | Hint::L2Output(_) => {
+                return Err(eyre!(
+                    "{} hints are not served by derivation fixtures",
+                    hint.kind()
+                ))
+            }
+        };
+        ensure!(served, "Fixture has no data for hint {hint}");
+        Ok(())
+    }
+
+    /// Returns the L2 payload attributes for every block in `range`, in order, failing if
+    /// any block in the range has no payload.
+    pub fn l2_payloads_range(
+        &self,
+        range: Range<u64>,
+    ) -> eyre::Result<Vec<(u64, &PayloadAttributes)>> {
+        range
+            .map(|number| {
+                self.l2_payloads
+                    .get(&number)
+                    .map(|payload| (number, payload))
+                    .ok_or_else(|| eyre!("Missing L2 payload for block {number}"))
+            })
+            .collect()
+    }
+
+    /// Returns the system config in effect at L2 block `number`, which is the config
+    /// recorded at the closest block at or before it.
+    pub fn system_config_at(&self, number: u64) -> Option<&SystemConfig> {
+        self.l2_system_configs
+            .iter()
+            .filter(|(&at, _)| at <= number)
+            .max_by_key(|(&at, _)| at)
+            .map(|(_, config)| config)
+    }
+
+    /// Returns the payload attributes recorded for L2 block `number`, from either the
+    /// derived or the reference payloads.
+    fn payload_attributes(&self, number: u64) -> Option<&PayloadAttributes> {
+        self.l2_payloads
+            .get(&number)
+            .or_else(|| self.ref_payloads.get(&number))
+    }
+
+    /// Computes `l2_block_infos` for executed L2 blocks from their headers and the payload
+    /// attributes recorded for them, replacing any supplied infos for those blocks.
+    pub fn compute_l2_block_infos<'a>(
+        &mut self,
+        headers: impl IntoIterator<Item = &'a Header>,
+    ) -> eyre::Result<()> {
+        for header in headers {
+            let attributes = self.payload_attributes(header.number).ok_or_else(|| {
+                eyre!("Missing payload attributes for L2 block {}", header.number)
+            })?;
+            let info = L2BlockInfo::from_payload(header, attributes)
+                .map_err(|e| eyre!("Invalid L2 block {}: {e}", header.number))?;
+            self.l2_block_infos.insert(header.number, info);
+        }
+        Ok(())
+    }
+
+    /// Validates that the L1 origin of every L2 block info is one of the fixture's L1
+    /// blocks, by number and hash.
+    pub fn validate_l1_origins(&self) -> eyre::Result<()> {
+        let hashes = self
+            .l1_blocks
+            .iter()
+            .map(|b| (b.header.number, b.header.hash_slow()))
+            .collect::<BTreeMap<_, _>>();
+        let mut numbers = self.l2_block_infos.keys().copied().collect::<Vec<_>>();
+        numbers.sort_unstable();
+        for number in numbers {
+            let origin = &self.l2_block_infos[&number].l1_origin;
+            let hash = hashes.get(&origin.number).ok_or_else(|| {
+                eyre!(
+                    "L1 origin {} of L2 block {number} is not among the L1 blocks",
+                    origin.number
+                )
+            })?;
+            ensure!(
+                *hash == origin.hash,
+                "L1 origin {} of L2 block {number} has hash {}, but the L1 block hashes to {hash}",
+                origin.number,
+                origin.hash
+            );
+        }
+        Ok(())
+    }
+
+    /// Cross-checks `l2_block_infos` against each other and against the L1 info deposits
+    /// of the recorded payload attributes.
+    ///
+    /// Consecutive infos must link by hash, advance in time, and either share an L1 origin
+    /// with an incremented sequence number or move to the next L1 origin at sequence
+    /// number zero.
+    pub fn validate_l2_block_infos(&self) -> eyre::Result<()> {
+        let mut numbers = self.l2_block_infos.keys().copied().collect::<Vec<_>>();
+        numbers.sort_unstable();
+        for number in numbers {
+            let info = &self.l2_block_infos[&number];
+            ensure!(
+                info.block_info.number == number,
+                "L2 block info for block {number} has number {}",
+                info.block_info.number
+            );
+            let parent = number
+                .checked_sub(1)
+                .and_then(|n| self.l2_block_infos.get(&n));
+            if let Some(parent) = parent {
+                ensure!(
+                    info.block_info.parent_hash == parent.block_info.hash,
+                    "L2 block {number} does not build on the hash of block {}",
+                    parent.block_info.number
+                );
+                ensure!(
+                    info.block_info.timestamp > parent.block_info.timestamp,
+                    "L2 block {number} is not later than its parent"
+                );
+                let expected_seq_num = if info.l1_origin == parent.l1_origin {
+                    parent.seq_num + 1
+                } else {
+                    ensure!(
+                        info.l1_origin.number == parent.l1_origin.number + 1,
+                        "L2 block {number} moves from L1 origin {} to {}",
+                        parent.l1_origin.number,
+                        info.l1_origin.number
+                    );
+                    0
+                };
+                ensure!(
+                    info.seq_num == expected_seq_num,
+                    "L2 block {number} has sequence number {}, expected {expected_seq_num}",
+                    info.seq_num
+                );
+            }
+            let Some(attributes) = self.payload_attributes(number) else {
+                continue;
+            };
+            ensure!(
+                info.block_info.timestamp == attributes.timestamp,
+                "L2 block {number} info timestamp {} does not match its payload timestamp {}",
+                info.block_info.timestamp,
+                attributes.timestamp
+            );
+            if let Some(deposit) = attributes.transactions.first() {
+                let (l1_origin, seq_num) = l1_info(deposit)
+                    .map_err(|e| eyre!("Invalid L1 info deposit for L2 block {number}: {e}"))?;
+                ensure!(
+                    info.l1_origin == l1_origin && info.seq_num == seq_num,
+                    "L2 block {number} info does not match its L1 info deposit"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the fixture against the hardforks its rollup config activates, so that
+    /// fixtures of pre-Ecotone history are held to the rules of their own era.
+    ///
+    /// Only L1 blocks from Ecotone may carry blobs. Payload attributes must carry
+    /// withdrawals exactly from Canyon and a parent beacon block root exactly from
+    /// Ecotone. System config scalars must decode in their legacy or Ecotone encoding
+    /// and agree with any explicit Ecotone fee scalars.
+    pub fn validate_forks(&self) -> eyre::Result<()> {
+        let cfg = &self.rollup_config;
+        for block in &self.l1_blocks {
+            ensure!(
+                block.blobs.is_empty() || cfg.is_ecotone_active(block.header.timestamp),
+                "L1 block {} carries blobs before Ecotone",
+                block.header.number
+            );
+        }
+        for (number, attributes) in self.l2_payloads.iter().chain(&self.ref_payloads) {
+            ensure_fork_field(
+                *number,
+                "withdrawals",
+                attributes.withdrawals.is_some(),
+                "Canyon",
+                cfg.is_canyon_active(attributes.timestamp),
+            )?;
+            ensure_fork_field(
+                *number,
+                "a parent beacon block root",
+                attributes.parent_beacon_block_root.is_some(),
+                "Ecotone",
+                cfg.is_ecotone_active(attributes.timestamp),
+            )?;
+        }
+        for (number, config) in &self.l2_system_configs {
+            let (base_fee_scalar, blob_base_fee_scalar) = config
+                .fee_scalars()
+                .map_err(|e| eyre!("Invalid system config scalar at L2 block {number}: {e}"))?;
+            if config.has_ecotone_scalar() {
+                let agrees = |explicit: Option<u64>, decoded: u32| {
+                    explicit.is_none() || explicit == Some(decoded.into())
+                };
+                ensure!(
+                    agrees(config.base_fee_scalar, base_fee_scalar)
+                        && agrees(config.blob_base_fee_scalar, blob_base_fee_scalar),
+                    "System config at L2 block {number} has fee scalars that differ from its scalar"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges another fixture of the same chain into this one, returning the union of
+    /// their L1 blocks and L2 data over the combined cursor range.
+    ///
+    /// The fixtures must share a rollup config and beacon config, and their cursor ranges
+    /// must overlap or be adjacent. Any L1 block or L2 entry present in both must be
+    /// identical, except that the notes of shared L1 blocks are unioned, keeping this
+    /// fixture's note on a shared key. The result is deprecated only if both fixtures are.
+    pub fn merge(mut self, other: Self) -> eyre::Result<Self> {
+        ensure!(
+            self.rollup_config == other.rollup_config,
+            "Fixtures have different rollup configs"
+        );
+        match (&self.l1_beacon_config, other.l1_beacon_config) {
+            (Some(ours), Some(theirs)) => {
+                ensure!(*ours == theirs, "Fixtures have different beacon configs")
+            }
+            (None, theirs) => self.l1_beacon_config = theirs,
+            (Some(_), None) => {}
+        }
+        ensure!(
+            other.l2_cursor_start <= self.l2_cursor_end
+                && self.l2_cursor_start <= other.l2_cursor_end,
+            "Cursor ranges ({}, {}] and ({}, {}] leave a gap",
+            self.l2_cursor_start,
+            self.l2_cursor_end,
+            other.l2_cursor_start,
+            other.l2_cursor_end
+        );
+        self.l2_cursor_start = self.l2_cursor_start.min(other.l2_cursor_start);
+        self.l2_cursor_end = self.l2_cursor_end.max(other.l2_cursor_end);
+        self.version = self.version.max(other.version);
+        for (path, value) in other.rollup_config_overrides {
+            self.rollup_config_overrides.entry(path).or_insert(value);
+        }
+        self.description = self.description.or(other.description);
+        // The merged fixture is only deprecated if everything in it is.
+        self.deprecated &= other.deprecated;
+        self.superseded_by = self
+            .superseded_by
+            .or(other.superseded_by)
+            .filter(|_| self.deprecated);
+
+        let mut l1_blocks = BTreeMap::<u64, FixtureBlock>::new();
+        for block in self.l1_blocks.into_iter().chain(other.l1_blocks) {
+            let number = block.header.number;
+            match l1_blocks.entry(number) {
+                Entry::Occupied(mut existing) => {
+                    let existing = existing.get_mut();
+                    ensure!(existing.same_data(&block), "Conflicting L1 block {number}");
+                    for (key, note) in block.notes {
+                        existing.notes.entry(key).or_insert(note);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(block);
+                }
+            }
+        }
+        self.l1_blocks = l1_blocks.into_values().collect();
+
+        merge_map("L2 payload", &mut self.l2_payloads, other.l2_payloads)?;
+        merge_map(
+            "reference payload",
+            &mut self.ref_payloads,
+            other.ref_payloads,
+        )?;
+        merge_map(
+            "system config",
+            &mut self.l2_system_configs,
+            other.l2_system_configs,
+        )?;
+        merge_map(
+            "L2 block info",
+            &mut self.l2_block_infos,
+            other.l2_block_infos,
+        )?;
+        Ok(self)
+    }
+
+    /// Returns whether two fixtures derive from the same inputs: the rollup config, beacon
+    /// config, cursors and L1 block data, ignoring descriptions, deprecation and notes.
+    pub fn same_inputs(&self, other: &Self) -> bool {
+        self.rollup_config == other.rollup_config
+            && self.l1_beacon_config == other.l1_beacon_config
+            && (self.l2_cursor_start, self.l2_cursor_end)
+                == (other.l2_cursor_start, other.l2_cursor_end)
+            && self.l1_blocks.len() == other.l1_blocks.len()
+            && self
+                .l1_blocks
+                .iter()
+                .zip(&other.l1_blocks)
+                .all(|(ours, theirs)| ours.same_data(theirs))
+    }
+
+    /// Returns whether two fixtures hold the same derived L2 data.
+    pub fn same_outputs(&self, other: &Self) -> bool {
+        self.l2_payloads == other.l2_payloads
+            && self.ref_payloads == other.ref_payloads
+            && self.l2_system_configs == other.l2_system_configs
+            && self.l2_block_infos == other.l2_block_infos
+    }
+
+    /// Fills in metadata this fixture lacks from another fixture with the same inputs:
+    /// the description, and notes on L1 blocks. Notes already present are kept.
+    pub fn adopt_metadata(&mut self, other: &Self) {
+        if self.description.is_none() {
+            self.description.clone_from(&other.description);
+        }
+        for (ours, theirs) in self.l1_blocks.iter_mut().zip(&other.l1_blocks) {
+            for (key, note) in &theirs.notes {
+                ours.notes
+                    .entry(key.clone())
+                    .or_insert_with(|| note.clone());
+            }
+        }
+    }
+
+    /// Validates that every L1 block carrying blobs maps to a beacon slot using
+    /// the fixture's [BeaconConfig].
+    pub fn validate_blob_slots(&self) -> eyre::Result<()> {
+        let blocks = self.l1_blocks.iter().filter(|b| !b.blobs.is_empty());
+        for block in blocks {
+            let config = self.l1_beacon_config.as_ref().ok_or_else(|| {
+                eyre!(
+                    "Missing beacon config for blobs in L1 block {}",
+                    block.header.number
+                )
+            })?;
+            config
+                .slot(block.header.timestamp)
+                .map_err(|e| eyre!("Invalid slot for L1 block {}: {e}", block.header.number))?;
+        }
+        Ok(())
+    }
+
+    /// Summarizes the fixture's chains, ranges, contents and hardforks.
+    ///
+    /// Hardforks are read at the timestamps of the fixture's L2 payloads: those active
+    /// at the first payload, and those activating by the last.
+    pub fn summary(&self) -> Summary {
+        let numbers = self.l1_blocks.iter().map(|b| b.header.number);
+        let l1_range = numbers.clone().min().zip(numbers.max());
+        let transactions = self.l1_blocks.iter().flat_map(|b| &b.transactions);
+        let blobs = self.l1_blocks.iter().flat_map(|b| &b.blobs);
+        let timestamps = self.l2_payloads.values().map(|a| a.timestamp);
+        let (forks, activations) = match timestamps.clone().min().zip(timestamps.max()) {
+            Some((first, last)) => {
+                let forks = active_forks(&self.rollup_config, first);
+                let activations = active_forks(&self.rollup_config, last)
+                    .into_iter()
+                    .filter(|fork| !forks.contains(fork))
+                    .collect();
+                (forks, activations)
+            }
+            None => Default::default(),
+        };
+        Summary {
+            version: self.version,
+            l1_chain_id: self.rollup_config.l1_chain_id,
+            l2_chain_id: self.rollup_config.l2_chain_id,
+            l1_range,
+            l2_range: (self.l2_cursor_start, self.l2_cursor_end),
+            l1_blocks: self.l1_blocks.len(),
+            transactions: transactions.clone().count(),
+            transaction_bytes: transactions.map(|tx| tx.len()).sum(),
+            blobs: blobs.clone().count(),
+            blob_bytes: blobs.map(|blob| blob.len()).sum(),
+            l2_payloads: self.l2_payloads.len(),
+            forks,
+            activations,
+        }
+    }
+}
+
+/// A summary of a fixture, see [DerivationFixture::summary].
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    /// The fixture's schema version.
+    pub version: SchemaVersion,
+    /// The L1 chain ID of the rollup config.
+    pub l1_chain_id: u64,
+    /// The L2 chain ID of the rollup config.
+    pub l2_chain_id: u64,
+    /// The lowest and highest L1 block numbers, if there are any L1 blocks.
+    pub l1_range: Option<(u64, u64)>,
+    /// The starting and ending L2 cursors, so the derived blocks are `(start, end]`.
+    pub l2_range: (u64, u64),
+    /// The number of L1 blocks.
+    pub l1_blocks: usize,
+    /// The number of L1 transactions.
+    pub transactions: usize,
+    /// The total size of the encoded L1 transactions.
+    pub transaction_bytes: usize,
+    /// The number of blobs.
+    pub blobs: usize,
+    /// The total size of the blobs.
+    pub blob_bytes: usize,
+    /// The number of L2 payloads.
+    pub l2_payloads: usize,
+    /// The hardforks active at the first L2 payload.
+    pub forks: Vec<&'static str>,
+    /// The hardforks that activate between the first and the last L2 payload.
+    pub activations: Vec<&'static str>,
+}
+
+/// Returns the hardforks of the rollup config that are active at `timestamp`, in
+/// activation order.
+fn active_forks(cfg: &RollupConfig, timestamp: u64) -> Vec<&'static str> {
+    [
+        ("regolith", cfg.is_regolith_active(timestamp)),
+        ("canyon", cfg.is_canyon_active(timestamp)),
+        ("delta", cfg.is_delta_active(timestamp)),
+        ("ecotone", cfg.is_ecotone_active(timestamp)),
+        ("fjord", cfg.is_fjord_active(timestamp)),
+    ]
+    .into_iter()
+    .filter_map(|(fork, active)| active.then_some(fork))
+    .collect()
+}
+
+/// A consistency check that a fixture failed, see [DerivationFixture::validate].
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Violation {
+    /// The name of the check, e.g. `l1-chain`.
+    pub check: &'static str,
+    /// What the check found.
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.check, self.message)
+    }
+}
+
+/// Ensures that L2 block `number` has `field` exactly when `fork` is active.
+fn ensure_fork_field(
+    number: u64,
+    field: &str,
+    present: bool,
+    fork: &str,
+    active: bool,
+) -> eyre::Result<()> {
+    match (present, active) {
+        (true, false) => Err(eyre!("L2 block {number} has {field} before {fork}")),
+        (false, true) => Err(eyre!("L2 block {number} is missing {field} from {fork}")),
+        _ => Ok(()),
+    }
+}
+
+/// Inserts every entry of `from` into `into`, failing if a block number maps to
+/// different values in each.
+fn merge_map<V: PartialEq>(
+    name: &str,
+    into: &mut HashMap<u64, V>,
+    from: HashMap<u64, V>,
+) -> eyre::Result<()> {
+    for (number, value) in from {
+        match into.get(&number) {
+            Some(existing) => ensure!(*existing == value, "Conflicting {name} for block {number}"),
+            None => {
+                into.insert(number, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The beacon chain config needed to map L1 timestamps to beacon slots.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BeaconConfig {
+    /// The beacon chain genesis time.
+    #[serde(with = "crate::quantity")]
+    pub genesis_time: u64,
+    /// The duration of a slot in seconds.
+    #[serde(with = "crate::quantity")]
+    pub seconds_per_slot: u64,
+}
+
+impl BeaconConfig {
+    /// Computes the beacon slot for the given L1 block timestamp.
+    pub fn slot(&self, timestamp: u64) -> eyre::Result<u64> {
+        ensure!(
+            self.seconds_per_slot > 0,
+            "Seconds per slot must be non-zero"
+        );
+        ensure!(
+            timestamp >= self.genesis_time,
+            "Timestamp {timestamp} is before beacon genesis time {}",
+            self.genesis_time
+        );
+        let elapsed = timestamp - self.genesis_time;
+        let slot = elapsed / self.seconds_per_slot;
+        ensure!(
+            slot * self.seconds_per_slot == elapsed,
+            "Timestamp {timestamp} is not aligned to a slot boundary"
+        );
+        Ok(slot)
+    }
+}
+
+/// A fixture block is a minimal block with associated data including blobs
+/// to derive from.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureBlock {
+    /// The block header.
+    /// The entire header is required to generate the block hash when deriving the l1 block info
+    /// tx.
+    pub header: Header,
+    /// Block Transactions.
+    /// EIP-2718 encoded raw transactions
+    pub transactions: Vec<Bytes>,
+    /// Blobs for this block.
+    pub blobs: Vec<Box<Blob>>,
+    /// Receipts for this block.
+    pub receipts: Vec<Receipt>,
+    /// Free-form notes about this block, e.g. why it is part of the scenario.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<String, String>,
+}
+
+impl FixtureBlock {
+    /// Returns whether two blocks hold the same data, ignoring their notes.
+    pub fn same_data(&self, other: &Self) -> bool {
+        self.header == other.header
+            && self.transactions == other.transactions
+            && self.blobs == other.blobs
+            && self.receipts == other.receipts
+    }
+
+    /// Computes the transactions trie root of the block's transactions.
+    pub fn transactions_root(&self) -> B256 {
+        ordered_trie_root(self.transactions.iter().map(|tx| tx.to_vec()))
+    }
+
+    /// Computes the receipts trie root of the block's receipts.
+    ///
+    /// Receipts don't carry their transaction type, so it is taken from the transaction
+    /// at the same index.
+    pub fn receipts_root(&self) -> eyre::Result<B256> {
+        ensure!(
+            self.receipts.len() == self.transactions.len(),
+            "Block has {} receipts for {} transactions",
+            self.receipts.len(),
+            self.transactions.len()
+        );
+        let receipts = self
+            .receipts
+            .iter()
+            .zip(&self.transactions)
+            .map(|(receipt, tx)| {
+                let mut out = Vec::new();
+                // Typed transactions start with their type byte, legacy ones with an RLP
+                // list header.
+                if let Some(&ty) = tx.first().filter(|&&ty| ty <= 0x7f) {
+                    out.push(ty);
+                }
+                encode_receipt(receipt, &mut out);
+                out
+            });
+        Ok(ordered_trie_root(receipts))
+    }
+
+    /// Validates that every transaction decodes as an EIP-2718 envelope, with no bytes
+    /// beyond its encoding.
+    pub fn validate_transactions(&self) -> eyre::Result<()> {
+        for (i, tx) in self.transactions.iter().enumerate() {
+            let envelope = TxEnvelope::decode_2718(&mut tx.as_ref()).map_err(|e| {
+                eyre!(
+                    "Transaction {i} in L1 block {} does not decode: {e}",
+                    self.header.number
+                )
+            })?;
+            // Decoding doesn't consume typed transactions, so compare encoded lengths.
+            ensure!(
+                envelope.encode_2718_len() == tx.len(),
+                "Transaction {i} in L1 block {} has {} bytes beyond its encoding",
+                self.header.number,
+                tx.len().saturating_sub(envelope.encode_2718_len())
+            );
+        }
+        Ok(())
+    }
+
+    /// Validates that the transactions and receipts roots in the header match the
+    /// block's transactions and receipts.
+    pub fn validate_roots(&self) -> eyre::Result<()> {
+        let transactions_root = self.transactions_root();
+        ensure!(
+            transactions_root == self.header.transactions_root,
+            "Transactions root mismatch in L1 block {}: header {}, computed {}",
+            self.header.number,
+            self.header.transactions_root,
+            transactions_root
+        );
+        let receipts_root = self.receipts_root()?;
+        ensure!(
+            receipts_root == self.header.receipts_root,
+            "Receipts root mismatch in L1 block {}: header {}, computed {}",
+            self.header.number,
+            self.header.receipts_root,
+            receipts_root
+        );
+        Ok(())
+    }
+}
+
+/// RLP encodes a receipt, without its type byte, as `[status, gas, bloom, logs]`.
+fn encode_receipt(receipt: &Receipt, out: &mut Vec<u8>) {
+    let mut bloom = Bloom::default();
+    let mut logs = Vec::new();
+    for log in &receipt.logs {
+        bloom.accrue_log(log);
+        let mut fields = Vec::new();
+        log.address.encode(&mut fields);
+        rlp::encode_list::<_, B256>(log.data.topics(), &mut fields);
+        log.data.data.encode(&mut fields);
+        rlp::Header {
+            list: true,
+            payload_length: fields.len(),
+        }
+        .encode(&mut logs);
+        logs.extend_from_slice(&fields);
+    }
+
+    let mut fields = Vec::new();
+    match receipt.status {
+        Eip658Value::Eip658(success) => success.encode(&mut fields),
+        Eip658Value::PostState(state) => state.encode(&mut fields),
+    }
+    receipt.cumulative_gas_used.encode(&mut fields);
+    bloom.encode(&mut fields);
+    rlp::Header {
+        list: true,
+        payload_length: logs.len(),
+    }
+    .encode(&mut fields);
+    fields.extend_from_slice(&logs);
+
+    rlp::Header {
+        list: true,
+        payload_length: fields.len(),
+    }
+    .encode(out);
+    out.extend_from_slice(&fields);
+}
+
+/// Computes the root of a trie keyed by the RLP encoded index of each value.
+fn ordered_trie_root(values: impl Iterator<Item = Vec<u8>>) -> B256 {
+    let mut leaves = values
+        .enumerate()
+        .map(|(i, value)| (rlp::encode(i), value))
+        .collect::<Vec<_>>();
+    leaves.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+    let mut builder = HashBuilder::default();
+    for (key, value) in leaves {
+        builder.add_leaf(Nibbles::unpack(key), &value);
+    }
+    builder.root()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l2::{tests::l1_info_deposit, BlockId, BlockInfo};
+    use alloy_primitives::{address, b256, bytes, keccak256, uint, U256};
+
+    /// A signed EIP-1559 transaction.
+    const TX: Bytes = bytes!("02f870018307c100808476d0a39c82565f94388c818ca8b9251b393131c08a736a67ccb1929787b60572b2eb6c9080c001a033bee682348fa78ffc1027bc9981e7dc60eca03af909c4eb05720e781fdae179a01ccf85367c246082fa09ef748d3b07c90752c2b59034a6b881cf99aca586eaf5");
+
+    /// Returns an L1 chain of two blocks with matching header roots, the first holding [TX]
+    /// and its receipt and the second empty.
+    fn chain_with_tx() -> [FixtureBlock; 2] {
+        let mut parent = FixtureBlock {
+            transactions: vec![TX],
+            receipts: vec![Receipt {
+                status: Eip658Value::Eip658(true),
+                cumulative_gas_used: 21_000,
+                logs: vec![],
+            }],
+            ..Default::default()
+        };
+        parent.header.transactions_root = parent.transactions_root();
+        parent.header.receipts_root = parent.receipts_root().unwrap();
+        let mut block = FixtureBlock::default();
+        block.header.number = 1;
+        block.header.parent_hash = parent.header.hash_slow();
+        block.header.transactions_root = block.transactions_root();
+        block.header.receipts_root = block.receipts_root().unwrap();
+        [parent, block]
+    }
+
+    fn ref_blocks() -> Vec<FixtureBlock> {
+        vec![
+            FixtureBlock {
+                header: Header {
+                    number: 1,
+                    parent_hash: b256!(
+                        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                    ),
+                    ommers_hash: b256!(
+                        "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+                    ),
+                    beneficiary: address!("0000000000000000000000000000000000000000"),
+                    state_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    transactions_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    receipts_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    logs_bloom: Default::default(),
+                    difficulty: uint!(0_U256),
+                    gas_limit: 0,
+                    gas_used: 0,
+                    mix_hash: b256!(
+                        "0000000000000000000000000000000000000000000000000000000000000000"
+                    ),
+                    nonce: Default::default(),
+                    extra_data: bytes!(""),
+                    timestamp: 102,
+                    ..Default::default()
+                },
+                transactions: vec![TX, TX, TX],
+                blobs: vec![],
+                receipts: vec![Receipt {
+                    status: alloy_consensus::Eip658Value::Eip658(true),
+                    cumulative_gas_used: 10,
+                    logs: vec![alloy_primitives::Log {
+                        address: address!("4200000000000000000000000000000000000011"),
+                        data: alloy_primitives::LogData::new_unchecked(vec![], bytes!("")),
+                    }],
+                }],
+                notes: BTreeMap::new(),
+            },
+            FixtureBlock {
+                header: Header {
+                    number: 2,
+                    parent_hash: b256!(
+                        "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
+                    ),
+                    ommers_hash: b256!(
+                        "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+                    ),
+                    beneficiary: address!("0000000000000000000000000000000000000000"),
+                    state_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    transactions_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    receipts_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    logs_bloom: Default::default(),
+                    difficulty: uint!(0_U256),
+                    gas_limit: 0,
+                    gas_used: 0,
+                    mix_hash: b256!(
+                        "0000000000000000000000000000000000000000000000000000000000000000"
+                    ),
+                    nonce: Default::default(),
+                    extra_data: bytes!(""),
+                    timestamp: 104,
+                    ..Default::default()
+                },
+                transactions: vec![TX, TX],
+                blobs: vec![],
+                receipts: vec![Receipt {
+                    status: alloy_consensus::Eip658Value::Eip658(true),
+                    cumulative_gas_used: 10,
+                    logs: vec![alloy_primitives::Log {
+                        address: address!("4200000000000000000000000000000000000011"),
+                        data: alloy_primitives::LogData::new_unchecked(vec![], bytes!("")),
+                    }],
+                }],
+                notes: BTreeMap::new(),
+            },
+            FixtureBlock {
+                header: Header {
+                    number: 2,
+                    parent_hash: b256!(
+                        "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
+                    ),
+                    ommers_hash: b256!(
+                        "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+                    ),
+                    beneficiary: address!("0000000000000000000000000000000000000000"),
+                    state_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    transactions_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    receipts_root: b256!(
+                        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                    ),
+                    logs_bloom: Default::default(),
+                    difficulty: uint!(0_U256),
+                    gas_limit: 0,
+                    gas_used: 0,
+                    mix_hash: b256!(
+                        "0000000000000000000000000000000000000000000000000000000000000000"
+                    ),
+                    nonce: Default::default(),
+                    extra_data: bytes!(""),
+                    timestamp: 106,
+                    ..Default::default()
+                },
+                transactions: vec![TX],
+                blobs: vec![],
+                receipts: vec![Receipt {
+                    status: alloy_consensus::Eip658Value::Eip658(true),
+                    cumulative_gas_used: 10,
+                    logs: vec![alloy_primitives::Log {
+                        address: address!("4200000000000000000000000000000000000011"),
+                        data: alloy_primitives::LogData::new_unchecked(vec![], bytes!("")),
+                    }],
+                }],
+                notes: BTreeMap::new(),
+            },
+        ]
+    }
+
+    fn ref_payload_attributes() -> HashMap<u64, PayloadAttributes> {
+        [
+            (
+                1,
+                PayloadAttributes {
+                    timestamp: 1722550777,
+                    fee_recipient: address!("4200000000000000000000000000000000000011"),
+                    prev_randao: b256!(
+                        "73ce62c38a0714e87a4141f33ec2362dc800d7693d85e42ffe6bdc22a5c84610"
+                    ),
+                    parent_beacon_block_root: Some(b256!(
+                        "8693a4b644bc68b8562194814d2945e4a78e2b20967c0a5c2f5f8e741be5a379"
+                    )),
+                    gas_limit: Some(30000000),
+                    no_tx_pool: true,
+                    withdrawals: Some(vec![]),
+                    ..Default::default()
+                },
+            ),
+            (
+                2,
+                PayloadAttributes {
+                    timestamp: 1722550779,
+                    fee_recipient: address!("4200000000000000000000000000000000000011"),
+                    prev_randao: b256!(
+                        "73ce62c38a0714e87a4141f33ec2362dc800d7693d85e42ffe6bdc22a5c84610"
+                    ),
+                    parent_beacon_block_root: Some(b256!(
+                        "8693a4b644bc68b8562194814d2945e4a78e2b20967c0a5c2f5f8e741be5a379"
+                    )),
+                    gas_limit: Some(30000000),
+                    withdrawals: Some(vec![]),
+                    no_tx_pool: true,
+                    ..Default::default()
+                },
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn ref_system_configs() -> HashMap<u64, SystemConfig> {
+        let configs: HashMap<u64, SystemConfig> = [
+            (
+                1,
+                SystemConfig {
+                    batcher_address: address!("3333333333333333333333333333333333333333"),
+                    overhead: uint!(8_U256),
+                    scalar: uint!(7_U256),
+                    gas_limit: 0,
+                    base_fee_scalar: Some(0),
+                    blob_base_fee_scalar: Some(0),
+                    operator_fee_scalar: None,
+                    operator_fee_constant: None,
+                },
+            ),
+            (
+                2,
+                SystemConfig {
+                    batcher_address: address!("3333333333333333333333333333333333333333"),
+                    overhead: uint!(8_U256),
+                    scalar: uint!(7_U256),
+                    gas_limit: 0,
+                    base_fee_scalar: Some(0),
+                    blob_base_fee_scalar: Some(0),
+                    operator_fee_scalar: None,
+                    operator_fee_constant: None,
+                },
+            ),
+            (
+                3,
+                SystemConfig {
+                    batcher_address: address!("3333333333333333333333333333333333333333"),
+                    overhead: uint!(8_U256),
+                    scalar: uint!(7_U256),
+                    gas_limit: 0,
+                    base_fee_scalar: Some(0),
+                    blob_base_fee_scalar: Some(0),
+                    operator_fee_scalar: None,
+                    operator_fee_constant: None,
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+        configs
+    }
+
+    fn ref_l2_block_infos() -> HashMap<u64, L2BlockInfo> {
+        let infos: HashMap<u64, L2BlockInfo> = [
+            (
+                1,
+                L2BlockInfo {
+                    block_info: BlockInfo {
+                        hash: b256!(
+                            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
+                        ),
+                        number: 1,
+                        parent_hash: b256!(
+                            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                        ),
+                        timestamp: 102,
+                    },
+                    l1_origin: BlockId {
+                        hash: b256!(
+                            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                        ),
+                        number: 1,
+                    },
+                    seq_num: 0,
+                },
+            ),
+            (
+                2,
+                L2BlockInfo {
+                    block_info: BlockInfo {
+                        hash: b256!(
+                            "cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc"
+                        ),
+                        number: 2,
+                        parent_hash: b256!(
+                            "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
+                        ),
+                        timestamp: 104,
+                    },
+                    l1_origin: BlockId {
+                        hash: b256!(
+                            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                        ),
+                        number: 1,
+                    },
+                    seq_num: 0,
+                },
+            ),
+            (
+                3,
+                L2BlockInfo {
+                    block_info: BlockInfo {
+                        hash: b256!(
+                            "dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd"
+                        ),
+                        number: 3,
+                        parent_hash: b256!(
+                            "cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc"
+                        ),
+                        timestamp: 106,
+                    },
+                    l1_origin: BlockId {
+                        hash: b256!(
+                            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                        ),
+                        number: 1,
+                    },
+                    seq_num: 0,
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+        infos
+    }
+
+    fn ref_rollup_config() -> RollupConfig {
+        RollupConfig {
+            canyon_time: Some(0),
+            ecotone_time: Some(0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_derivation_fixture() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        let expected = DerivationFixture {
+            version: SchemaVersion::UNVERSIONED,
+            description: None,
+            deprecated: false,
+            superseded_by: None,
+            rollup_config: ref_rollup_config(),
+            rollup_config_overrides: BTreeMap::new(),
+            l1_blocks: ref_blocks(),
+            l2_payloads: ref_payload_attributes(),
+            l2_system_configs: ref_system_configs(),
+            l2_block_infos: ref_l2_block_infos(),
+            ref_payloads: HashMap::new(),
+            l2_cursor_start: 0,
+            l2_cursor_end: 2,
+            l1_beacon_config: None,
+        };
+        assert_eq!(fixture, expected);
+    }
+
+    #[test]
+    fn test_migrate() {
+        assert_eq!(DerivationFixture::default().version, SchemaVersion::CURRENT);
+
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let value: Value = serde_json::from_str(fixture_str).unwrap();
+        let (fixture, from) = DerivationFixture::migrate(value.clone()).unwrap();
+        assert_eq!(from, SchemaVersion::UNVERSIONED);
+        assert_eq!(fixture.version, SchemaVersion::CURRENT);
+
+        let migrated = serde_json::to_value(&fixture).unwrap();
+        assert_eq!(migrated["version"], 1);
+        let (again, from) = DerivationFixture::migrate(migrated).unwrap();
+        assert_eq!(from, SchemaVersion::CURRENT);
+        assert_eq!(again, fixture);
+
+        let mut newer = value;
+        newer["version"] = "0x2".into();
+        assert!(DerivationFixture::migrate(newer).is_err());
+    }
+
+    #[test]
+    fn test_derivation_fixture_hex_quantities() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        let hex_str = fixture_str
+            .replacen("\"l2CursorStart\": 0", "\"l2CursorStart\": \"0x0\"", 1)
+            .replacen("\"l2CursorEnd\": 2", "\"l2CursorEnd\": \"2\"", 1);
+        let hex_fixture: DerivationFixture = serde_json::from_str(&hex_str).unwrap();
+        assert_eq!(hex_fixture, fixture);
+
+        // Go tooling also encodes timestamps, block numbers and gas as hex.
+        let mut value: Value = serde_json::from_str(&hex_str).unwrap();
+        let payload = &mut value["l2Payloads"]["1"];
+        payload["timestamp"] = "0x66ac09f9".into();
+        payload["gasLimit"] = "0x1c9c380".into();
+        value["l2SystemConfigs"]["1"]["gasLimit"] = "0x0".into();
+        let info = &mut value["l2BlockInfos"]["1"];
+        info["block_info"]["number"] = "0x1".into();
+        info["block_info"]["timestamp"] = "0x66".into();
+        info["l1_origin"]["number"] = "1".into();
+        info["seq_num"] = "0x0".into();
+        let hex_fixture: DerivationFixture = serde_json::from_value(value).unwrap();
+        assert_eq!(hex_fixture, fixture);
+    }
+
+    #[test]
+    fn test_derivation_fixture_accessors() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let mut fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+
+        // The test data repeats its last L1 block.
+        assert!(fixture.l1_blocks_ordered().is_err());
+        fixture.l1_blocks.pop();
+        let numbers = fixture
+            .l1_blocks_ordered()
+            .unwrap()
+            .map(|b| b.header.number)
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, vec![1, 2]);
+
+        let payloads = fixture.l2_payloads_range(1..3).unwrap();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0], (1, &fixture.l2_payloads[&1]));
+        assert_eq!(payloads[1], (2, &fixture.l2_payloads[&2]));
+        assert!(fixture.l2_payloads_range(0..2).is_err());
+        assert!(fixture.l2_payloads_range(2..2).unwrap().is_empty());
+
+        fixture.l2_system_configs.remove(&2);
+        assert!(fixture.system_config_at(0).is_none());
+        assert_eq!(
+            fixture.system_config_at(2),
+            fixture.l2_system_configs.get(&1)
+        );
+        assert_eq!(
+            fixture.system_config_at(100),
+            fixture.l2_system_configs.get(&3)
+        );
+    }
+
+    #[test]
+    fn test_compute_and_validate_l2_block_infos() {
+        let first_origin = BlockId {
+            hash: b256!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            number: 10,
+        };
+        let second_origin = BlockId {
+            hash: b256!("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+            number: 11,
+        };
+        let mut fixture = DerivationFixture::default();
+        let mut headers: Vec<Header> = Vec::new();
+        for (number, origin, seq_num) in [
+            (1, first_origin, 0),
+            (2, first_origin, 1),
+            (3, second_origin, 0),
+        ] {
+            let header = Header {
+                number,
+                timestamp: 100 + 2 * number,
+                parent_hash: headers.last().map(Header::hash_slow).unwrap_or_default(),
+                ..Default::default()
+            };
+            let attributes = PayloadAttributes {
+                timestamp: header.timestamp,
+                transactions: vec![l1_info_deposit(origin, seq_num)],
+                ..Default::default()
+            };
+            fixture.l2_payloads.insert(number, attributes);
+            headers.push(header);
+        }
+
+        fixture.compute_l2_block_infos(&headers).unwrap();
+        assert_eq!(fixture.l2_block_infos.len(), 3);
+        assert_eq!(
+            fixture.l2_block_infos[&2].block_info.hash,
+            headers[1].hash_slow()
+        );
+        assert_eq!(fixture.l2_block_infos[&2].seq_num, 1);
+        assert_eq!(fixture.l2_block_infos[&3].l1_origin, second_origin);
+        fixture.validate_l2_block_infos().unwrap();
+
+        let mut wrong_seq_num = fixture.clone();
+        wrong_seq_num.l2_block_infos.get_mut(&2).unwrap().seq_num = 0;
+        assert!(wrong_seq_num.validate_l2_block_infos().is_err());
+
+        let mut wrong_parent = fixture.clone();
+        wrong_parent
+            .l2_block_infos
+            .get_mut(&3)
+            .unwrap()
+            .block_info
+            .parent_hash = B256::ZERO;
+        assert!(wrong_parent.validate_l2_block_infos().is_err());
+
+        let mut wrong_origin = fixture.clone();
+        wrong_origin.l2_block_infos.get_mut(&3).unwrap().l1_origin = first_origin;
+        assert!(wrong_origin.validate_l2_block_infos().is_err());
+
+        let mut wrong_timestamp = fixture.clone();
+        wrong_timestamp.l2_payloads.get_mut(&1).unwrap().timestamp += 1;
+        assert!(wrong_timestamp.validate_l2_block_infos().is_err());
+
+        fixture.l2_payloads.remove(&3);
+        assert!(fixture.compute_l2_block_infos(&headers).is_err());
+    }
+
+    #[test]
+    fn test_merge_fixtures() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let mut fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        fixture.l1_blocks.pop();
+
+        // Split the fixture into two overlapping halves.
+        let mut first = fixture.clone();
+        first.l1_blocks.truncate(1);
+        first.l2_payloads.remove(&2);
+        first.l2_cursor_end = 1;
+        let mut second = fixture.clone();
+        second.l2_payloads.remove(&1);
+        second.l2_system_configs.remove(&1);
+        second.l2_cursor_start = 1;
+
+        let merged = second.clone().merge(first.clone()).unwrap();
+        assert_eq!(merged, fixture);
+        assert_eq!(merged.clone().merge(merged.clone()).unwrap(), merged);
+
+        let mut deprecated = first.clone();
+        deprecated.deprecated = true;
+        deprecated.superseded_by = Some("v2.json".to_string());
+        let merged = second.clone().merge(deprecated.clone()).unwrap();
+        assert!(!merged.deprecated);
+        assert_eq!(merged.superseded_by, None);
+        let mut both = second.clone();
+        both.deprecated = true;
+        let merged = both.merge(deprecated).unwrap();
+        assert!(merged.deprecated);
+        assert_eq!(merged.superseded_by.as_deref(), Some("v2.json"));
+
+        let mut annotated = second.clone();
+        annotated.l1_blocks[0]
+            .notes
+            .insert("batch".to_string(), "First frame".to_string());
+        let mut noted = first.clone();
+        noted.l1_blocks[0]
+            .notes
+            .insert("batch".to_string(), "Channel open".to_string());
+        noted.l1_blocks[0]
+            .notes
+            .insert("origin".to_string(), "Epoch 0".to_string());
+        let merged = annotated.merge(noted).unwrap();
+        assert_eq!(
+            merged.l1_blocks[0].notes.values().collect::<Vec<_>>(),
+            ["First frame", "Epoch 0"]
+        );
+
+        let mut conflicting = second.clone();
+        conflicting.l1_blocks[0].header.gas_used += 1;
+        assert!(first.clone().merge(conflicting).is_err());
+
+        // The halves share every L2 block info, so any difference is a conflict.
+        let mut conflicting = second.clone();
+        conflicting.l2_block_infos.get_mut(&2).unwrap().seq_num += 1;
+        assert!(first.clone().merge(conflicting).is_err());
+
+        let mut gap = second.clone();
+        gap.l2_cursor_start = 2;
+        assert!(first.clone().merge(gap).is_err());
+
+        let mut other_chain = second;
+        other_chain.rollup_config.l2_chain_id += 1;
+        assert!(first.merge(other_chain).is_err());
+    }
+
+    #[test]
+    fn test_rollup_config_overrides() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let mut fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        // Drop the second block 2, which conflicts with the first when merging.
+        fixture.l1_blocks.pop();
+        let value = serde_json::to_value(&fixture).unwrap();
+        assert!(value.get("rollupConfigOverrides").is_none());
+
+        let mut overridden = fixture.clone();
+        overridden.rollup_config.fjord_time = Some(123);
+        overridden
+            .rollup_config_overrides
+            .insert("fjord_time".to_string(), 123.into());
+        let value = serde_json::to_value(&overridden).unwrap();
+        assert_eq!(value["rollupConfigOverrides"]["fjord_time"], 123);
+        assert_eq!(
+            serde_json::from_value::<DerivationFixture>(value).unwrap(),
+            overridden
+        );
+
+        let mut plain = fixture;
+        plain.rollup_config.fjord_time = Some(123);
+        let merged = plain.merge(overridden.clone()).unwrap();
+        assert_eq!(
+            merged.rollup_config_overrides,
+            overridden.rollup_config_overrides
+        );
+    }
+
+    #[test]
+    fn test_equivalent_fixtures() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+
+        let mut annotated = fixture.clone();
+        annotated.description = Some("Channel spanning two L1 blocks".to_string());
+        annotated.deprecated = true;
+        annotated.l1_blocks[0]
+            .notes
+            .insert("batch".to_string(), "First frame".to_string());
+        assert!(fixture.same_inputs(&annotated));
+        assert!(fixture.same_outputs(&annotated));
+
+        let mut adopted = fixture.clone();
+        adopted.l1_blocks[0]
+            .notes
+            .insert("batch".to_string(), "Kept".to_string());
+        adopted.adopt_metadata(&annotated);
+        assert_eq!(adopted.description, annotated.description);
+        assert_eq!(adopted.l1_blocks[0].notes["batch"], "Kept");
+        assert!(!adopted.deprecated);
+
+        let mut other_range = fixture.clone();
+        other_range.l2_cursor_end += 1;
+        assert!(!fixture.same_inputs(&other_range));
+        let mut other_block = fixture.clone();
+        other_block.l1_blocks[0].header.gas_used += 1;
+        assert!(!fixture.same_inputs(&other_block));
+        let mut other_outputs = fixture.clone();
+        other_outputs.l2_payloads.get_mut(&1).unwrap().timestamp += 1;
+        assert!(fixture.same_inputs(&other_outputs));
+        assert!(!fixture.same_outputs(&other_outputs));
+    }
+
+    #[test]
+    fn test_beacon_config_slot() {
+        let config = BeaconConfig {
+            genesis_time: 100,
+            seconds_per_slot: 12,
+        };
+        assert_eq!(config.slot(100).unwrap(), 0);
+        assert_eq!(config.slot(124).unwrap(), 2);
+        assert!(config.slot(99).is_err());
+        assert!(config.slot(105).is_err());
+        assert!(BeaconConfig::default().slot(0).is_err());
+    }
+
+    #[test]
+    fn test_validate_blob_slots() {
+        let block = FixtureBlock {
+            header: Header {
+                timestamp: 124,
+                ..Default::default()
+            },
+            blobs: vec![Box::default()],
+            ..Default::default()
+        };
+        let mut fixture = DerivationFixture {
+            l1_blocks: vec![block],
+            ..Default::default()
+        };
+        assert!(fixture.validate_blob_slots().is_err());
+
+        fixture.l1_beacon_config = Some(BeaconConfig {
+            genesis_time: 100,
+            seconds_per_slot: 12,
+        });
+        fixture.validate_blob_slots().unwrap();
+
+        fixture.l1_beacon_config = Some(BeaconConfig {
+            genesis_time: 101,
+            seconds_per_slot: 12,
+        });
+        assert!(fixture.validate_blob_slots().is_err());
+    }
+
+    #[test]
+    fn test_validate_forks() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let mut fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        fixture.validate_forks().unwrap();
+
+        // Before Ecotone, payloads have no parent beacon block root.
+        fixture.rollup_config.ecotone_time = None;
+        assert!(fixture.validate_forks().is_err());
+        for payload in fixture.l2_payloads.values_mut() {
+            payload.parent_beacon_block_root = None;
+        }
+        fixture.validate_forks().unwrap();
+
+        // Before Canyon, payloads have no withdrawals.
+        fixture.rollup_config.canyon_time = Some(u64::MAX);
+        assert!(fixture.validate_forks().is_err());
+        for payload in fixture.l2_payloads.values_mut() {
+            payload.withdrawals = None;
+        }
+        fixture.validate_forks().unwrap();
+
+        // Pre-Ecotone batches are calldata only.
+        fixture.l1_blocks[0].blobs.push(Box::default());
+        assert!(fixture.validate_forks().is_err());
+        fixture.l1_blocks[0].blobs.clear();
+
+        // Packed Ecotone scalars must agree with the explicit fee scalars.
+        let config = fixture.l2_system_configs.get_mut(&1).unwrap();
+        config.scalar = (U256::from(1) << 248) | (U256::from(810949) << 32) | U256::from(1368);
+        config.base_fee_scalar = Some(1368);
+        config.blob_base_fee_scalar = Some(810949);
+        fixture.validate_forks().unwrap();
+        let config = fixture.l2_system_configs.get_mut(&1).unwrap();
+        config.base_fee_scalar = Some(0);
+        assert!(fixture.validate_forks().is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let [parent, block] = chain_with_tx();
+        let info = L2BlockInfo {
+            block_info: BlockInfo {
+                number: 1,
+                ..Default::default()
+            },
+            l1_origin: BlockId {
+                hash: block.header.hash_slow(),
+                number: 1,
+            },
+            seq_num: 0,
+        };
+        let fixture = DerivationFixture {
+            l1_blocks: vec![parent, block],
+            l2_payloads: [(1, PayloadAttributes::default())].into_iter().collect(),
+            l2_block_infos: [(1, info)].into_iter().collect(),
+            l2_cursor_start: 0,
+            l2_cursor_end: 1,
+            ..Default::default()
+        };
+        assert_eq!(fixture.validate(), vec![]);
+
+        // L1 origins must be among the L1 blocks, by hash as well as number.
+        let mut forged = fixture.clone();
+        forged.l1_blocks[1].header.timestamp += 1;
+        let violations = forged.validate();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].check, "l1-origins");
+        let mut missing = fixture.clone();
+        missing.l1_blocks.pop();
+        assert_eq!(
+            missing.validate()[0].to_string(),
+            "l1-origins: L1 origin 1 of L2 block 1 is not among the L1 blocks"
+        );
+
+        // Every check reports, rather than the first failure hiding the rest.
+        let mut broken = fixture.clone();
+        broken.l2_cursor_start = 3;
+        broken.l1_blocks[1].header.parent_hash = B256::ZERO;
+        broken.l1_blocks[0].transactions[0] = bytes!("02f8");
+        let checks = broken
+            .validate()
+            .into_iter()
+            .map(|v| v.check)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            checks,
+            vec!["cursors", "l1-chain", "roots", "transactions", "l1-origins"]
+        );
+
+        let mut outside = fixture.clone();
+        outside.l2_payloads.insert(3, PayloadAttributes::default());
+        let violations = outside.validate();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "cursors: L2 payload for block 3 is outside the cursor range (0, 1]"
+        );
+
+        // The starting cursor is the safe head, which is not derived.
+        let mut at_start = fixture;
+        at_start.l2_payloads.insert(0, PayloadAttributes::default());
+        assert_eq!(
+            at_start.validate()[0].to_string(),
+            "cursors: L2 payload for block 0 is outside the cursor range (0, 1]"
+        );
+    }
+
+    #[test]
+    fn test_repair() {
+        let [parent, block] = chain_with_tx();
+        let mut fixture = DerivationFixture {
+            l1_blocks: vec![block.clone(), parent.clone(), block],
+            ..Default::default()
+        };
+        let checks = fixture
+            .validate()
+            .into_iter()
+            .map(|v| v.check)
+            .collect::<Vec<_>>();
+        assert_eq!(checks, vec!["l1-chain"]);
+
+        let repairs = fixture.repair();
+        assert_eq!(repairs.len(), 2);
+        assert_eq!(fixture.l1_blocks.len(), 2);
+        assert_eq!(fixture.validate(), vec![]);
+        assert!(fixture.repair().is_empty());
+
+        // A broken chain is not papered over.
+        let mut broken = fixture.clone();
+        broken.l1_blocks[1].header.parent_hash = B256::ZERO;
+        assert!(broken.repair().is_empty());
+        assert_eq!(broken.validate().len(), 1);
+
+        // Headers are never rewritten to match the bodies, since that would change the
+        // block hashes the chain and the L2 data commit to.
+        let mut wrong_bodies = fixture;
+        wrong_bodies.l1_blocks[0].receipts[0].cumulative_gas_used += 1;
+        let headers = wrong_bodies
+            .l1_blocks
+            .iter()
+            .map(|b| b.header.clone())
+            .collect::<Vec<_>>();
+        assert!(wrong_bodies.repair().is_empty());
+        assert!(wrong_bodies
+            .l1_blocks
+            .iter()
+            .map(|b| &b.header)
+            .eq(&headers));
+        let checks = wrong_bodies
+            .validate()
+            .into_iter()
+            .map(|v| v.check)
+            .collect::<Vec<_>>();
+        assert_eq!(checks, vec!["roots"]);
+    }
+
+    #[test]
+    fn test_summary() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let mut fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        fixture.rollup_config = RollupConfig {
+            l1_chain_id: 1,
+            l2_chain_id: 10,
+            regolith_time: Some(0),
+            canyon_time: Some(0),
+            delta_time: Some(0),
+            ecotone_time: Some(1722550778),
+            ..Default::default()
+        };
+        let summary = fixture.summary();
+        assert_eq!(
+            summary,
+            Summary {
+                version: SchemaVersion::UNVERSIONED,
+                l1_chain_id: 1,
+                l2_chain_id: 10,
+                l1_range: Some((1, 2)),
+                l2_range: (0, 2),
+                l1_blocks: 3,
+                transactions: 6,
+                transaction_bytes: 690,
+                blobs: 0,
+                blob_bytes: 0,
+                l2_payloads: 2,
+                forks: vec!["regolith", "canyon", "delta"],
+                activations: vec!["ecotone"],
+            }
+        );
+
+        let empty = DerivationFixture::default().summary();
+        assert_eq!(empty.l1_range, None);
+        assert!(empty.forks.is_empty() && empty.activations.is_empty());
+    }
+
+    #[test]
+    fn test_output_roots() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        let mut executed = Vec::new();
+        let roots = fixture
+            .output_roots(|number, _| {
+                executed.push(number);
+                Ok(L2Output {
+                    block_hash: fixture.l2_block_infos[&number].block_info.hash,
+                    ..Default::default()
+                })
+            })
+            .unwrap();
+        assert_eq!(executed, vec![1, 2]);
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[1].l2_block_number, 2);
+        assert_eq!(
+            roots[1].root,
+            L2Output {
+                block_hash: fixture.l2_block_infos[&2].block_info.hash,
+                ..Default::default()
+            }
+            .root()
+        );
+
+        // A block that executes differently from the chain is caught.
+        assert!(fixture
+            .output_roots(|_, _| Ok(L2Output::default()))
+            .is_err());
+        assert!(fixture
+            .output_roots(|_, _| Err(eyre!("missing state")))
+            .is_err());
+    }
+
+    #[test]
+    fn test_check_hint() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        let l1_hash = fixture.l1_blocks[0].header.hash_slow();
+        fixture.check_hint(&Hint::L1BlockHeader(l1_hash)).unwrap();
+        fixture.check_hint(&Hint::L1Receipts(l1_hash)).unwrap();
+        assert!(fixture.check_hint(&Hint::L1Receipts(B256::ZERO)).is_err());
+
+        let l2_hash = fixture
+            .l2_block_infos
+            .values()
+            .next()
+            .unwrap()
+            .block_info
+            .hash;
+        fixture.check_hint(&Hint::L2Transactions(l2_hash)).unwrap();
+        assert!(fixture.check_hint(&Hint::L2BlockHeader(l1_hash)).is_err());
+        assert!(fixture.check_hint(&Hint::L2Output(l2_hash)).is_err());
+    }
+
+    #[test]
+    fn test_annotations_roundtrip() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        let serialized = serde_json::to_string(&fixture).unwrap();
+        assert!(!serialized.contains("description"));
+        assert!(!serialized.contains("notes"));
+
+        let mut annotated = fixture.clone();
+        annotated.description = Some("Channel spanning two L1 blocks".to_string());
+        annotated.l1_blocks[0].notes.insert(
+            "batch".to_string(),
+            "First frame of the channel".to_string(),
+        );
+        let serialized = serde_json::to_string(&annotated).unwrap();
+        let roundtrip: DerivationFixture = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(roundtrip, annotated);
+    }
+
+    #[test]
+    fn test_fixture_block_roots() {
+        let empty = FixtureBlock::default();
+        assert_eq!(empty.transactions_root(), alloy_trie::EMPTY_ROOT_HASH);
+        assert_eq!(empty.receipts_root().unwrap(), alloy_trie::EMPTY_ROOT_HASH);
+
+        // A single leaf trie is the hash of the leaf node `[hex_prefix(key), value]`,
+        // where the key is `rlp(0) = 0x80`.
+        let mut leaf = Vec::new();
+        rlp::Header {
+            list: true,
+            payload_length: [0x20u8, 0x80].as_slice().length() + TX.length(),
+        }
+        .encode(&mut leaf);
+        [0x20u8, 0x80].as_slice().encode(&mut leaf);
+        TX.encode(&mut leaf);
+
+        let [mut block, _] = chain_with_tx();
+        block.header = Header::default();
+        assert_eq!(block.transactions_root(), keccak256(&leaf));
+        assert!(block.validate_roots().is_err());
+
+        block.header.transactions_root = block.transactions_root();
+        block.header.receipts_root = block.receipts_root().unwrap();
+        block.validate_roots().unwrap();
+
+        // The receipt is typed by its transaction, so the same receipt for a legacy
+        // transaction has a different root.
+        let mut legacy = block.clone();
+        legacy.transactions = vec![bytes!("f8")];
+        assert_ne!(
+            legacy.receipts_root().unwrap(),
+            block.receipts_root().unwrap()
+        );
+
+        block.receipts.clear();
+        assert!(block.receipts_root().is_err());
+    }
+
+    #[test]
+    fn test_fixture_block() {
+        let fixture_str = include_str!("./testdata/fixture_block.json");
+        let fixture: FixtureBlock = serde_json::from_str(fixture_str).unwrap();
+        assert_eq!(fixture.header.number, 1);
+        assert_eq!(
+            fixture.header.parent_hash,
+            b256!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")
+        );
+        assert_eq!(fixture.header.timestamp, 102);
+        assert_eq!(fixture.transactions.len(), 1);
+        assert_eq!(fixture.blobs.len(), 0);
+    }
+}
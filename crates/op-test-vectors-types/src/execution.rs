@@ -0,0 +1,693 @@
+//! Module containing the execution test fixture.
+//!
+//! The fixture is generic over the transaction and receipt types, which are whatever the
+//! EVM that produced it serializes them as. They default to raw JSON values, so fixtures
+//! can be read without that EVM's types; `op-test-vectors` fills them in with anvil's.
+
+use crate::l2::PayloadAttributes;
+use alloy_primitives::{address, Address, Bloom, Bytes, Log as PrimitiveLog, B256, U256};
+use alloy_rpc_types::trace::geth::AccountState;
+use color_eyre::eyre::{self, ensure, eyre};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The selector of the `Error(string)` revert payload.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// The address of the `L1Block` predeploy, which holds the L1 fee parameters.
+pub const L1_BLOCK_ADDRESS: Address = address!("4200000000000000000000000000000000000015");
+
+/// The `L1Block` storage slots read by the L1 cost functions.
+const L1_BASE_FEE_SLOT: u64 = 1;
+const L1_FEE_SCALARS_SLOT: u64 = 3;
+const L1_FEE_OVERHEAD_SLOT: u64 = 5;
+const L1_FEE_SCALAR_SLOT: u64 = 6;
+const L1_BLOB_BASE_FEE_SLOT: u64 = 7;
+
+/// The execution fixture is the top-level object that contains
+/// everything needed to run an execution test.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionFixture<T = Value, R = Value> {
+    /// A description of the scenario the fixture covers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether the fixture is deprecated, so fixture runs skip it by default.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// The path of the fixture that supersedes this one, relative to this fixture.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<String>,
+    /// The execution environment sets up the block context of the first block the
+    /// transactions were mined in.
+    pub env: ExecutionEnvironment,
+    /// The initial state of the accounts before running the transactions, also called the
+    /// "pre-state".
+    pub alloc: HashMap<Address, AccountState>,
+    /// The expected state of the accounts after running the transactions, also called the
+    /// "post-state".
+    pub out_alloc: HashMap<Address, AccountState>,
+    /// Transactions to execute.
+    #[serde(rename = "txs")]
+    pub transactions: Vec<T>,
+    /// The expected result after executing transactions.
+    pub result: ExecutionResult<R>,
+    /// The engine payload attributes that build each block the transactions were mined
+    /// in, in order, linking the fixture to the derivation layer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub payload_attributes: Vec<PayloadAttributes>,
+    /// The execution environment of each block the transactions were mined in, in order
+    /// and aligned with the payload attributes, so each block's fees can be reproduced
+    /// with its own base fee and L1 fee parameters.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub block_environments: Vec<ExecutionEnvironment>,
+    /// Behavior the transactions are expected to show beyond the post-state, checked by
+    /// [ExecutionFixture::check_expectations].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expectations: Vec<TransactionExpectation>,
+}
+
+impl<T, R> Default for ExecutionFixture<T, R> {
+    fn default() -> Self {
+        Self {
+            description: None,
+            deprecated: false,
+            superseded_by: None,
+            env: ExecutionEnvironment::default(),
+            alloc: HashMap::new(),
+            out_alloc: HashMap::new(),
+            transactions: Vec::new(),
+            result: ExecutionResult::default(),
+            payload_attributes: Vec::new(),
+            block_environments: Vec::new(),
+            expectations: Vec::new(),
+        }
+    }
+}
+
+impl<T, R> ExecutionFixture<T, R> {
+    /// Checks the outcome of executing each transaction, in order, against the
+    /// expectations for it.
+    pub fn check_expectations(&self, outcomes: &[TransactionOutcome]) -> eyre::Result<()> {
+        for expectation in &self.expectations {
+            let index = expectation.transaction_index;
+            let outcome = outcomes
+                .get(index)
+                .ok_or_else(|| eyre!("Missing outcome for transaction {index}"))?;
+            expectation
+                .check(outcome)
+                .map_err(|e| eyre!("Transaction {index}: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// What executing a transaction produced, as reported by the EVM under test.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionOutcome {
+    /// Whether the transaction succeeded rather than reverted or halted.
+    pub success: bool,
+    /// The return data, or the revert data if the transaction reverted.
+    pub output: Bytes,
+    /// The logs emitted, in order.
+    pub logs: Vec<PrimitiveLog>,
+}
+
+/// The expected behavior of a single transaction. Unset fields are not checked.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionExpectation {
+    /// The index of the transaction in the fixture's transactions.
+    pub transaction_index: usize,
+    /// Whether the transaction reverts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverts: Option<bool>,
+    /// The message of the `Error(string)` the transaction reverts with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    /// The exact return data, or revert data if the transaction reverts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<Bytes>,
+    /// The logs the transaction emits, in order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs: Option<Vec<LogExpectation>>,
+}
+
+impl TransactionExpectation {
+    /// Checks an outcome against the expectation.
+    pub fn check(&self, outcome: &TransactionOutcome) -> eyre::Result<()> {
+        if let Some(reverts) = self.reverts {
+            ensure!(
+                reverts != outcome.success,
+                "Expected the transaction to {}",
+                if reverts { "revert" } else { "succeed" }
+            );
+        }
+        if let Some(reason) = &self.revert_reason {
+            ensure!(!outcome.success, "Expected a revert with reason {reason:?}");
+            let actual = revert_reason(&outcome.output).ok_or_else(|| {
+                eyre!("Expected revert reason {reason:?}, got {}", outcome.output)
+            })?;
+            ensure!(
+                actual == *reason,
+                "Expected revert reason {reason:?}, got {actual:?}"
+            );
+        }
+        if let Some(data) = &self.return_data {
+            ensure!(
+                *data == outcome.output,
+                "Expected output {data}, got {}",
+                outcome.output
+            );
+        }
+        if let Some(logs) = &self.logs {
+            ensure!(
+                logs.len() == outcome.logs.len(),
+                "Expected {} logs, got {}",
+                logs.len(),
+                outcome.logs.len()
+            );
+            for (i, (expected, log)) in logs.iter().zip(&outcome.logs).enumerate() {
+                expected.check(log).map_err(|e| eyre!("Log {i}: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The expected contents of an emitted log. Unset fields are not checked.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogExpectation {
+    /// The address of the emitting contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// The exact topics, in order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topics: Option<Vec<B256>>,
+    /// The exact data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
+}
+
+impl LogExpectation {
+    /// Checks a log against the expectation.
+    pub fn check(&self, log: &PrimitiveLog) -> eyre::Result<()> {
+        if let Some(address) = self.address {
+            ensure!(
+                address == log.address,
+                "Expected address {address}, got {}",
+                log.address
+            );
+        }
+        if let Some(topics) = &self.topics {
+            ensure!(
+                topics.as_slice() == log.topics(),
+                "Expected topics {topics:?}, got {:?}",
+                log.topics()
+            );
+        }
+        if let Some(data) = &self.data {
+            ensure!(
+                *data == log.data.data,
+                "Expected data {data}, got {}",
+                log.data.data
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the message of an ABI-encoded `Error(string)` revert payload.
+fn revert_reason(output: &[u8]) -> Option<String> {
+    let body = output.strip_prefix(&ERROR_SELECTOR)?;
+    let word = |at: usize| -> Option<usize> {
+        let word = body.get(at..at.checked_add(32)?)?;
+        // Offsets and lengths beyond a u64 cannot be in bounds.
+        if word[..24].iter().any(|&b| b != 0) {
+            return None;
+        }
+        usize::try_from(u64::from_be_bytes(word[24..].try_into().ok()?)).ok()
+    };
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32)?;
+    let message = body.get(start..start.checked_add(len)?)?;
+    String::from_utf8(message.to_vec()).ok()
+}
+
+/// The execution environment is the initial state of the execution context.
+/// It's used to set the execution environment current block information.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionEnvironment {
+    /// The current block coinbase.
+    pub current_coinbase: Address,
+    /// The current block difficulty.
+    pub current_difficulty: U256,
+    /// The current block gas limit.
+    pub current_gas_limit: U256,
+    /// The previous block hash.
+    pub previous_hash: B256,
+    /// The current block number.
+    pub current_number: U256,
+    /// The current block timestamp.
+    pub current_timestamp: U256,
+    /// The current block base fee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_base_fee: Option<U256>,
+    /// The current block excess blob gas, from Ecotone on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_excess_blob_gas: Option<U256>,
+    /// The current block blob base fee, derived from the excess blob gas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_blob_base_fee: Option<U256>,
+    /// The L1 fee parameters the transactions were charged with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee: Option<L1FeeEnvironment>,
+    /// The chain ID the transactions were executed on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<U256>,
+    /// The block hashes of the previous blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hashes: Option<HashMap<U256, B256>>,
+}
+
+/// The L1 cost function, which changes with the fork active at the block.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum L1CostFunction {
+    /// Calldata gas plus overhead, times the L1 base fee and scalar, with a fixed
+    /// allowance for the signature.
+    Bedrock,
+    /// The Bedrock cost function, without the signature allowance.
+    Regolith,
+    /// Calldata gas times the scaled L1 base fee and blob base fee.
+    Ecotone,
+    /// The estimated compressed size times the scaled L1 base fee and blob base fee.
+    Fjord,
+}
+
+impl L1CostFunction {
+    /// Returns the constants the cost function uses.
+    pub const fn constants(self) -> L1FeeConstants {
+        let bedrock = L1FeeConstants {
+            zero_byte_cost: 4,
+            non_zero_byte_cost: 16,
+            signature_cost: 68 * 16,
+            divisor: 1_000_000,
+            fastlz_coefficient: None,
+            cost_intercept: None,
+            min_tx_size_scaled: None,
+        };
+        match self {
+            Self::Bedrock => bedrock,
+            Self::Regolith => L1FeeConstants {
+                signature_cost: 0,
+                ..bedrock
+            },
+            Self::Ecotone => L1FeeConstants {
+                signature_cost: 0,
+                divisor: 16 * 1_000_000,
+                ..bedrock
+            },
+            Self::Fjord => L1FeeConstants {
+                signature_cost: 0,
+                divisor: 1_000_000_000_000,
+                fastlz_coefficient: Some(836_500),
+                cost_intercept: Some(-42_585_600),
+                min_tx_size_scaled: Some(100 * 1_000_000),
+                ..bedrock
+            },
+        }
+    }
+}
+
+/// The constants of an [L1CostFunction], recorded so runners need not hardcode them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct L1FeeConstants {
+    /// The gas charged per zero byte of calldata.
+    pub zero_byte_cost: u64,
+    /// The gas charged per non-zero byte of calldata.
+    pub non_zero_byte_cost: u64,
+    /// The gas added for the signature, which deposits omit from their calldata.
+    pub signature_cost: u64,
+    /// The divisor of the product of the gas or size and the scaled fees.
+    pub divisor: u64,
+    /// The coefficient of the FastLZ compressed size in the size estimate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fastlz_coefficient: Option<u64>,
+    /// The intercept of the size estimate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_intercept: Option<i64>,
+    /// The minimum size estimate, scaled by 1e6.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_tx_size_scaled: Option<u64>,
+}
+
+/// The L1 fee parameters of a block, as stored in the `L1Block` predeploy by the L1 info
+/// deposit of the block's L1 origin.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct L1FeeEnvironment {
+    /// The cost function active at the block.
+    pub cost_function: L1CostFunction,
+    /// The constants of the cost function.
+    pub constants: L1FeeConstants,
+    /// The base fee of the L1 origin.
+    pub l1_base_fee: U256,
+    /// The blob base fee of the L1 origin, from Ecotone on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_blob_base_fee: Option<U256>,
+    /// The scalar applied to the L1 base fee, from Ecotone on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_scalar: Option<u32>,
+    /// The scalar applied to the L1 blob base fee, from Ecotone on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_base_fee_scalar: Option<u32>,
+    /// The pre-Ecotone fee overhead, also used by Ecotone while its scalars are unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee_overhead: Option<U256>,
+    /// The pre-Ecotone fee scalar, also used by Ecotone while its scalars are unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee_scalar: Option<U256>,
+}
+
+impl L1FeeEnvironment {
+    /// Reads the fee parameters used by `cost_function` from the `L1Block` predeploy,
+    /// given a reader of its storage slots.
+    pub fn from_storage(
+        cost_function: L1CostFunction,
+        mut storage: impl FnMut(U256) -> eyre::Result<U256>,
+    ) -> eyre::Result<Self> {
+        let mut slot = |slot: u64| storage(U256::from(slot));
+        let l1_base_fee = slot(L1_BASE_FEE_SLOT)?;
+        let (mut l1_blob_base_fee, mut base_fee_scalar, mut blob_base_fee_scalar) =
+            (None, None, None);
+        let mut uses_bedrock_fields = matches!(
+            cost_function,
+            L1CostFunction::Bedrock | L1CostFunction::Regolith
+        );
+        if !uses_bedrock_fields {
+            // The scalars are packed as big-endian u32s after the first 16 bytes.
+            let scalars = slot(L1_FEE_SCALARS_SLOT)?.to_be_bytes::<32>();
+            let scalar =
+                |at: usize| u32::from_be_bytes(scalars[at..at + 4].try_into().expect("4 bytes"));
+            l1_blob_base_fee = Some(slot(L1_BLOB_BASE_FEE_SLOT)?);
+            base_fee_scalar = Some(scalar(16));
+            blob_base_fee_scalar = Some(scalar(20));
+            // Ecotone falls back to the Bedrock parameters until the scalars are set.
+            uses_bedrock_fields =
+                cost_function == L1CostFunction::Ecotone && scalar(16) == 0 && scalar(20) == 0;
+        }
+        let (l1_fee_overhead, l1_fee_scalar) = if uses_bedrock_fields {
+            (
+                Some(slot(L1_FEE_OVERHEAD_SLOT)?),
+                Some(slot(L1_FEE_SCALAR_SLOT)?),
+            )
+        } else {
+            (None, None)
+        };
+        Ok(Self {
+            cost_function,
+            constants: cost_function.constants(),
+            l1_base_fee,
+            l1_blob_base_fee,
+            base_fee_scalar,
+            blob_base_fee_scalar,
+            l1_fee_overhead,
+            l1_fee_scalar,
+        })
+    }
+}
+
+/// The execution result is the expected result after running the transactions
+/// in the execution environment over the pre-state.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionResult<R = Value> {
+    /// The state root.
+    pub state_root: B256,
+    /// The transaction root.
+    pub tx_root: B256,
+    /// The receipt root.
+    pub receipt_root: B256,
+    /// The logs bloom.
+    pub logs_bloom: Bloom,
+    /// A list of execution receipts for each executed transaction.
+    pub receipts: Vec<ExecutionReceipt<R>>,
+}
+
+impl<R> Default for ExecutionResult<R> {
+    fn default() -> Self {
+        Self {
+            state_root: B256::ZERO,
+            tx_root: B256::ZERO,
+            receipt_root: B256::ZERO,
+            logs_bloom: Bloom::ZERO,
+            receipts: Vec::new(),
+        }
+    }
+}
+
+/// An execution receipt is the result of running a transaction in the execution environment.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionReceipt<R = Value> {
+    /// The state root.
+    pub root: B256,
+    /// The hash of the transaction.
+    pub transaction_hash: B256,
+    /// The contract address that the transaction created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<Address>,
+    /// The gas used by the transaction.
+    pub gas_used: U256,
+    /// The block hash.
+    pub block_hash: B256,
+    /// The transaction index.
+    pub transaction_index: U256,
+    /// The Isthmus operator fee scalar the transaction was charged with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_fee_scalar: Option<U256>,
+    /// The Isthmus operator fee constant the transaction was charged with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_fee_constant: Option<U256>,
+    /// The inner log receipt.
+    #[serde(flatten)]
+    pub inner: R,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256, bytes, LogData};
+
+    #[test]
+    fn test_serialize_execution_environment() {
+        let expected_env = include_str!("./testdata/environment.json");
+        let env = serde_json::from_str::<ExecutionEnvironment>(expected_env)
+            .expect("failed to parse environment");
+        let serialized_env = serde_json::to_string(&env).expect("failed to serialize environment");
+        let serialized_value = serde_json::from_str::<Value>(&serialized_env)
+            .expect("failed to parse serialized environment");
+        let expected_value = serde_json::from_str::<Value>(expected_env)
+            .expect("failed to parse expected environment");
+        assert_eq!(serialized_value, expected_value);
+    }
+
+    #[test]
+    fn test_execution_environment_chain_fields() {
+        let expected_env = include_str!("./testdata/environment.json");
+        let mut env = serde_json::from_str::<ExecutionEnvironment>(expected_env)
+            .expect("failed to parse environment");
+        assert_eq!(env.current_base_fee, None);
+        assert_eq!(env.chain_id, None);
+
+        env.current_base_fee = Some(U256::from(7));
+        env.chain_id = Some(U256::from(8453));
+        let value = serde_json::to_value(&env).expect("failed to serialize environment");
+        assert_eq!(value["currentBaseFee"], "0x7");
+        assert_eq!(value["chainId"], "0x2105");
+        let parsed: ExecutionEnvironment =
+            serde_json::from_value(value).expect("failed to parse serialized environment");
+        assert_eq!(parsed.current_base_fee, env.current_base_fee);
+        assert_eq!(parsed.chain_id, env.chain_id);
+    }
+
+    #[test]
+    fn test_l1_fee_environment_from_storage() {
+        let mut scalars = [0u8; 32];
+        scalars[16..20].copy_from_slice(&1368u32.to_be_bytes());
+        scalars[20..24].copy_from_slice(&810_949u32.to_be_bytes());
+        let mut slots = HashMap::from([
+            (L1_BASE_FEE_SLOT, U256::from(7_000_000_000u64)),
+            (L1_FEE_SCALARS_SLOT, U256::from_be_bytes(scalars)),
+            (L1_FEE_OVERHEAD_SLOT, U256::from(188)),
+            (L1_FEE_SCALAR_SLOT, U256::from(684_000)),
+            (L1_BLOB_BASE_FEE_SLOT, U256::from(1)),
+        ]);
+        let read = |slots: &HashMap<u64, U256>, cost_function| {
+            L1FeeEnvironment::from_storage(cost_function, |slot| {
+                Ok(slots.get(&slot.to::<u64>()).copied().unwrap_or_default())
+            })
+            .unwrap()
+        };
+
+        let fjord = read(&slots, L1CostFunction::Fjord);
+        assert_eq!(fjord.l1_base_fee, U256::from(7_000_000_000u64));
+        assert_eq!(fjord.l1_blob_base_fee, Some(U256::from(1)));
+        assert_eq!(fjord.base_fee_scalar, Some(1368));
+        assert_eq!(fjord.blob_base_fee_scalar, Some(810_949));
+        assert_eq!((fjord.l1_fee_overhead, fjord.l1_fee_scalar), (None, None));
+        assert_eq!(fjord.constants.cost_intercept, Some(-42_585_600));
+        let value = serde_json::to_value(&fjord).unwrap();
+        assert_eq!(value["costFunction"], "fjord");
+        assert_eq!(value["baseFeeScalar"], 1368);
+        assert_eq!(value["constants"]["divisor"], 1_000_000_000_000u64);
+        assert_eq!(
+            serde_json::from_value::<L1FeeEnvironment>(value).unwrap(),
+            fjord
+        );
+
+        let bedrock = read(&slots, L1CostFunction::Bedrock);
+        assert_eq!(bedrock.l1_fee_overhead, Some(U256::from(188)));
+        assert_eq!(bedrock.l1_fee_scalar, Some(U256::from(684_000)));
+        assert_eq!(bedrock.base_fee_scalar, None);
+        assert_eq!(bedrock.constants.signature_cost, 68 * 16);
+
+        // Ecotone uses the Bedrock parameters until its scalars are set.
+        assert_eq!(read(&slots, L1CostFunction::Ecotone).l1_fee_overhead, None);
+        slots.insert(L1_FEE_SCALARS_SLOT, U256::ZERO);
+        let ecotone = read(&slots, L1CostFunction::Ecotone);
+        assert_eq!(ecotone.base_fee_scalar, Some(0));
+        assert_eq!(ecotone.l1_fee_overhead, Some(U256::from(188)));
+        assert_eq!(ecotone.constants.divisor, 16_000_000);
+    }
+
+    #[test]
+    fn test_block_environments() {
+        let env = serde_json::from_str::<ExecutionEnvironment>(include_str!(
+            "./testdata/environment.json"
+        ))
+        .expect("failed to parse environment");
+        let mut fixture = ExecutionFixture::<Value, Value> {
+            env: env.clone(),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&fixture).expect("failed to serialize fixture");
+        assert!(value.get("blockEnvironments").is_none());
+
+        let mut next = env.clone();
+        next.current_number += U256::from(1);
+        next.current_base_fee = Some(U256::from(7));
+        fixture.block_environments = vec![env, next];
+        let value = serde_json::to_value(&fixture).expect("failed to serialize fixture");
+        assert_eq!(value["blockEnvironments"][1]["currentBaseFee"], "0x7");
+        let parsed: ExecutionFixture =
+            serde_json::from_value(value).expect("failed to parse serialized fixture");
+        assert_eq!(parsed.block_environments.len(), 2);
+        assert_eq!(
+            parsed.block_environments[1].current_base_fee,
+            Some(U256::from(7))
+        );
+    }
+
+    #[test]
+    fn test_serialize_execution_result() {
+        let expected_result = include_str!("./testdata/result.json");
+        let execution_result = serde_json::from_str::<ExecutionResult>(expected_result)
+            .expect("failed to parse result");
+        let serialized_result =
+            serde_json::to_string(&execution_result).expect("failed to serialize result");
+        let serialized_value = serde_json::from_str::<Value>(&serialized_result)
+            .expect("failed to parse serialized result");
+        let expected_value = serde_json::from_str::<Value>(expected_result)
+            .expect("failed to parse expected result");
+        assert_eq!(serialized_value, expected_value);
+    }
+
+    #[test]
+    fn test_revert_reason() {
+        let output = bytes!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "000000000000000000000000000000000000000000000000000000000000000e"
+            "4e6f7420617574686f72697a6564000000000000000000000000000000000000"
+        );
+        assert_eq!(revert_reason(&output).as_deref(), Some("Not authorized"));
+        assert_eq!(revert_reason(&output[..40]), None);
+        assert_eq!(revert_reason(&output[4..]), None);
+        assert_eq!(revert_reason(&[]), None);
+    }
+
+    #[test]
+    fn test_check_expectations() {
+        let emitter = address!("4200000000000000000000000000000000000042");
+        let topic = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+        let log = PrimitiveLog {
+            address: emitter,
+            data: LogData::new_unchecked(vec![topic], bytes!("01")),
+        };
+        let outcomes = vec![
+            TransactionOutcome {
+                success: true,
+                output: bytes!("2a"),
+                logs: vec![log],
+            },
+            TransactionOutcome {
+                success: false,
+                output: bytes!(
+                    "08c379a0"
+                    "0000000000000000000000000000000000000000000000000000000000000020"
+                    "0000000000000000000000000000000000000000000000000000000000000002"
+                    "6e6f000000000000000000000000000000000000000000000000000000000000"
+                ),
+                logs: vec![],
+            },
+        ];
+        let mut fixture: ExecutionFixture = ExecutionFixture {
+            expectations: vec![
+                TransactionExpectation {
+                    transaction_index: 0,
+                    reverts: Some(false),
+                    return_data: Some(bytes!("2a")),
+                    logs: Some(vec![LogExpectation {
+                        address: Some(emitter),
+                        topics: Some(vec![topic]),
+                        data: None,
+                    }]),
+                    ..Default::default()
+                },
+                TransactionExpectation {
+                    transaction_index: 1,
+                    reverts: Some(true),
+                    revert_reason: Some("no".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        fixture.check_expectations(&outcomes).unwrap();
+        assert!(fixture.check_expectations(&outcomes[..1]).is_err());
+
+        let json = serde_json::to_value(&fixture.expectations).unwrap();
+        assert_eq!(json[1]["revertReason"], "no");
+        assert!(json[1].get("logs").is_none());
+        let parsed: Vec<TransactionExpectation> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, fixture.expectations);
+
+        fixture.expectations[1].revert_reason = Some("yes".to_string());
+        assert!(fixture.check_expectations(&outcomes).is_err());
+        fixture.expectations[1].revert_reason = None;
+        fixture.expectations[0].logs.as_mut().unwrap()[0].data = Some(bytes!("02"));
+        assert!(fixture.check_expectations(&outcomes).is_err());
+        fixture.expectations[0].logs = Some(vec![]);
+        assert!(fixture.check_expectations(&outcomes).is_err());
+        fixture.expectations[0].logs = None;
+        fixture.expectations[0].reverts = Some(true);
+        assert!(fixture.check_expectations(&outcomes).is_err());
+    }
+}
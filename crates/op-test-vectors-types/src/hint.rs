@@ -0,0 +1,212 @@
+//! Module containing the hints op-program sends its host to request preimages.
+//!
+//! A hint is a type followed by a space and the hex encoded data it refers to, e.g.
+//! `l1-block-header 0x…`. Hints are parsed strictly, so a hint type or data layout that
+//! changed between op-program versions fails to parse instead of going unnoticed.
+
+use alloy_primitives::{hex, Address, Bytes, B256};
+use color_eyre::eyre::{self, ensure, eyre};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// A hint from op-program to its host.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Hint {
+    /// The header of the L1 block with the given hash.
+    L1BlockHeader(B256),
+    /// The transactions of the L1 block with the given hash.
+    L1Transactions(B256),
+    /// The receipts of the L1 block with the given hash.
+    L1Receipts(B256),
+    /// A blob, by its versioned hash and the timestamp of the L1 block carrying it.
+    L1Blob {
+        /// The versioned hash of the blob.
+        versioned_hash: B256,
+        /// The timestamp of the L1 block carrying the blob.
+        timestamp: u64,
+    },
+    /// The result of an L1 precompile call.
+    L1Precompile {
+        /// The address of the precompile.
+        address: Address,
+        /// The input of the call.
+        input: Bytes,
+    },
+    /// The header of the L2 block with the given hash.
+    L2BlockHeader(B256),
+    /// The transactions of the L2 block with the given hash.
+    L2Transactions(B256),
+    /// The contract code with the given hash.
+    L2Code(B256),
+    /// The L2 state trie node with the given hash.
+    L2StateNode(B256),
+    /// The L2 output with the given root.
+    L2Output(B256),
+}
+
+impl Hint {
+    /// Returns the hint type, e.g. `l1-block-header`.
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::L1BlockHeader(_) => "l1-block-header",
+            Self::L1Transactions(_) => "l1-transactions",
+            Self::L1Receipts(_) => "l1-receipts",
+            Self::L1Blob { .. } => "l1-blob",
+            Self::L1Precompile { .. } => "l1-precompile",
+            Self::L2BlockHeader(_) => "l2-block-header",
+            Self::L2Transactions(_) => "l2-transactions",
+            Self::L2Code(_) => "l2-code",
+            Self::L2StateNode(_) => "l2-state-node",
+            Self::L2Output(_) => "l2-output",
+        }
+    }
+
+    /// Returns the data the hint refers to, as it is hex encoded in the hint.
+    pub fn data(&self) -> Bytes {
+        match self {
+            Self::L1BlockHeader(hash)
+            | Self::L1Transactions(hash)
+            | Self::L1Receipts(hash)
+            | Self::L2BlockHeader(hash)
+            | Self::L2Transactions(hash)
+            | Self::L2Code(hash)
+            | Self::L2StateNode(hash)
+            | Self::L2Output(hash) => Bytes::copy_from_slice(hash.as_slice()),
+            Self::L1Blob {
+                versioned_hash,
+                timestamp,
+            } => [versioned_hash.as_slice(), &timestamp.to_be_bytes()]
+                .concat()
+                .into(),
+            Self::L1Precompile { address, input } => [address.as_slice(), input].concat().into(),
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind(), self.data())
+    }
+}
+
+impl FromStr for Hint {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        let (kind, data) = s
+            .split_once(' ')
+            .ok_or_else(|| eyre!("Hint {s:?} has no data"))?;
+        let data = hex::decode(data).map_err(|e| eyre!("Invalid {kind} hint data: {e}"))?;
+        let hash = || {
+            ensure!(
+                data.len() == 32,
+                "{kind} hint data is {} bytes, expected 32",
+                data.len()
+            );
+            Ok(B256::from_slice(&data))
+        };
+        let hint = match kind {
+            "l1-block-header" => Self::L1BlockHeader(hash()?),
+            "l1-transactions" => Self::L1Transactions(hash()?),
+            "l1-receipts" => Self::L1Receipts(hash()?),
+            "l1-blob" => {
+                ensure!(
+                    data.len() == 40,
+                    "l1-blob hint data is {} bytes, expected 40",
+                    data.len()
+                );
+                let (hash, timestamp) = data.split_at(32);
+                Self::L1Blob {
+                    versioned_hash: B256::from_slice(hash),
+                    timestamp: u64::from_be_bytes(timestamp.try_into()?),
+                }
+            }
+            "l1-precompile" => {
+                ensure!(
+                    data.len() >= 20,
+                    "l1-precompile hint data is {} bytes, expected at least 20",
+                    data.len()
+                );
+                let (address, input) = data.split_at(20);
+                Self::L1Precompile {
+                    address: Address::from_slice(address),
+                    input: Bytes::copy_from_slice(input),
+                }
+            }
+            "l2-block-header" => Self::L2BlockHeader(hash()?),
+            "l2-transactions" => Self::L2Transactions(hash()?),
+            "l2-code" => Self::L2Code(hash()?),
+            "l2-state-node" => Self::L2StateNode(hash()?),
+            "l2-output" => Self::L2Output(hash()?),
+            _ => return Err(eyre!("Unknown hint type {kind:?}")),
+        };
+        Ok(hint)
+    }
+}
+
+impl Serialize for Hint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256, bytes};
+
+    #[test]
+    fn test_hint_roundtrip() {
+        let hash = b256!("c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1");
+        let hints = [
+            Hint::L1BlockHeader(hash),
+            Hint::L1Transactions(hash),
+            Hint::L1Receipts(hash),
+            Hint::L1Blob {
+                versioned_hash: hash,
+                timestamp: 1722550777,
+            },
+            Hint::L1Precompile {
+                address: address!("000000000000000000000000000000000000000a"),
+                input: bytes!("01"),
+            },
+            Hint::L2BlockHeader(hash),
+            Hint::L2Transactions(hash),
+            Hint::L2Code(hash),
+            Hint::L2StateNode(hash),
+            Hint::L2Output(hash),
+        ];
+        for hint in hints {
+            assert_eq!(hint.to_string().parse::<Hint>().unwrap(), hint);
+            let json = serde_json::to_string(&hint).unwrap();
+            assert_eq!(serde_json::from_str::<Hint>(&json).unwrap(), hint);
+        }
+
+        let blob = Hint::L1Blob {
+            versioned_hash: hash,
+            timestamp: 1,
+        };
+        assert_eq!(
+            blob.to_string(),
+            "l1-blob 0xc5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c10000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_hint_rejects_drift() {
+        let hash = "0xc5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1c5e2b6c1";
+        assert!(format!("l1-block-header {hash}").parse::<Hint>().is_ok());
+        assert!(format!("l1-block-headers {hash}").parse::<Hint>().is_err());
+        assert!("l1-block-header 0xc5e2".parse::<Hint>().is_err());
+        assert!(format!("l1-blob {hash}").parse::<Hint>().is_err());
+        assert!("l1-block-header".parse::<Hint>().is_err());
+    }
+}
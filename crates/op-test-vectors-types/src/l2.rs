@@ -0,0 +1,451 @@
+//! Module containing the L2 types recorded in derivation and execution fixtures.
+//!
+//! These mirror the kona-derive types the pipeline produces, but their serialized form
+//! is owned by this crate, so a field rename or layout change in kona-derive does not
+//! break loading existing fixtures. `op-test-vectors` converts them to and from the
+//! kona-derive types for generating and running fixtures.
+
+use alloy_consensus::Header;
+use alloy_eips::eip4895::Withdrawal;
+use alloy_primitives::{address, keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{self as rlp, Decodable};
+use color_eyre::eyre::{self, bail, ensure, eyre};
+use serde::{Deserialize, Serialize};
+
+/// The address of the `L2ToL1MessagePasser` predeploy, whose storage root output roots
+/// commit to.
+pub const L2_TO_L1_MESSAGE_PASSER_ADDRESS: Address =
+    address!("4200000000000000000000000000000000000016");
+
+/// The EIP-2718 type of deposit transactions.
+const DEPOSIT_TX_TYPE: u8 = 0x7e;
+
+/// The selector of the Bedrock `setL1BlockValues` L1 info call.
+const BEDROCK_L1_INFO_SELECTOR: [u8; 4] = [0x01, 0x5d, 0x8e, 0xb9];
+
+/// The selector of the Ecotone `setL1BlockValuesEcotone` L1 info call.
+const ECOTONE_L1_INFO_SELECTOR: [u8; 4] = [0x44, 0x0a, 0x5e, 0x20];
+
+/// The selector of the Isthmus `setL1BlockValuesIsthmus` L1 info call.
+const ISTHMUS_L1_INFO_SELECTOR: [u8; 4] = [0x09, 0x89, 0x99, 0xbe];
+
+/// The version byte of a pre-Ecotone system config scalar.
+const LEGACY_SCALAR_VERSION: u8 = 0;
+
+/// The version byte of a system config scalar packing the Ecotone fee scalars.
+const ECOTONE_SCALAR_VERSION: u8 = 1;
+
+/// The divisor applied to the operator fee scalar, which has six decimals.
+const OPERATOR_FEE_SCALAR_DECIMALS: u64 = 1_000_000;
+
+/// The payload attributes the derivation pipeline produces for an L2 block.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadAttributes {
+    /// The timestamp of the new payload.
+    #[serde(with = "crate::quantity")]
+    pub timestamp: u64,
+    /// The random value of the new payload.
+    pub prev_randao: B256,
+    /// The suggested coinbase of the new payload.
+    #[serde(rename = "suggestedFeeRecipient")]
+    pub fee_recipient: Address,
+    /// The withdrawals to include, empty after Canyon and absent before.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// The parent beacon block root, set from Ecotone.
+    pub parent_beacon_block_root: Option<B256>,
+    /// The EIP-2718 encoded transactions forced into the start of the block.
+    pub transactions: Vec<Bytes>,
+    /// Whether to exclude transactions from the transaction pool.
+    pub no_tx_pool: bool,
+    /// The gas limit override.
+    #[serde(default, with = "crate::quantity::option")]
+    pub gas_limit: Option<u64>,
+}
+
+/// The L2 system config in effect at a block.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemConfig {
+    /// The address the batcher posts batches from.
+    pub batcher_address: Address,
+    /// The pre-Ecotone L1 fee overhead.
+    pub overhead: U256,
+    /// The pre-Ecotone L1 fee scalar.
+    pub scalar: U256,
+    /// The L2 block gas limit.
+    #[serde(with = "crate::quantity")]
+    pub gas_limit: u64,
+    /// The Ecotone base fee scalar.
+    pub base_fee_scalar: Option<u64>,
+    /// The Ecotone blob base fee scalar.
+    pub blob_base_fee_scalar: Option<u64>,
+    /// The Isthmus operator fee scalar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_fee_scalar: Option<u32>,
+    /// The Isthmus operator fee constant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_fee_constant: Option<u64>,
+}
+
+impl SystemConfig {
+    /// Returns whether `scalar` packs the Ecotone fee scalars rather than holding a legacy
+    /// pre-Ecotone scalar.
+    pub fn has_ecotone_scalar(&self) -> bool {
+        self.scalar.to_be_bytes::<32>()[0] == ECOTONE_SCALAR_VERSION
+    }
+
+    /// Decodes the base fee and blob base fee scalars from `scalar`, which holds either a
+    /// legacy pre-Ecotone scalar or the packed Ecotone scalars, as told by its version
+    /// byte.
+    ///
+    /// A legacy scalar is its own base fee scalar and has no blob base fee scalar. Like
+    /// op-node, a legacy scalar that doesn't fit in a `u32` is rejected rather than
+    /// truncated.
+    pub fn fee_scalars(&self) -> eyre::Result<(u32, u32)> {
+        let scalar = self.scalar.to_be_bytes::<32>();
+        let u32_at = |i: usize| u32::from_be_bytes(scalar[i..i + 4].try_into().expect("4 bytes"));
+        match scalar[0] {
+            LEGACY_SCALAR_VERSION => {
+                ensure!(
+                    scalar[1..28].iter().all(|&b| b == 0),
+                    "Legacy scalar {} has non-zero padding",
+                    self.scalar
+                );
+                Ok((u32_at(28), 0))
+            }
+            ECOTONE_SCALAR_VERSION => {
+                ensure!(
+                    scalar[1..24].iter().all(|&b| b == 0),
+                    "Ecotone scalar {} has non-zero padding",
+                    self.scalar
+                );
+                Ok((u32_at(28), u32_at(24)))
+            }
+            version => bail!("Unknown scalar version {version}"),
+        }
+    }
+
+    /// Returns the Isthmus operator fee charged for a transaction using `gas_used` gas,
+    /// `gas_used * operator_fee_scalar / 1e6 + operator_fee_constant`, or `None` if the
+    /// config carries no operator fee parameters.
+    pub fn operator_fee(&self, gas_used: u64) -> Option<U256> {
+        if self.operator_fee_scalar.is_none() && self.operator_fee_constant.is_none() {
+            return None;
+        }
+        let scalar = U256::from(self.operator_fee_scalar.unwrap_or_default());
+        let constant = U256::from(self.operator_fee_constant.unwrap_or_default());
+        Some(U256::from(gas_used) * scalar / U256::from(OPERATOR_FEE_SCALAR_DECIMALS) + constant)
+    }
+}
+
+/// A block's number and hash.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BlockId {
+    /// The block hash.
+    pub hash: B256,
+    /// The block number.
+    #[serde(with = "crate::quantity")]
+    pub number: u64,
+}
+
+/// A block's identity, parent and timestamp.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BlockInfo {
+    /// The block hash.
+    pub hash: B256,
+    /// The block number.
+    #[serde(with = "crate::quantity")]
+    pub number: u64,
+    /// The parent block hash.
+    pub parent_hash: B256,
+    /// The block timestamp.
+    #[serde(with = "crate::quantity")]
+    pub timestamp: u64,
+}
+
+/// An L2 block's info along with its L1 origin and position in the sequencing epoch.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct L2BlockInfo {
+    /// The block's identity, parent and timestamp.
+    pub block_info: BlockInfo,
+    /// The L1 origin of the block.
+    pub l1_origin: BlockId,
+    /// The sequence number of the block within its epoch.
+    #[serde(with = "crate::quantity")]
+    pub seq_num: u64,
+}
+
+impl L2BlockInfo {
+    /// Computes the block info of an executed L2 block from its header and the payload
+    /// attributes that built it, reading the L1 origin and sequence number from the
+    /// block's L1 info deposit.
+    pub fn from_payload(header: &Header, attributes: &PayloadAttributes) -> eyre::Result<Self> {
+        ensure!(
+            header.timestamp == attributes.timestamp,
+            "Header timestamp {} does not match payload timestamp {}",
+            header.timestamp,
+            attributes.timestamp
+        );
+        let deposit = attributes
+            .transactions
+            .first()
+            .ok_or_else(|| eyre!("Payload for block {} has no L1 info deposit", header.number))?;
+        let (l1_origin, seq_num) = l1_info(deposit)?;
+        Ok(Self {
+            block_info: BlockInfo {
+                hash: header.hash_slow(),
+                number: header.number,
+                parent_hash: header.parent_hash,
+                timestamp: header.timestamp,
+            },
+            l1_origin,
+            seq_num,
+        })
+    }
+}
+
+/// The parts of an executed L2 block that its output root commits to.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct L2Output {
+    /// The state root after the block.
+    pub state_root: B256,
+    /// The storage root of the [L2_TO_L1_MESSAGE_PASSER_ADDRESS] predeploy after the block.
+    pub message_passer_storage_root: B256,
+    /// The block hash.
+    pub block_hash: B256,
+}
+
+impl L2Output {
+    /// Computes the version 0 output root, the hash of a zero version followed by the
+    /// state root, message passer storage root and block hash.
+    pub fn root(&self) -> B256 {
+        keccak256(
+            [
+                B256::ZERO.as_slice(),
+                self.state_root.as_slice(),
+                self.message_passer_storage_root.as_slice(),
+                self.block_hash.as_slice(),
+            ]
+            .concat(),
+        )
+    }
+}
+
+/// Reads the L1 origin and sequence number from an encoded L1 info deposit transaction,
+/// in the Bedrock, Ecotone or Isthmus format.
+pub fn l1_info(deposit: &[u8]) -> eyre::Result<(BlockId, u64)> {
+    let input = deposit_input(deposit)?;
+    let word = |i: usize| &input[4 + 32 * i..4 + 32 * (i + 1)];
+    let u64_at = |bytes: &[u8]| u64::from_be_bytes(bytes.try_into().expect("8 bytes"));
+    match input.get(..4) {
+        Some(selector) if selector == BEDROCK_L1_INFO_SELECTOR => {
+            ensure!(
+                input.len() == 4 + 32 * 8,
+                "Invalid Bedrock L1 info length {}",
+                input.len()
+            );
+            let origin = BlockId {
+                hash: B256::from_slice(word(3)),
+                number: u64_at(&word(0)[24..]),
+            };
+            Ok((origin, u64_at(&word(4)[24..])))
+        }
+        Some(selector)
+            if selector == ECOTONE_L1_INFO_SELECTOR || selector == ISTHMUS_L1_INFO_SELECTOR =>
+        {
+            // Isthmus appends the operator fee scalar and constant to the Ecotone fields.
+            let (fork, len) = if selector == ECOTONE_L1_INFO_SELECTOR {
+                ("Ecotone", 164)
+            } else {
+                ("Isthmus", 176)
+            };
+            ensure!(
+                input.len() == len,
+                "Invalid {fork} L1 info length {}",
+                input.len()
+            );
+            let origin = BlockId {
+                hash: B256::from_slice(&input[100..132]),
+                number: u64_at(&input[28..36]),
+            };
+            Ok((origin, u64_at(&input[12..20])))
+        }
+        _ => bail!("Transaction is not an L1 info deposit"),
+    }
+}
+
+/// Returns the input of an EIP-2718 encoded deposit transaction.
+fn deposit_input(tx: &[u8]) -> eyre::Result<Bytes> {
+    let (&ty, mut buf) = tx.split_first().ok_or_else(|| eyre!("Empty transaction"))?;
+    ensure!(
+        ty == DEPOSIT_TX_TYPE,
+        "Transaction type {ty:#x} is not a deposit"
+    );
+    let header = rlp::Header::decode(&mut buf).map_err(|e| eyre!("Invalid deposit: {e}"))?;
+    ensure!(header.list, "Deposit is not an RLP list");
+    // Skip the source hash, from, to, mint, value, gas limit and system flag.
+    for _ in 0..7 {
+        let field = rlp::Header::decode(&mut buf).map_err(|e| eyre!("Invalid deposit: {e}"))?;
+        buf = buf
+            .get(field.payload_length..)
+            .ok_or_else(|| eyre!("Truncated deposit"))?;
+    }
+    Bytes::decode(&mut buf).map_err(|e| eyre!("Invalid deposit input: {e}"))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alloy_primitives::{b256, bytes, uint};
+    use alloy_rlp::Encodable;
+
+    /// Encodes a deposit transaction with the given input.
+    fn deposit(input: Vec<u8>) -> Bytes {
+        let mut fields = Vec::new();
+        B256::ZERO.encode(&mut fields);
+        Address::ZERO.encode(&mut fields);
+        Address::ZERO.encode(&mut fields);
+        0u128.encode(&mut fields);
+        U256::ZERO.encode(&mut fields);
+        1_000_000u64.encode(&mut fields);
+        false.encode(&mut fields);
+        Bytes::from(input).encode(&mut fields);
+        let mut tx = vec![DEPOSIT_TX_TYPE];
+        rlp::Header {
+            list: true,
+            payload_length: fields.len(),
+        }
+        .encode(&mut tx);
+        tx.extend(fields);
+        tx.into()
+    }
+
+    /// Encodes an Ecotone L1 info deposit for the given L1 origin and sequence number.
+    pub(crate) fn l1_info_deposit(origin: BlockId, seq_num: u64) -> Bytes {
+        let mut input = ECOTONE_L1_INFO_SELECTOR.to_vec();
+        input.extend_from_slice(&[0; 8]);
+        input.extend_from_slice(&seq_num.to_be_bytes());
+        input.extend_from_slice(&[0; 8]);
+        input.extend_from_slice(&origin.number.to_be_bytes());
+        input.extend_from_slice(&[0; 64]);
+        input.extend_from_slice(origin.hash.as_slice());
+        input.extend_from_slice(&[0; 32]);
+        deposit(input)
+    }
+
+    #[test]
+    fn test_l2_output_root() {
+        let output = L2Output {
+            state_root: B256::repeat_byte(1),
+            message_passer_storage_root: B256::repeat_byte(2),
+            block_hash: B256::repeat_byte(3),
+        };
+        let mut preimage = [0u8; 128];
+        preimage[32..64].fill(1);
+        preimage[64..96].fill(2);
+        preimage[96..].fill(3);
+        assert_eq!(output.root(), keccak256(preimage));
+        assert_ne!(
+            output.root(),
+            L2Output {
+                block_hash: B256::ZERO,
+                ..output
+            }
+            .root()
+        );
+    }
+
+    #[test]
+    fn test_l1_info() {
+        let origin = BlockId {
+            hash: b256!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            number: 20_000_000,
+        };
+        assert_eq!(l1_info(&l1_info_deposit(origin, 3)).unwrap(), (origin, 3));
+
+        let mut input = deposit_input(&l1_info_deposit(origin, 4)).unwrap().to_vec();
+        input[..4].copy_from_slice(&ISTHMUS_L1_INFO_SELECTOR);
+        assert!(l1_info(&deposit(input.clone())).is_err());
+        input.extend_from_slice(&7u32.to_be_bytes());
+        input.extend_from_slice(&9u64.to_be_bytes());
+        assert_eq!(l1_info(&deposit(input)).unwrap(), (origin, 4));
+
+        let mut input = BEDROCK_L1_INFO_SELECTOR.to_vec();
+        for word in [
+            B256::left_padding_from(&origin.number.to_be_bytes()),
+            B256::ZERO,
+            B256::ZERO,
+            origin.hash,
+            B256::left_padding_from(&5u64.to_be_bytes()),
+            B256::ZERO,
+            B256::ZERO,
+            B256::ZERO,
+        ] {
+            input.extend_from_slice(word.as_slice());
+        }
+        assert_eq!(l1_info(&deposit(input)).unwrap(), (origin, 5));
+
+        assert!(l1_info(&deposit(vec![0xde, 0xad, 0xbe, 0xef])).is_err());
+        assert!(l1_info(&bytes!("02c0")).is_err());
+        assert!(l1_info(&[]).is_err());
+    }
+
+    #[test]
+    fn test_fee_scalars() {
+        let config = |scalar: U256| SystemConfig {
+            scalar,
+            ..Default::default()
+        };
+        let legacy = config(uint!(684000_U256));
+        assert!(!legacy.has_ecotone_scalar());
+        assert_eq!(legacy.fee_scalars().unwrap(), (684000, 0));
+        assert!(config(U256::from(u32::MAX) + U256::from(1))
+            .fee_scalars()
+            .is_err());
+
+        let mut packed = [0u8; 32];
+        packed[0] = ECOTONE_SCALAR_VERSION;
+        packed[24..28].copy_from_slice(&810949u32.to_be_bytes());
+        packed[28..].copy_from_slice(&1368u32.to_be_bytes());
+        let ecotone = config(U256::from_be_bytes(packed));
+        assert!(ecotone.has_ecotone_scalar());
+        assert_eq!(ecotone.fee_scalars().unwrap(), (1368, 810949));
+
+        packed[10] = 1;
+        assert!(config(U256::from_be_bytes(packed)).fee_scalars().is_err());
+        packed[0] = 2;
+        assert!(config(U256::from_be_bytes(packed)).fee_scalars().is_err());
+    }
+
+    #[test]
+    fn test_operator_fee() {
+        let mut config = SystemConfig::default();
+        assert_eq!(config.operator_fee(21000), None);
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json.get("operatorFeeScalar").is_none());
+        assert!(json.get("operatorFeeConstant").is_none());
+
+        config.operator_fee_scalar = Some(1_500_000);
+        config.operator_fee_constant = Some(100);
+        assert_eq!(config.operator_fee(21000), Some(uint!(31600_U256)));
+        assert_eq!(config.operator_fee(0), Some(uint!(100_U256)));
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["operatorFeeScalar"], 1_500_000);
+        assert_eq!(json["operatorFeeConstant"], 100);
+        assert_eq!(
+            serde_json::from_value::<SystemConfig>(json).unwrap(),
+            config
+        );
+
+        config.operator_fee_scalar = Some(u32::MAX);
+        config.operator_fee_constant = Some(u64::MAX);
+        assert_eq!(
+            config.operator_fee(u64::MAX),
+            Some(
+                U256::from(u64::MAX) * U256::from(u32::MAX) / uint!(1000000_U256)
+                    + U256::from(u64::MAX)
+            )
+        );
+    }
+}
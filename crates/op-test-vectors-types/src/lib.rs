@@ -0,0 +1,29 @@
+#![doc = include_str!("../README.md")]
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    unreachable_pub,
+    rustdoc::all
+)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+pub mod anchor_state;
+
+pub mod building;
+
+pub mod cannon;
+
+pub mod derivation;
+
+pub mod execution;
+
+pub mod hint;
+
+pub mod l2;
+
+pub mod preimage;
+
+pub mod quantity;
+
+pub mod version;
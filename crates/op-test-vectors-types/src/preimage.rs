@@ -0,0 +1,247 @@
+//! Module containing the keys op-program reads preimages by.
+//!
+//! A key is 32 bytes: the [KeyType] in the first byte, followed by the last 31 bytes of a
+//! digest that depends on the key type, e.g. the keccak256 hash of the preimage. Every key
+//! type implements [PreimageKey], so fixtures derive keys in one place rather than
+//! hashing inline with a scheme that could drift from op-program's.
+
+use alloy_primitives::{keccak256, Address, FixedBytes, B256};
+use color_eyre::eyre::{self, eyre};
+use sha2::{Digest, Sha256};
+
+/// The type of a preimage key, stored in its first byte.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum KeyType {
+    /// A value local to the program, such as the L1 head, by index.
+    Local = 1,
+    /// A preimage by its keccak256 hash.
+    Keccak256 = 2,
+    /// A preimage by a global, application defined key.
+    GlobalGeneric = 3,
+    /// A preimage by its sha256 hash.
+    Sha256 = 4,
+    /// A field element of a blob, by the blob's commitment and the element's evaluation
+    /// point.
+    Blob = 5,
+    /// The result of a precompile call, by the precompile address and call input.
+    Precompile = 6,
+}
+
+impl KeyType {
+    /// Returns the type of the given key, failing if its first byte is not a known type.
+    pub fn of(key: B256) -> eyre::Result<Self> {
+        Self::try_from(key[0])
+    }
+}
+
+impl TryFrom<u8> for KeyType {
+    type Error = eyre::Report;
+
+    fn try_from(value: u8) -> eyre::Result<Self> {
+        Ok(match value {
+            1 => Self::Local,
+            2 => Self::Keccak256,
+            3 => Self::GlobalGeneric,
+            4 => Self::Sha256,
+            5 => Self::Blob,
+            6 => Self::Precompile,
+            _ => return Err(eyre!("Unknown preimage key type {value}")),
+        })
+    }
+}
+
+/// Derives a preimage key of a single [KeyType].
+pub trait PreimageKey {
+    /// The type of the key.
+    const TYPE: KeyType;
+
+    /// Returns the digest the key is derived from. Its first byte is replaced by the key
+    /// type.
+    fn digest(&self) -> B256;
+
+    /// Returns the key.
+    fn key(&self) -> B256 {
+        let mut key = self.digest();
+        key[0] = Self::TYPE as u8;
+        key
+    }
+}
+
+/// The key of a value local to the program, by its index.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LocalKey(pub u64);
+
+impl PreimageKey for LocalKey {
+    const TYPE: KeyType = KeyType::Local;
+
+    fn digest(&self) -> B256 {
+        B256::left_padding_from(&self.0.to_be_bytes())
+    }
+}
+
+/// The key of a preimage by its keccak256 hash.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Keccak256Key<'a>(pub &'a [u8]);
+
+impl PreimageKey for Keccak256Key<'_> {
+    const TYPE: KeyType = KeyType::Keccak256;
+
+    fn digest(&self) -> B256 {
+        keccak256(self.0)
+    }
+}
+
+/// The key of a preimage by its sha256 hash, as used for the inputs of the point
+/// evaluation precompile.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Sha256Key<'a>(pub &'a [u8]);
+
+impl PreimageKey for Sha256Key<'_> {
+    const TYPE: KeyType = KeyType::Sha256;
+
+    fn digest(&self) -> B256 {
+        B256::from(<[u8; 32]>::from(Sha256::digest(self.0)))
+    }
+}
+
+/// The key of a single field element of a blob.
+///
+/// op-program reads a blob one field element at a time, keyed by the keccak256 hash of
+/// the blob's KZG commitment followed by the element's evaluation point, the root of
+/// unity at its index.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BlobKey {
+    /// The KZG commitment of the blob.
+    pub commitment: FixedBytes<48>,
+    /// The evaluation point of the field element.
+    pub z: B256,
+}
+
+impl PreimageKey for BlobKey {
+    const TYPE: KeyType = KeyType::Blob;
+
+    fn digest(&self) -> B256 {
+        keccak256([self.commitment.as_slice(), self.z.as_slice()].concat())
+    }
+}
+
+/// The key of the result of a precompile call, by the keccak256 hash of the precompile
+/// address followed by the call input, the same data as its `l1-precompile` hint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PrecompileKey<'a> {
+    /// The address of the precompile.
+    pub address: Address,
+    /// The input of the call.
+    pub input: &'a [u8],
+}
+
+impl PreimageKey for PrecompileKey<'_> {
+    const TYPE: KeyType = KeyType::Precompile;
+
+    fn digest(&self) -> B256 {
+        keccak256([self.address.as_slice(), self.input].concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256, fixed_bytes};
+
+    #[test]
+    fn test_local_key() {
+        let vectors = [
+            (
+                0,
+                b256!("0100000000000000000000000000000000000000000000000000000000000000"),
+            ),
+            (
+                1,
+                b256!("0100000000000000000000000000000000000000000000000000000000000001"),
+            ),
+            (
+                0xdeadbeef,
+                b256!("01000000000000000000000000000000000000000000000000000000deadbeef"),
+            ),
+            (
+                u64::MAX,
+                b256!("010000000000000000000000000000000000000000000000ffffffffffffffff"),
+            ),
+        ];
+        for (index, key) in vectors {
+            assert_eq!(LocalKey(index).key(), key, "local key {index}");
+        }
+    }
+
+    #[test]
+    fn test_hash_keys() {
+        let vectors: [(&[u8], B256, B256); 2] = [
+            (
+                b"",
+                b256!("02d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+                b256!("04b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+            ),
+            (
+                b"hello world",
+                b256!("02173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad"),
+                b256!("044d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"),
+            ),
+        ];
+        for (preimage, keccak, sha) in vectors {
+            assert_eq!(Keccak256Key(preimage).key(), keccak);
+            assert_eq!(Sha256Key(preimage).key(), sha);
+        }
+    }
+
+    #[test]
+    fn test_blob_key() {
+        // The commitment of the empty blob, at the first root of unity.
+        let key = BlobKey {
+            commitment: fixed_bytes!("c00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"),
+            z: b256!("0000000000000000000000000000000000000000000000000000000000000001"),
+        };
+        assert_eq!(
+            key.key(),
+            b256!("05d0386f1692642e9b46449e7ee5a7aaa85d92ab248930e0ff388ed56c79977e")
+        );
+    }
+
+    #[test]
+    fn test_precompile_key() {
+        let vectors: [(Address, &[u8], B256); 2] = [
+            (
+                address!("0000000000000000000000000000000000000001"),
+                b"",
+                b256!("0668288056310c82aa4c01a7e12a10f8111a0560e72b700555479031b86c357d"),
+            ),
+            (
+                address!("000000000000000000000000000000000000000a"),
+                &[0x01],
+                b256!("06f23a990df372fc24407666d83ac3c3a9e4cfa0a24c5885a1f1be86ec55af5b"),
+            ),
+        ];
+        for (address, input, key) in vectors {
+            assert_eq!(PrecompileKey { address, input }.key(), key);
+        }
+    }
+
+    #[test]
+    fn test_key_type() {
+        let types = [
+            KeyType::Local,
+            KeyType::Keccak256,
+            KeyType::GlobalGeneric,
+            KeyType::Sha256,
+            KeyType::Blob,
+            KeyType::Precompile,
+        ];
+        for ty in types {
+            assert_eq!(KeyType::try_from(ty as u8).unwrap(), ty);
+        }
+        assert_eq!(KeyType::of(LocalKey(1).key()).unwrap(), KeyType::Local);
+        assert_eq!(KeyType::of(Sha256Key(b"").key()).unwrap(), KeyType::Sha256);
+        assert!(KeyType::try_from(0).is_err());
+        assert!(KeyType::try_from(7).is_err());
+    }
+}
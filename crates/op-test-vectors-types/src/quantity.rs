@@ -0,0 +1,209 @@
+//! Module containing serde helpers for numeric fields that accept both `0x`-prefixed hex
+//! and decimal encodings.
+//!
+//! Fixtures produced by Go tooling often encode quantities as hex strings, while serde
+//! defaults to plain numbers. These helpers accept either form on load and always
+//! serialize the canonical form, a plain number.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Example {
+//!     #[serde(with = "crate::quantity")]
+//!     number: u64,
+//!     #[serde(deserialize_with = "crate::quantity::map::deserialize")]
+//!     by_number: HashMap<u64, String>,
+//! }
+//! ```
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serializer,
+};
+use std::{fmt, num::ParseIntError};
+
+/// Parses a `0x`-prefixed hex or decimal string into a [u64].
+pub fn parse_u64(s: &str) -> Result<u64, ParseIntError> {
+    let s = s.trim();
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+/// Serializes a [u64] as a plain number.
+pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(*value)
+}
+
+/// Deserializes a [u64] from a number, or a `0x`-prefixed hex or decimal string.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Quantity::deserialize(deserializer).map(|q| q.0)
+}
+
+/// A [u64] that deserializes from either encoding, used for map keys.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+struct Quantity(u64);
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct QuantityVisitor;
+
+        impl<'de> Visitor<'de> for QuantityVisitor {
+            type Value = Quantity;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a number, or a 0x-prefixed hex or decimal string")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Quantity, E> {
+                Ok(Quantity(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Quantity, E> {
+                u64::try_from(value)
+                    .map(Quantity)
+                    .map_err(|_| E::custom(format!("negative quantity: {value}")))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Quantity, E> {
+                parse_u64(value)
+                    .map(Quantity)
+                    .map_err(|e| E::custom(format!("invalid quantity {value:?}: {e}")))
+            }
+        }
+
+        deserializer.deserialize_any(QuantityVisitor)
+    }
+}
+
+/// Helpers for optional quantities, used with `#[serde(default, with = "...")]` so a
+/// missing field still reads as [None].
+pub mod option {
+    use super::Quantity;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serializes an optional [u64] as a plain number or null.
+    pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_some(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes an optional [u64] from null, a number, or a `0x`-prefixed hex or
+    /// decimal string.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        Ok(Option::<Quantity>::deserialize(deserializer)?.map(|q| q.0))
+    }
+}
+
+/// Helpers for maps keyed by an integer quantity.
+pub mod map {
+    use super::Quantity;
+    use serde::{de::Error, Deserialize, Deserializer};
+    use std::collections::HashMap;
+
+    /// Deserializes a map whose keys are numbers, or `0x`-prefixed hex or decimal strings.
+    pub fn deserialize<'de, D, K, V, M>(deserializer: D) -> Result<M, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: TryFrom<u64>,
+        V: Deserialize<'de>,
+        M: FromIterator<(K, V)>,
+    {
+        HashMap::<Quantity, V>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(k, v)| {
+                K::try_from(k.0)
+                    .map(|k| (k, v))
+                    .map_err(|_| D::Error::custom(format!("map key out of range: {}", k.0)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hashbrown::HashMap;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Example {
+        #[serde(with = "super")]
+        number: u64,
+        #[serde(deserialize_with = "map::deserialize")]
+        by_number: HashMap<u64, u64>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Optional {
+        #[serde(default, with = "option")]
+        number: Option<u64>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct SmallKeys {
+        #[serde(deserialize_with = "map::deserialize")]
+        by_number: HashMap<u8, u64>,
+    }
+
+    #[test]
+    fn test_parse_u64() {
+        assert_eq!(parse_u64("0x10").unwrap(), 16);
+        assert_eq!(parse_u64("0X10").unwrap(), 16);
+        assert_eq!(parse_u64("16").unwrap(), 16);
+        assert!(parse_u64("0x").is_err());
+        assert!(parse_u64("sixteen").is_err());
+    }
+
+    #[test]
+    fn test_quantity_encodings() {
+        let expected = Example {
+            number: 16,
+            by_number: [(1, 2)].into_iter().collect(),
+        };
+        for json in [
+            r#"{"number": 16, "by_number": {"1": 2}}"#,
+            r#"{"number": "16", "by_number": {"0x1": 2}}"#,
+            r#"{"number": "0x10", "by_number": {"1": 2}}"#,
+        ] {
+            let example: Example = serde_json::from_str(json).unwrap();
+            assert_eq!(example, expected);
+        }
+        assert!(serde_json::from_str::<Example>(r#"{"number": -1, "by_number": {}}"#).is_err());
+
+        let small: SmallKeys = serde_json::from_str(r#"{"by_number": {"0xff": 1}}"#).unwrap();
+        assert_eq!(small.by_number.get(&255), Some(&1));
+        assert!(serde_json::from_str::<SmallKeys>(r#"{"by_number": {"256": 1}}"#).is_err());
+
+        let serialized = serde_json::to_string(&expected).unwrap();
+        assert_eq!(serialized, r#"{"number":16,"by_number":{"1":2}}"#);
+    }
+
+    #[test]
+    fn test_optional_quantity() {
+        for (json, number) in [
+            (r#"{"number": "0x10"}"#, Some(16)),
+            (r#"{"number": 16}"#, Some(16)),
+            (r#"{"number": null}"#, None),
+            ("{}", None),
+        ] {
+            let optional: Optional = serde_json::from_str(json).unwrap();
+            assert_eq!(optional.number, number);
+        }
+        let serialized = serde_json::to_string(&Optional { number: Some(16) }).unwrap();
+        assert_eq!(serialized, r#"{"number":16}"#);
+        let serialized = serde_json::to_string(&Optional { number: None }).unwrap();
+        assert_eq!(serialized, r#"{"number":null}"#);
+    }
+
+    #[test]
+    fn test_quantity_yaml() {
+        let example: Example = serde_yaml::from_str("number: 0x10\nby_number:\n  1: 2\n").unwrap();
+        assert_eq!(example.number, 16);
+        assert_eq!(example.by_number.get(&1), Some(&2));
+    }
+}
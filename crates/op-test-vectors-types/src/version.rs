@@ -0,0 +1,36 @@
+//! Module containing the schema version fixtures record their layout with.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The schema version of a fixture, stored in its `version` field.
+///
+/// Fixtures written before versioning have no `version` field and read as
+/// [SchemaVersion::UNVERSIONED]. New fixtures default to [SchemaVersion::CURRENT].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(transparent)]
+pub struct SchemaVersion(#[serde(with = "crate::quantity")] pub u64);
+
+impl SchemaVersion {
+    /// The version of fixtures written before versioning.
+    pub const UNVERSIONED: Self = Self(0);
+    /// The version fixtures are written at.
+    pub const CURRENT: Self = Self(1);
+
+    /// Returns [SchemaVersion::UNVERSIONED], for fixtures without a `version` field.
+    pub const fn unversioned() -> Self {
+        Self::UNVERSIONED
+    }
+}
+
+impl Default for SchemaVersion {
+    fn default() -> Self {
+        Self::CURRENT
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.0)
+    }
+}
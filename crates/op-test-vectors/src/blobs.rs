@@ -0,0 +1,351 @@
+//! Module containing the blob data codec used by Ecotone+ batcher transactions.
+//!
+//! Blobs carry channel data encoded into 4096 field elements. Each round of 4 field
+//! elements holds 127 bytes: 31 bytes in the low order bytes of each element plus
+//! 4 six-bit chunks in the high order bytes that reassemble into 3 more bytes.
+
+use alloy_primitives::{Bytes, FixedBytes};
+use color_eyre::eyre::{self, bail, ensure};
+use kona_derive::types::Blob;
+
+/// The size of a blob in bytes.
+pub const BLOB_SIZE: usize = 4096 * 32;
+/// The blob encoding version.
+pub const BLOB_ENCODING_VERSION: u8 = 0;
+/// The maximum amount of data that can be encoded into a single blob.
+pub const MAX_BLOB_DATA_SIZE: usize = (4 * 31 + 3) * 1024 - 4;
+/// The derivation version prefixing channel data.
+pub const DERIVATION_VERSION_0: u8 = 0;
+
+/// The number of 4 field element rounds in a blob.
+const ENCODING_ROUNDS: usize = 1024;
+/// The size of an encoded frame without its data: channel id, frame number,
+/// data length and the is_last flag.
+const FRAME_OVERHEAD: usize = 16 + 2 + 4 + 1;
+
+/// Decodes the data encoded in a blob.
+pub fn decode(blob: &Blob) -> eyre::Result<Bytes> {
+    let blob = blob.as_slice();
+    ensure!(
+        blob[1] == BLOB_ENCODING_VERSION,
+        "invalid blob encoding version: {}",
+        blob[1]
+    );
+
+    let len = (blob[2] as usize) << 16 | (blob[3] as usize) << 8 | blob[4] as usize;
+    ensure!(
+        len <= MAX_BLOB_DATA_SIZE,
+        "blob data length {len} exceeds max {MAX_BLOB_DATA_SIZE}"
+    );
+
+    let mut output = vec![0u8; MAX_BLOB_DATA_SIZE];
+    let mut encoded = [0u8; 4];
+
+    // The first field element only holds 27 bytes of data after the version and length.
+    encoded[0] = high_order_bits(blob, 0)?;
+    output[..27].copy_from_slice(&blob[5..32]);
+    let mut opos = 28;
+    let mut ipos = 32;
+    for byte in encoded.iter_mut().skip(1) {
+        *byte = high_order_bits(blob, ipos)?;
+        output[opos..opos + 31].copy_from_slice(&blob[ipos + 1..ipos + 32]);
+        opos += 32;
+        ipos += 32;
+    }
+    opos = reassemble(opos, &encoded, &mut output);
+
+    for _ in 1..ENCODING_ROUNDS {
+        if opos >= len {
+            break;
+        }
+        for byte in encoded.iter_mut() {
+            *byte = high_order_bits(blob, ipos)?;
+            output[opos..opos + 31].copy_from_slice(&blob[ipos + 1..ipos + 32]);
+            opos += 32;
+            ipos += 32;
+        }
+        opos = reassemble(opos, &encoded, &mut output);
+    }
+
+    if let Some(i) = output[len..].iter().position(|b| *b != 0) {
+        bail!(
+            "non-zero data at output position {} past blob data",
+            len + i
+        );
+    }
+    if let Some(i) = blob[ipos..].iter().position(|b| *b != 0) {
+        bail!("non-zero data at blob position {} past blob data", ipos + i);
+    }
+
+    output.truncate(len);
+    Ok(output.into())
+}
+
+/// Encodes data into a blob.
+pub fn encode(data: &[u8]) -> eyre::Result<Blob> {
+    ensure!(
+        data.len() <= MAX_BLOB_DATA_SIZE,
+        "data length {} exceeds max blob data size {MAX_BLOB_DATA_SIZE}",
+        data.len()
+    );
+
+    let mut blob = FixedBytes::<BLOB_SIZE>::ZERO;
+    let mut reader = Reader { data, offset: 0 };
+    let mut woff = 0;
+    let mut buf = [0u8; 31];
+
+    for round in 0..ENCODING_ROUNDS {
+        if round > 0 && reader.offset >= data.len() {
+            break;
+        }
+        if round == 0 {
+            // The first field element holds the version and the length as a big-endian uint24.
+            buf[0] = BLOB_ENCODING_VERSION;
+            buf[1..4].copy_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+            buf[4..].fill(0);
+            let n = data.len().min(27);
+            buf[4..4 + n].copy_from_slice(&data[..n]);
+            reader.offset = n;
+        } else {
+            reader.read31(&mut buf);
+        }
+
+        let x = reader.read1();
+        write_field_element(&mut blob, &mut woff, x & 0b0011_1111, &buf);
+
+        reader.read31(&mut buf);
+        let y = reader.read1();
+        write_field_element(
+            &mut blob,
+            &mut woff,
+            (y & 0b0000_1111) | ((x & 0b1100_0000) >> 2),
+            &buf,
+        );
+
+        reader.read31(&mut buf);
+        let z = reader.read1();
+        write_field_element(&mut blob, &mut woff, z & 0b0011_1111, &buf);
+
+        reader.read31(&mut buf);
+        write_field_element(
+            &mut blob,
+            &mut woff,
+            ((z & 0b1100_0000) >> 2) | ((y & 0b1111_0000) >> 4),
+            &buf,
+        );
+    }
+
+    ensure!(
+        reader.offset >= data.len(),
+        "failed to encode all data into blob"
+    );
+    Ok(blob)
+}
+
+/// The header of a frame, as found in channel data.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FrameHeader {
+    /// The channel the frame belongs to.
+    pub channel_id: FixedBytes<16>,
+    /// The index of the frame within the channel.
+    pub number: u16,
+    /// The length of the frame data.
+    pub data_len: u32,
+    /// Whether this is the last frame of the channel.
+    pub is_last: bool,
+}
+
+/// Parses the frame headers from versioned channel data, validating that the data
+/// consists entirely of well-formed frames.
+pub fn parse_frame_headers(data: &[u8]) -> eyre::Result<Vec<FrameHeader>> {
+    let Some((&version, mut rest)) = data.split_first() else {
+        bail!("empty channel data");
+    };
+    ensure!(
+        version == DERIVATION_VERSION_0,
+        "invalid derivation version: {version}"
+    );
+
+    let mut headers = Vec::new();
+    while !rest.is_empty() {
+        ensure!(
+            rest.len() >= FRAME_OVERHEAD,
+            "truncated frame header at frame {}",
+            headers.len()
+        );
+        let data_len = u32::from_be_bytes(rest[18..22].try_into().expect("4 byte slice"));
+        let end = 22 + data_len as usize;
+        ensure!(
+            rest.len() > end,
+            "truncated frame data at frame {}",
+            headers.len()
+        );
+        let is_last = match rest[end] {
+            0 => false,
+            1 => true,
+            b => bail!("invalid is_last flag {b} at frame {}", headers.len()),
+        };
+        headers.push(FrameHeader {
+            channel_id: FixedBytes::from_slice(&rest[..16]),
+            number: u16::from_be_bytes([rest[16], rest[17]]),
+            data_len,
+            is_last,
+        });
+        rest = &rest[end + 1..];
+    }
+    Ok(headers)
+}
+
+/// Decodes a blob and validates that it contains parseable frames, returning
+/// the decoded channel data.
+pub fn decode_frames(blob: &Blob) -> eyre::Result<(Bytes, Vec<FrameHeader>)> {
+    let data = decode(blob)?;
+    let headers = parse_frame_headers(&data)?;
+    Ok((data, headers))
+}
+
+/// Returns the high order byte of the field element at `ipos`, which must only
+/// use its lower 6 bits.
+fn high_order_bits(blob: &[u8], ipos: usize) -> eyre::Result<u8> {
+    ensure!(
+        blob[ipos] & 0b1100_0000 == 0,
+        "invalid field element {}: high order bits set",
+        ipos / 32
+    );
+    Ok(blob[ipos])
+}
+
+/// Reassembles the 4 six-bit chunks into 3 bytes placed in the gaps left between the
+/// 31 byte chunks of the round. Returns the new output position.
+fn reassemble(opos: usize, encoded: &[u8; 4], output: &mut [u8]) -> usize {
+    // There is no 128th byte in a round.
+    let opos = opos - 1;
+    let x = (encoded[0] & 0b0011_1111) | ((encoded[1] & 0b0011_0000) << 2);
+    let y = (encoded[1] & 0b0000_1111) | ((encoded[3] & 0b0000_1111) << 4);
+    let z = (encoded[2] & 0b0011_1111) | ((encoded[3] & 0b0011_0000) << 2);
+    output[opos - 32] = z;
+    output[opos - 32 * 2] = y;
+    output[opos - 32 * 3] = x;
+    opos
+}
+
+/// Writes a field element made of a six-bit high order byte followed by 31 bytes.
+fn write_field_element(blob: &mut Blob, woff: &mut usize, high: u8, buf: &[u8; 31]) {
+    blob[*woff] = high;
+    blob[*woff + 1..*woff + 32].copy_from_slice(buf);
+    *woff += 32;
+}
+
+/// Reads input data for blob encoding, padding with zeroes once exhausted.
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn read1(&mut self) -> u8 {
+        let Some(b) = self.data.get(self.offset) else {
+            return 0;
+        };
+        self.offset += 1;
+        *b
+    }
+
+    fn read31(&mut self, buf: &mut [u8; 31]) {
+        let rest = self.data.get(self.offset..).unwrap_or_default();
+        let n = rest.len().min(31);
+        buf[..n].copy_from_slice(&rest[..n]);
+        buf[n..].fill(0);
+        self.offset += n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(channel_id: u8, number: u16, data: &[u8], is_last: bool) -> Vec<u8> {
+        let mut out = vec![channel_id; 16];
+        out.extend_from_slice(&number.to_be_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(data);
+        out.push(is_last as u8);
+        out
+    }
+
+    #[test]
+    fn test_blob_roundtrip() {
+        for len in [0, 1, 27, 28, 123, 124, 250, 1000, MAX_BLOB_DATA_SIZE] {
+            let data = (0..len).map(|i| (i % 256) as u8).collect::<Vec<_>>();
+            let blob = encode(&data).unwrap();
+            assert_eq!(
+                decode(&blob).unwrap().as_ref(),
+                data.as_slice(),
+                "len {len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_blob_high_bytes() {
+        let data = vec![0xff; 1000];
+        let blob = encode(&data).unwrap();
+        assert!(blob.chunks_exact(32).all(|fe| fe[0] & 0b1100_0000 == 0));
+        assert_eq!(decode(&blob).unwrap().as_ref(), data.as_slice());
+    }
+
+    #[test]
+    fn test_encode_too_large() {
+        assert!(encode(&vec![0; MAX_BLOB_DATA_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_decode_invalid_version() {
+        let mut blob = encode(&[1, 2, 3]).unwrap();
+        blob[1] = 1;
+        assert!(decode(&blob).is_err());
+    }
+
+    #[test]
+    fn test_decode_invalid_field_element() {
+        let mut blob = encode(&[1, 2, 3]).unwrap();
+        blob[32] = 0b1000_0000;
+        assert!(decode(&blob).is_err());
+    }
+
+    #[test]
+    fn test_decode_trailing_data() {
+        let mut blob = encode(&[1, 2, 3]).unwrap();
+        blob[BLOB_SIZE - 1] = 1;
+        assert!(decode(&blob).is_err());
+    }
+
+    #[test]
+    fn test_decode_frames() {
+        let mut data = vec![DERIVATION_VERSION_0];
+        data.extend(frame(0xaa, 0, &[1, 2, 3], false));
+        data.extend(frame(0xaa, 1, &[], true));
+        let blob = encode(&data).unwrap();
+        let (decoded, headers) = decode_frames(&blob).unwrap();
+        assert_eq!(decoded.as_ref(), data.as_slice());
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].channel_id, FixedBytes::from([0xaa; 16]));
+        assert_eq!(headers[0].data_len, 3);
+        assert!(!headers[0].is_last);
+        assert_eq!(headers[1].number, 1);
+        assert!(headers[1].is_last);
+    }
+
+    #[test]
+    fn test_parse_frame_headers_invalid() {
+        assert!(parse_frame_headers(&[]).is_err());
+        assert!(parse_frame_headers(&[1]).is_err());
+
+        let mut data = vec![DERIVATION_VERSION_0];
+        data.extend(frame(0xaa, 0, &[1, 2, 3], true));
+        assert!(parse_frame_headers(&data[..data.len() - 1]).is_err());
+
+        *data.last_mut().unwrap() = 2;
+        assert!(parse_frame_headers(&data).is_err());
+    }
+}
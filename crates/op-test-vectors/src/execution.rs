@@ -1,151 +1,104 @@
-//! Module containing the execution test fixture.
+//! Module containing the execution test fixture, with anvil's transaction and receipt
+//! types, and its conversions from the anvil blocks and receipts `opt8n` records.
 
-use alloy_primitives::{Address, Bloom, B256, U256};
-use alloy_rpc_types::trace::geth::AccountState;
+use crate::l2::PayloadAttributes;
+use alloy_eips::{eip2718::Encodable2718, eip4844::calc_blob_gasprice};
+use alloy_primitives::U256;
 use alloy_rpc_types::{Log, TransactionReceipt};
 use anvil_core::eth::block::Block;
 use anvil_core::eth::transaction::{TypedReceipt, TypedTransaction};
-use color_eyre::eyre;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
-/// The execution fixture is the top-level object that contains
-/// everything needed to run an execution test.
-#[derive(Serialize, Deserialize, Debug, Default)]
-#[serde(rename_all = "camelCase")]
-pub struct ExecutionFixture {
-    /// The execution environment sets up the current block context.
-    pub env: ExecutionEnvironment,
-    /// The initial state of the accounts before running the transactions, also called the
-    /// "pre-state".
-    pub alloc: HashMap<Address, AccountState>,
-    /// The expected state of the accounts after running the transactions, also called the
-    /// "post-state".
-    pub out_alloc: HashMap<Address, AccountState>,
-    /// Transactions to execute.
-    #[serde(rename = "txs")]
-    pub transactions: Vec<TypedTransaction>,
-    /// The expected result after executing transactions.
-    pub result: ExecutionResult,
-}
+use color_eyre::eyre::{self, eyre};
+use op_test_vectors_types::execution;
 
-/// The execution environment is the initial state of the execution context.
-/// It's used to set the execution environment current block information.
-#[derive(Serialize, Deserialize, Debug, Default)]
-#[serde(rename_all = "camelCase")]
-pub struct ExecutionEnvironment {
-    /// The current block coinbase.
-    pub current_coinbase: Address,
-    /// The current block difficulty.
-    pub current_difficulty: U256,
-    /// The current block gas limit.
-    pub current_gas_limit: U256,
-    /// The previous block hash.
-    pub previous_hash: B256,
-    /// The current block number.
-    pub current_number: U256,
-    /// The current block timestamp.
-    pub current_timestamp: U256,
-    /// The block hashes of the previous blocks.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub block_hashes: Option<HashMap<U256, B256>>,
-}
+pub use op_test_vectors_types::execution::*;
 
-impl From<Block> for ExecutionEnvironment {
-    fn from(block: Block) -> Self {
-        Self {
-            current_coinbase: block.header.beneficiary,
-            current_difficulty: block.header.difficulty,
-            current_gas_limit: U256::from(block.header.gas_limit),
-            previous_hash: block.header.parent_hash,
-            current_number: U256::from(block.header.number),
-            current_timestamp: U256::from(block.header.timestamp),
-            block_hashes: None,
-        }
-    }
-}
+/// An [execution::ExecutionFixture] of anvil's transactions and receipts.
+pub type ExecutionFixture = execution::ExecutionFixture<TypedTransaction, TypedReceipt<Log>>;
 
-/// The execution result is the expected result after running the transactions
-/// in the execution environment over the pre-state.
-#[derive(Serialize, Deserialize, Debug, Default)]
-#[serde(rename_all = "camelCase")]
-pub struct ExecutionResult {
-    /// The state root.
-    pub state_root: B256,
-    /// The transaction root.
-    pub tx_root: B256,
-    /// The receipt root.
-    pub receipt_root: B256,
-    /// The logs bloom.
-    pub logs_bloom: Bloom,
-    /// A list of execution receipts for each executed transaction.
-    pub receipts: Vec<ExecutionReceipt>,
-}
+/// An [execution::ExecutionResult] of anvil's receipts.
+pub type ExecutionResult = execution::ExecutionResult<TypedReceipt<Log>>;
+
+/// An [execution::ExecutionReceipt] of anvil's receipt.
+pub type ExecutionReceipt = execution::ExecutionReceipt<TypedReceipt<Log>>;
 
-/// An execution receipt is the result of running a transaction in the execution environment.
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct ExecutionReceipt {
-    /// The state root.
-    pub root: B256,
-    /// The hash of the transaction.
-    pub transaction_hash: B256,
-    /// The contract address that the transaction created.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub contract_address: Option<Address>,
-    /// The gas used by the transaction.
-    pub gas_used: U256,
-    /// The block hash.
-    pub block_hash: B256,
-    /// The transaction index.
-    pub transaction_index: U256,
-    /// The inner log receipt.
-    #[serde(flatten)]
-    pub inner: TypedReceipt<Log>,
+/// Returns the execution environment of `block`, without the L1 fee parameters, chain ID
+/// or block hashes, which the block doesn't carry.
+pub fn environment(block: &Block) -> ExecutionEnvironment {
+    ExecutionEnvironment {
+        current_coinbase: block.header.beneficiary,
+        current_difficulty: block.header.difficulty,
+        current_gas_limit: U256::from(block.header.gas_limit),
+        previous_hash: block.header.parent_hash,
+        current_number: U256::from(block.header.number),
+        current_timestamp: U256::from(block.header.timestamp),
+        current_base_fee: block.header.base_fee_per_gas.map(U256::from),
+        current_excess_blob_gas: block.header.excess_blob_gas.map(U256::from),
+        current_blob_base_fee: block
+            .header
+            .excess_blob_gas
+            .map(|excess| U256::from(calc_blob_gasprice(excess))),
+        l1_fee: None,
+        chain_id: None,
+        block_hashes: None,
+    }
 }
 
-impl TryFrom<TransactionReceipt<TypedReceipt<Log>>> for ExecutionReceipt {
-    type Error = eyre::Error;
-
-    fn try_from(receipt: TransactionReceipt<TypedReceipt<Log>>) -> eyre::Result<Self> {
-        Ok(Self {
-            transaction_hash: receipt.transaction_hash,
-            root: receipt
-                .state_root
-                .ok_or_else(|| eyre::eyre!("missing state root"))?,
-            contract_address: receipt.contract_address,
-            gas_used: U256::from(receipt.gas_used),
-            block_hash: receipt
-                .block_hash
-                .ok_or_else(|| eyre::eyre!("missing block hash"))?,
-            transaction_index: U256::from(
-                receipt
-                    .transaction_index
-                    .ok_or_else(|| eyre::eyre!("missing transaction index"))?,
-            ),
-            inner: receipt.inner,
+/// Returns the payload attributes that have the engine build `block`, including its
+/// transactions verbatim.
+pub fn payload_attributes(block: &Block) -> PayloadAttributes {
+    let transactions = block
+        .transactions
+        .iter()
+        .map(|tx| {
+            let mut encoded = Vec::with_capacity(tx.encode_2718_len());
+            tx.encode_2718(&mut encoded);
+            encoded.into()
         })
+        .collect();
+    PayloadAttributes {
+        timestamp: block.header.timestamp,
+        prev_randao: block.header.mix_hash,
+        fee_recipient: block.header.beneficiary,
+        withdrawals: block.header.withdrawals_root.map(|_| Vec::new()),
+        parent_beacon_block_root: block.header.parent_beacon_block_root,
+        gas_limit: Some(block.header.gas_limit as u64),
+        transactions,
+        no_tx_pool: true,
     }
 }
 
+/// Converts an anvil transaction receipt into an [ExecutionReceipt], failing if it has
+/// no state root, block hash or transaction index.
+pub fn execution_receipt(
+    receipt: TransactionReceipt<TypedReceipt<Log>>,
+) -> eyre::Result<ExecutionReceipt> {
+    Ok(ExecutionReceipt {
+        transaction_hash: receipt.transaction_hash,
+        root: receipt
+            .state_root
+            .ok_or_else(|| eyre!("missing state root"))?,
+        contract_address: receipt.contract_address,
+        gas_used: U256::from(receipt.gas_used),
+        block_hash: receipt
+            .block_hash
+            .ok_or_else(|| eyre!("missing block hash"))?,
+        transaction_index: U256::from(
+            receipt
+                .transaction_index
+                .ok_or_else(|| eyre!("missing transaction index"))?,
+        ),
+        // Anvil does not execute with the operator fee.
+        operator_fee_scalar: None,
+        operator_fee_constant: None,
+        inner: receipt.inner,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::Value;
 
-    #[test]
-    fn test_serialize_execution_environment() {
-        let expected_env = include_str!("./testdata/environment.json");
-        let env = serde_json::from_str::<ExecutionEnvironment>(expected_env)
-            .expect("failed to parse environment");
-        let serialized_env = serde_json::to_string(&env).expect("failed to serialize environment");
-        let serialized_value = serde_json::from_str::<Value>(&serialized_env)
-            .expect("failed to parse serialized environment");
-        let expected_value = serde_json::from_str::<Value>(expected_env)
-            .expect("failed to parse expected environment");
-        assert_eq!(serialized_value, expected_value);
-    }
-
     #[test]
     fn test_serialize_execution_result() {
         let expected_result = include_str!("./testdata/result.json");
@@ -161,11 +114,11 @@ mod tests {
     }
 
     #[test]
-    fn test_exec_receipt_try_from_tx_receipt() {
+    fn test_execution_receipt() {
         let tx_receipt_str = include_str!("./testdata/tx_receipt.json");
         let tx_receipt: TransactionReceipt<TypedReceipt<Log>> =
             serde_json::from_str(tx_receipt_str).expect("failed to parse tx receipt");
-        let exec_receipt = ExecutionReceipt::try_from(tx_receipt.clone())
+        let exec_receipt = execution_receipt(tx_receipt.clone())
             .expect("failed to convert tx receipt to exec receipt");
         assert_eq!(exec_receipt.transaction_hash, tx_receipt.transaction_hash);
         assert_eq!(exec_receipt.root, tx_receipt.state_root.unwrap());
@@ -177,35 +130,61 @@ mod tests {
             U256::from(tx_receipt.transaction_index.unwrap())
         );
         assert_eq!(exec_receipt.inner, tx_receipt.inner);
+        assert_eq!(exec_receipt.operator_fee_scalar, None);
+        assert_eq!(exec_receipt.operator_fee_constant, None);
+    }
+
+    #[test]
+    fn test_execution_receipt_operator_fee_fields() {
+        let tx_receipt_str = include_str!("./testdata/tx_receipt.json");
+        let tx_receipt: TransactionReceipt<TypedReceipt<Log>> =
+            serde_json::from_str(tx_receipt_str).expect("failed to parse tx receipt");
+        let mut exec_receipt = execution_receipt(tx_receipt).expect("failed to convert tx receipt");
+        let json = serde_json::to_value(&exec_receipt).expect("failed to serialize receipt");
+        assert!(json.get("operatorFeeScalar").is_none());
+        assert!(json.get("operatorFeeConstant").is_none());
+
+        exec_receipt.operator_fee_scalar = Some(U256::from(1_500_000));
+        exec_receipt.operator_fee_constant = Some(U256::from(100));
+        let json = serde_json::to_value(&exec_receipt).expect("failed to serialize receipt");
+        assert_eq!(json["operatorFeeScalar"], "0x16e360");
+        assert_eq!(json["operatorFeeConstant"], "0x64");
+        let parsed: ExecutionReceipt =
+            serde_json::from_value(json).expect("failed to parse receipt");
+        assert_eq!(parsed.operator_fee_scalar, exec_receipt.operator_fee_scalar);
+        assert_eq!(
+            parsed.operator_fee_constant,
+            exec_receipt.operator_fee_constant
+        );
     }
 
     #[test]
-    fn test_exec_receipt_try_from_missing_root() {
+    fn test_execution_receipt_missing_root() {
         let tx_receipt_str = include_str!("./testdata/tx_receipt.json");
         let mut tx_receipt: TransactionReceipt<TypedReceipt<Log>> =
             serde_json::from_str(tx_receipt_str).expect("failed to parse tx receipt");
         tx_receipt.state_root = None;
-        let exec_receipt = ExecutionReceipt::try_from(tx_receipt);
+        let exec_receipt = execution_receipt(tx_receipt);
         assert!(exec_receipt.is_err());
     }
 
     #[test]
-    fn test_exec_receipt_try_from_missing_block_hash() {
+    fn test_execution_receipt_missing_block_hash() {
         let tx_receipt_str = include_str!("./testdata/tx_receipt.json");
         let mut tx_receipt: TransactionReceipt<TypedReceipt<Log>> =
             serde_json::from_str(tx_receipt_str).expect("failed to parse tx receipt");
         tx_receipt.block_hash = None;
-        let exec_receipt = ExecutionReceipt::try_from(tx_receipt);
+        let exec_receipt = execution_receipt(tx_receipt);
         assert!(exec_receipt.is_err());
     }
 
     #[test]
-    fn test_exec_receipt_try_from_missing_tx_index() {
+    fn test_execution_receipt_missing_tx_index() {
         let tx_receipt_str = include_str!("./testdata/tx_receipt.json");
         let mut tx_receipt: TransactionReceipt<TypedReceipt<Log>> =
             serde_json::from_str(tx_receipt_str).expect("failed to parse tx receipt");
         tx_receipt.transaction_index = None;
-        let exec_receipt = ExecutionReceipt::try_from(tx_receipt);
+        let exec_receipt = execution_receipt(tx_receipt);
         assert!(exec_receipt.is_err());
     }
 }
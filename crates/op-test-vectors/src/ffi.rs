@@ -0,0 +1,361 @@
+//! C FFI for loading and inspecting fixtures from other languages.
+//!
+//! Fixtures are returned as opaque handles that must be released with their matching
+//! `*_free` function. Strings returned by this module are owned by the caller and must
+//! be released with [otv_string_free]. When a function fails it returns a null pointer
+//! or `false`, and the error message can be read with [otv_last_error].
+//!
+//! Build the shared library with:
+//!
+//! ```sh
+//! cargo rustc -p op-test-vectors --release --features ffi --crate-type cdylib
+//! ```
+
+use crate::{derivation::DerivationFixture, execution::ExecutionFixture, format};
+use color_eyre::eyre::{self, eyre};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    path::PathBuf,
+    ptr,
+};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records the error for the current thread.
+fn set_last_error(err: eyre::Report) {
+    let msg = CString::new(format!("{err:#}").replace('\0', ""))
+        .expect("interior nul bytes were removed");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(msg));
+}
+
+/// Loads a JSON or YAML fixture from a nul-terminated path.
+///
+/// # Safety
+///
+/// `path` must be null or a valid nul-terminated string.
+unsafe fn load<T: DeserializeOwned>(path: *const c_char) -> *mut T {
+    let result = (|| {
+        if path.is_null() {
+            return Err(eyre!("path is null"));
+        }
+        format::load::<T>(&path_from_c(CStr::from_ptr(path))?)
+    })();
+    match result {
+        Ok(fixture) => Box::into_raw(Box::new(fixture)),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Converts a C string to a path.
+///
+/// Unix paths are arbitrary bytes and are taken as-is. Elsewhere the path must be UTF-8.
+fn path_from_c(path: &CStr) -> eyre::Result<PathBuf> {
+    #[cfg(unix)]
+    {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+        Ok(OsStr::from_bytes(path.to_bytes()).into())
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(path.to_str()?.into())
+    }
+}
+
+/// Serializes a value to a caller-owned JSON string.
+fn to_json<T: Serialize>(value: &T) -> *mut c_char {
+    let result = serde_json::to_string(value)
+        .map_err(eyre::Report::from)
+        .and_then(|json| Ok(CString::new(json)?));
+    match result {
+        Ok(json) => json.into_raw(),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the last error that occurred on the calling thread, or null if there was none.
+///
+/// The returned string is owned by the library and is valid until the next failing call
+/// on the same thread.
+#[no_mangle]
+pub extern "C" fn otv_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |msg| msg.as_ptr())
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn otv_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Loads a [DerivationFixture] from a JSON or YAML file, returning null on failure.
+///
+/// # Safety
+///
+/// `path` must be null or a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn otv_derivation_fixture_load(
+    path: *const c_char,
+) -> *mut DerivationFixture {
+    load(path)
+}
+
+/// Releases a [DerivationFixture].
+///
+/// # Safety
+///
+/// `fixture` must be null or a handle returned by [otv_derivation_fixture_load] that has
+/// not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn otv_derivation_fixture_free(fixture: *mut DerivationFixture) {
+    if !fixture.is_null() {
+        drop(Box::from_raw(fixture));
+    }
+}
+
+/// Validates the internal consistency of a [DerivationFixture].
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_derivation_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_derivation_fixture_validate(
+    fixture: *const DerivationFixture,
+) -> bool {
+    let fixture = &*fixture;
+    match fixture
+        .validate_blob_slots()
+        .and_then(|_| fixture.validate_forks())
+        .and_then(|_| fixture.validate_l2_block_infos())
+    {
+        Ok(()) => true,
+        Err(err) => {
+            set_last_error(err);
+            false
+        }
+    }
+}
+
+/// Returns the L2 safe head derivation starts from, which is not itself derived.
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_derivation_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_derivation_fixture_l2_cursor_start(
+    fixture: *const DerivationFixture,
+) -> u64 {
+    (*fixture).l2_cursor_start
+}
+
+/// Returns the inclusive L2 block number derivation ends at.
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_derivation_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_derivation_fixture_l2_cursor_end(
+    fixture: *const DerivationFixture,
+) -> u64 {
+    (*fixture).l2_cursor_end
+}
+
+/// Returns the number of L1 blocks in the fixture.
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_derivation_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_derivation_fixture_l1_block_count(
+    fixture: *const DerivationFixture,
+) -> usize {
+    (*fixture).l1_blocks.len()
+}
+
+/// Returns the L1 block at `index` as a JSON string, or null if out of bounds.
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_derivation_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_derivation_fixture_l1_block_json(
+    fixture: *const DerivationFixture,
+    index: usize,
+) -> *mut c_char {
+    let fixture = &*fixture;
+    match fixture.l1_blocks.get(index) {
+        Some(block) => to_json(block),
+        None => {
+            set_last_error(eyre!("L1 block index {index} out of bounds"));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the L2 payload attributes for `number` as a JSON string, or null if the
+/// fixture has none.
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_derivation_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_derivation_fixture_l2_payload_json(
+    fixture: *const DerivationFixture,
+    number: u64,
+) -> *mut c_char {
+    let fixture = &*fixture;
+    match fixture.l2_payloads.get(&number) {
+        Some(payload) => to_json(payload),
+        None => {
+            set_last_error(eyre!("No L2 payload for block {number}"));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Loads an [ExecutionFixture] from a JSON or YAML file, returning null on failure.
+///
+/// # Safety
+///
+/// `path` must be null or a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn otv_execution_fixture_load(path: *const c_char) -> *mut ExecutionFixture {
+    load(path)
+}
+
+/// Releases an [ExecutionFixture].
+///
+/// # Safety
+///
+/// `fixture` must be null or a handle returned by [otv_execution_fixture_load] that has
+/// not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn otv_execution_fixture_free(fixture: *mut ExecutionFixture) {
+    if !fixture.is_null() {
+        drop(Box::from_raw(fixture));
+    }
+}
+
+/// Returns the number of transactions in the fixture.
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_execution_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_execution_fixture_transaction_count(
+    fixture: *const ExecutionFixture,
+) -> usize {
+    (*fixture).transactions.len()
+}
+
+/// Returns the transaction at `index` as a JSON string, or null if out of bounds.
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_execution_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_execution_fixture_transaction_json(
+    fixture: *const ExecutionFixture,
+    index: usize,
+) -> *mut c_char {
+    let fixture = &*fixture;
+    match fixture.transactions.get(index) {
+        Some(tx) => to_json(tx),
+        None => {
+            set_last_error(eyre!("Transaction index {index} out of bounds"));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Returns the expected execution result as a JSON string.
+///
+/// # Safety
+///
+/// `fixture` must be a valid handle returned by [otv_execution_fixture_load].
+#[no_mangle]
+pub unsafe extern "C" fn otv_execution_fixture_result_json(
+    fixture: *const ExecutionFixture,
+) -> *mut c_char {
+    to_json(&(*fixture).result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::File, io::Write};
+
+    #[test]
+    fn test_load_derivation_fixture() {
+        let mut file = std::env::temp_dir();
+        file.push("otv_ffi_derivation_fixture.json");
+        File::create(&file)
+            .unwrap()
+            .write_all(include_bytes!("./testdata/derivation_fixture.json"))
+            .unwrap();
+        let path = CString::new(file.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let fixture = otv_derivation_fixture_load(path.as_ptr());
+            assert!(!fixture.is_null());
+            assert_eq!(otv_derivation_fixture_l2_cursor_start(fixture), 0);
+            assert_eq!(otv_derivation_fixture_l2_cursor_end(fixture), 2);
+            assert_eq!(otv_derivation_fixture_l1_block_count(fixture), 3);
+
+            let block = otv_derivation_fixture_l1_block_json(fixture, 0);
+            assert!(!block.is_null());
+            otv_string_free(block);
+
+            assert!(otv_derivation_fixture_l1_block_json(fixture, 3).is_null());
+            assert!(!otv_last_error().is_null());
+            otv_derivation_fixture_free(fixture);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_load_non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let mut file = std::env::temp_dir();
+        file.push(OsStr::from_bytes(b"otv_ffi_\xff_fixture.json"));
+        std::fs::write(&file, include_bytes!("./testdata/derivation_fixture.json")).unwrap();
+        let path = CString::new(file.as_os_str().as_bytes()).unwrap();
+
+        unsafe {
+            let fixture = otv_derivation_fixture_load(path.as_ptr());
+            assert!(!fixture.is_null());
+            otv_derivation_fixture_free(fixture);
+        }
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_load_missing_fixture() {
+        let path = CString::new("does/not/exist.json").unwrap();
+        unsafe {
+            assert!(otv_derivation_fixture_load(path.as_ptr()).is_null());
+            assert!(otv_derivation_fixture_load(ptr::null()).is_null());
+        }
+        assert!(!otv_last_error().is_null());
+    }
+}
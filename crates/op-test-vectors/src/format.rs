@@ -0,0 +1,219 @@
+//! Module containing fixture file formats.
+//!
+//! JSON is the canonical fixture format. YAML is accepted as an authoring format so small,
+//! hand-written fixtures can carry comments, and can be converted to canonical JSON.
+//!
+//! Fields that are not part of a fixture's schema are collected while parsing. In
+//! [LoadMode::Lenient] they are returned to the caller to report, which allows loading
+//! fixtures written by newer tooling. In [LoadMode::Strict] they are an error, which
+//! catches typos such as `l2_payloads` instead of `l2Payloads`.
+
+use color_eyre::eyre::{self, bail, eyre};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{fmt, path::Path, str::FromStr};
+
+pub use op_test_vectors_types::version::SchemaVersion;
+
+/// A fixture file format.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Format {
+    /// The canonical JSON format.
+    #[default]
+    Json,
+    /// YAML, for hand-authored fixtures.
+    Yaml,
+}
+
+/// The deprecation metadata every fixture type carries, which can be read from a fixture
+/// file without knowing its type.
+#[derive(Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Deprecation {
+    /// Whether the fixture is deprecated.
+    #[serde(default)]
+    pub deprecated: bool,
+    /// The path of the fixture that supersedes it, relative to the fixture.
+    #[serde(default)]
+    pub superseded_by: Option<String>,
+}
+
+/// How to treat fields in a fixture that are not part of its schema.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum LoadMode {
+    /// Unknown fields are returned to the caller but otherwise ignored.
+    #[default]
+    Lenient,
+    /// Unknown fields are an error.
+    Strict,
+}
+
+impl Format {
+    /// Detects the format from a path's extension, defaulting to [Format::Json].
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                Self::Yaml
+            }
+            _ => Self::Json,
+        }
+    }
+
+    /// Deserializes a value from a string in this format, ignoring unknown fields.
+    pub fn parse<T: DeserializeOwned>(&self, s: &str) -> eyre::Result<T> {
+        self.parse_with(s, LoadMode::Lenient)
+            .map(|(value, _)| value)
+    }
+
+    /// Deserializes a value from a string in this format, returning the paths of any
+    /// unknown fields alongside it.
+    ///
+    /// In [LoadMode::Strict], unknown fields are an error instead.
+    pub fn parse_with<T: DeserializeOwned>(
+        &self,
+        s: &str,
+        mode: LoadMode,
+    ) -> eyre::Result<(T, Vec<String>)> {
+        let mut unknown = Vec::new();
+        let on_unknown = |path: serde_ignored::Path<'_>| unknown.push(path.to_string());
+        let value = match self {
+            Self::Json => {
+                let mut de = serde_json::Deserializer::from_str(s);
+                let value =
+                    serde_ignored::deserialize(&mut de, on_unknown).map_err(|e| eyre!(e))?;
+                de.end().map_err(|e| eyre!(e))?;
+                value
+            }
+            Self::Yaml => {
+                let de = serde_yaml::Deserializer::from_str(s);
+                serde_ignored::deserialize(de, on_unknown).map_err(|e| eyre!(e))?
+            }
+        };
+        if mode == LoadMode::Strict && !unknown.is_empty() {
+            bail!("Unknown fields: {}", unknown.join(", "));
+        }
+        Ok((value, unknown))
+    }
+
+    /// Serializes a value to a string in this format. JSON output is pretty-printed.
+    pub fn serialize<T: Serialize>(&self, value: &T) -> eyre::Result<String> {
+        match self {
+            Self::Json => serde_json::to_string_pretty(value).map_err(|e| eyre!(e)),
+            Self::Yaml => serde_yaml::to_string(value).map_err(|e| eyre!(e)),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => bail!("Unknown fixture format: {s}, expected json or yaml"),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Yaml => write!(f, "yaml"),
+        }
+    }
+}
+
+/// Loads a fixture from a file, detecting the format from its extension and ignoring
+/// unknown fields.
+pub fn load<T: DeserializeOwned>(path: &Path) -> eyre::Result<T> {
+    load_with(path, LoadMode::Lenient).map(|(value, _)| value)
+}
+
+/// Loads a fixture from a file, detecting the format from its extension and returning
+/// the paths of any unknown fields alongside it.
+pub fn load_with<T: DeserializeOwned>(
+    path: &Path,
+    mode: LoadMode,
+) -> eyre::Result<(T, Vec<String>)> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| eyre!("Failed to read fixture {}: {e}", path.display()))?;
+    Format::from_path(path)
+        .parse_with(&contents, mode)
+        .map_err(|e| eyre!("Failed to parse fixture {}: {e}", path.display()))
+}
+
+/// Writes a fixture to a file in the given format.
+pub fn save<T: Serialize>(path: &Path, value: &T, format: Format) -> eyre::Result<()> {
+    std::fs::write(path, format.serialize(value)?)
+        .map_err(|e| eyre!("Failed to write fixture {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derivation::DerivationFixture;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path(Path::new("a/b.json")), Format::Json);
+        assert_eq!(Format::from_path(Path::new("a/b.yaml")), Format::Yaml);
+        assert_eq!(Format::from_path(Path::new("a/b.YML")), Format::Yaml);
+        assert_eq!(Format::from_path(Path::new("a/b")), Format::Json);
+        assert_eq!("yml".parse::<Format>().unwrap(), Format::Yaml);
+        assert!("toml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let (_, unknown): (DerivationFixture, _) = Format::Json
+            .parse_with(fixture_str, LoadMode::Strict)
+            .unwrap();
+        assert!(unknown.is_empty());
+
+        let typo = fixture_str.replacen(
+            "\"l2CursorEnd\": 2,",
+            "\"l2CursorEnd\": 2,\n  \"l2_cursor_end\": 2,",
+            1,
+        );
+        let (_, unknown): (DerivationFixture, _) =
+            Format::Json.parse_with(&typo, LoadMode::Lenient).unwrap();
+        assert_eq!(unknown, vec!["l2_cursor_end".to_string()]);
+        assert!(Format::Json
+            .parse_with::<DerivationFixture>(&typo, LoadMode::Strict)
+            .is_err());
+    }
+
+    #[test]
+    fn test_yaml_roundtrip() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let fixture: DerivationFixture = Format::Json.parse(fixture_str).unwrap();
+        let yaml = Format::Yaml.serialize(&fixture).unwrap();
+        let from_yaml: DerivationFixture = Format::Yaml.parse(&yaml).unwrap();
+        assert_eq!(fixture, from_yaml);
+    }
+
+    #[test]
+    fn test_deprecation() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let mut fixture: DerivationFixture = Format::Json.parse(fixture_str).unwrap();
+        let json = Format::Json.serialize(&fixture).unwrap();
+        assert!(!json.contains("deprecated"));
+        assert!(!json.contains("supersededBy"));
+        let deprecation: Deprecation = Format::Json.parse(&json).unwrap();
+        assert_eq!(deprecation, Deprecation::default());
+
+        fixture.deprecated = true;
+        fixture.superseded_by = Some("v2/channel.json".to_string());
+        let json = Format::Json.serialize(&fixture).unwrap();
+        let deprecation: Deprecation = Format::Json.parse(&json).unwrap();
+        assert!(deprecation.deprecated);
+        assert_eq!(
+            deprecation.superseded_by.as_deref(),
+            Some("v2/channel.json")
+        );
+        let parsed: DerivationFixture = Format::Json.parse(&json).unwrap();
+        assert_eq!(parsed, fixture);
+    }
+}
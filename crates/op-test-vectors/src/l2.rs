@@ -0,0 +1,287 @@
+//! Module containing the L2 types recorded in derivation and execution fixtures, and their
+//! conversions to and from the kona-derive types the pipeline produces.
+//!
+//! The types themselves live in `op-test-vectors-types`, which owns their serialized form
+//! without depending on kona-derive. Neither side of a conversion is defined here, so the
+//! conversions are the [IntoFixture] and [IntoKona] traits rather than `From` impls.
+
+use kona_derive::types as kona;
+
+pub use op_test_vectors_types::l2::*;
+
+/// Converts a kona-derive type into its fixture counterpart.
+pub trait IntoFixture {
+    /// The fixture counterpart.
+    type Fixture;
+
+    /// Converts into the fixture counterpart.
+    fn into_fixture(self) -> Self::Fixture;
+}
+
+/// Converts a fixture type into its kona-derive counterpart.
+pub trait IntoKona {
+    /// The kona-derive counterpart.
+    type Kona;
+
+    /// Converts into the kona-derive counterpart.
+    fn into_kona(self) -> Self::Kona;
+}
+
+impl IntoFixture for kona::L2PayloadAttributes {
+    type Fixture = PayloadAttributes;
+
+    fn into_fixture(self) -> PayloadAttributes {
+        PayloadAttributes {
+            timestamp: self.timestamp,
+            prev_randao: self.prev_randao,
+            fee_recipient: self.fee_recipient,
+            withdrawals: self.withdrawals,
+            parent_beacon_block_root: self.parent_beacon_block_root,
+            transactions: self.transactions.into_iter().map(|tx| tx.0).collect(),
+            no_tx_pool: self.no_tx_pool,
+            gas_limit: self.gas_limit,
+        }
+    }
+}
+
+impl IntoKona for PayloadAttributes {
+    type Kona = kona::L2PayloadAttributes;
+
+    fn into_kona(self) -> kona::L2PayloadAttributes {
+        kona::L2PayloadAttributes {
+            timestamp: self.timestamp,
+            prev_randao: self.prev_randao,
+            fee_recipient: self.fee_recipient,
+            withdrawals: self.withdrawals,
+            parent_beacon_block_root: self.parent_beacon_block_root,
+            transactions: self
+                .transactions
+                .into_iter()
+                .map(kona::RawTransaction)
+                .collect(),
+            no_tx_pool: self.no_tx_pool,
+            gas_limit: self.gas_limit,
+        }
+    }
+}
+
+/// The Isthmus operator fee parameters are not tracked by kona-derive yet, so they are
+/// absent when converting from its system config.
+impl IntoFixture for kona::SystemConfig {
+    type Fixture = SystemConfig;
+
+    fn into_fixture(self) -> SystemConfig {
+        SystemConfig {
+            batcher_address: self.batcher_address,
+            overhead: self.overhead,
+            scalar: self.scalar,
+            gas_limit: self.gas_limit,
+            base_fee_scalar: self.base_fee_scalar,
+            blob_base_fee_scalar: self.blob_base_fee_scalar,
+            operator_fee_scalar: None,
+            operator_fee_constant: None,
+        }
+    }
+}
+
+/// The Isthmus operator fee parameters are not tracked by kona-derive yet, so they are
+/// dropped when converting to its system config.
+impl IntoKona for SystemConfig {
+    type Kona = kona::SystemConfig;
+
+    fn into_kona(self) -> kona::SystemConfig {
+        kona::SystemConfig {
+            batcher_address: self.batcher_address,
+            overhead: self.overhead,
+            scalar: self.scalar,
+            gas_limit: self.gas_limit,
+            base_fee_scalar: self.base_fee_scalar,
+            blob_base_fee_scalar: self.blob_base_fee_scalar,
+        }
+    }
+}
+
+impl IntoFixture for kona::BlockID {
+    type Fixture = BlockId;
+
+    fn into_fixture(self) -> BlockId {
+        BlockId {
+            hash: self.hash,
+            number: self.number,
+        }
+    }
+}
+
+impl IntoKona for BlockId {
+    type Kona = kona::BlockID;
+
+    fn into_kona(self) -> kona::BlockID {
+        kona::BlockID {
+            hash: self.hash,
+            number: self.number,
+        }
+    }
+}
+
+impl IntoFixture for kona::BlockInfo {
+    type Fixture = BlockInfo;
+
+    fn into_fixture(self) -> BlockInfo {
+        BlockInfo {
+            hash: self.hash,
+            number: self.number,
+            parent_hash: self.parent_hash,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+impl IntoKona for BlockInfo {
+    type Kona = kona::BlockInfo;
+
+    fn into_kona(self) -> kona::BlockInfo {
+        kona::BlockInfo {
+            hash: self.hash,
+            number: self.number,
+            parent_hash: self.parent_hash,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+impl IntoFixture for kona::L2BlockInfo {
+    type Fixture = L2BlockInfo;
+
+    fn into_fixture(self) -> L2BlockInfo {
+        L2BlockInfo {
+            block_info: self.block_info.into_fixture(),
+            l1_origin: self.l1_origin.into_fixture(),
+            seq_num: self.seq_num,
+        }
+    }
+}
+
+impl IntoKona for L2BlockInfo {
+    type Kona = kona::L2BlockInfo;
+
+    fn into_kona(self) -> kona::L2BlockInfo {
+        kona::L2BlockInfo {
+            block_info: self.block_info.into_kona(),
+            l1_origin: self.l1_origin.into_kona(),
+            seq_num: self.seq_num,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256, bytes, uint};
+    use hashbrown::HashMap;
+    use serde::{de::DeserializeOwned, Serialize};
+    use serde_json::Value;
+
+    /// Asserts that `value` serializes identically to its kona-derive counterpart, and
+    /// that both conversions preserve it.
+    fn assert_matches_kona<T>(value: T)
+    where
+        T: Serialize + Clone + PartialEq + std::fmt::Debug + IntoKona,
+        T::Kona: Serialize + DeserializeOwned + IntoFixture<Fixture = T>,
+    {
+        let kona = value.clone().into_kona();
+        assert_eq!(
+            serde_json::to_value(&value).unwrap(),
+            serde_json::to_value(&kona).unwrap()
+        );
+        let json = serde_json::to_string(&value).unwrap();
+        let parsed: T::Kona = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.into_fixture(), value);
+    }
+
+    #[test]
+    fn test_payload_attributes_match_kona() {
+        assert_matches_kona(PayloadAttributes {
+            timestamp: 1722550777,
+            prev_randao: b256!("73ce62c38a0714e87a4141f33ec2362dc800d7693d85e42ffe6bdc22a5c84610"),
+            fee_recipient: address!("4200000000000000000000000000000000000011"),
+            withdrawals: Some(vec![]),
+            parent_beacon_block_root: Some(b256!(
+                "8693a4b644bc68b8562194814d2945e4a78e2b20967c0a5c2f5f8e741be5a379"
+            )),
+            transactions: vec![bytes!("7e01"), bytes!("02c0")],
+            no_tx_pool: true,
+            gas_limit: Some(30000000),
+        });
+        assert_matches_kona(PayloadAttributes::default());
+    }
+
+    #[test]
+    fn test_system_config_matches_kona() {
+        assert_matches_kona(SystemConfig {
+            batcher_address: address!("3333333333333333333333333333333333333333"),
+            overhead: uint!(8_U256),
+            scalar: uint!(7_U256),
+            gas_limit: 30000000,
+            base_fee_scalar: Some(1368),
+            blob_base_fee_scalar: None,
+            operator_fee_scalar: None,
+            operator_fee_constant: None,
+        });
+    }
+
+    #[test]
+    fn test_l2_block_info_matches_kona() {
+        assert_matches_kona(L2BlockInfo {
+            block_info: BlockInfo {
+                hash: b256!("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+                number: 2,
+                parent_hash: b256!(
+                    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+                ),
+                timestamp: 104,
+            },
+            l1_origin: BlockId {
+                hash: b256!("cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc"),
+                number: 1,
+            },
+            seq_num: 1,
+        });
+    }
+
+    #[test]
+    fn test_fixture_sections_match_kona() {
+        let fixture: Value =
+            serde_json::from_str(include_str!("./testdata/derivation_fixture.json")).unwrap();
+        let section = |name: &str| serde_json::to_string(&fixture[name]).unwrap();
+
+        let ours: HashMap<u64, PayloadAttributes> =
+            serde_json::from_str(&section("l2Payloads")).unwrap();
+        let theirs: HashMap<u64, kona::L2PayloadAttributes> =
+            serde_json::from_str(&section("l2Payloads")).unwrap();
+        let converted: HashMap<u64, PayloadAttributes> = theirs
+            .into_iter()
+            .map(|(n, p)| (n, p.into_fixture()))
+            .collect();
+        assert_eq!(ours, converted);
+
+        let ours: HashMap<u64, SystemConfig> =
+            serde_json::from_str(&section("l2SystemConfigs")).unwrap();
+        let theirs: HashMap<u64, kona::SystemConfig> =
+            serde_json::from_str(&section("l2SystemConfigs")).unwrap();
+        let converted: HashMap<u64, SystemConfig> = theirs
+            .into_iter()
+            .map(|(n, c)| (n, c.into_fixture()))
+            .collect();
+        assert_eq!(ours, converted);
+
+        let ours: HashMap<u64, L2BlockInfo> =
+            serde_json::from_str(&section("l2BlockInfos")).unwrap();
+        let theirs: HashMap<u64, kona::L2BlockInfo> =
+            serde_json::from_str(&section("l2BlockInfos")).unwrap();
+        let converted: HashMap<u64, L2BlockInfo> = theirs
+            .into_iter()
+            .map(|(n, i)| (n, i.into_fixture()))
+            .collect();
+        assert_eq!(ours, converted);
+    }
+}
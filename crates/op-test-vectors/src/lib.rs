@@ -12,6 +12,25 @@
 // and the crate is pinned to a specific version.
 pub use kona_derive;
 
-pub mod derivation;
+// Re-export the schema types so they keep their paths in this crate.
+pub use op_test_vectors_types::{
+    anchor_state, building, cannon, derivation, hint, preimage, quantity, version,
+};
+
+pub mod blobs;
 
 pub mod execution;
+
+pub mod format;
+
+pub mod l2;
+
+pub mod patch;
+
+pub mod secrets;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "python")]
+pub mod python;
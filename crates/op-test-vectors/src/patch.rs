@@ -0,0 +1,157 @@
+//! Module containing field overrides for deriving fixture variants.
+//!
+//! A [Patch] sets the value at a path in a fixture's JSON representation, e.g.
+//! `rollupConfig.fjord_time=123`. Paths are either dot-separated, or JSON pointers when
+//! they start with `/`. Array elements are addressed by index. Values are parsed as JSON
+//! where possible and used as strings otherwise, so `0x`-prefixed hex needs no quoting.
+//!
+//! Patched fixtures are re-parsed strictly, so a misspelled path is an error rather
+//! than a silently ignored field.
+
+use crate::format::{Format, LoadMode};
+use color_eyre::eyre::{self, bail, ensure, eyre};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// A single field override.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Patch {
+    /// The path segments to the field.
+    pub path: Vec<String>,
+    /// The value to set.
+    pub value: Value,
+}
+
+impl FromStr for Patch {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, raw) = s
+            .split_once('=')
+            .ok_or_else(|| eyre!("Invalid patch {s:?}, expected <path>=<value>"))?;
+        let path = match path.strip_prefix('/') {
+            Some(pointer) => pointer
+                .split('/')
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                .collect::<Vec<_>>(),
+            None => path.split('.').map(str::to_string).collect(),
+        };
+        ensure!(
+            path.iter().all(|segment| !segment.is_empty()),
+            "Invalid patch path in {s:?}"
+        );
+        let value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+        Ok(Self { path, value })
+    }
+}
+
+impl Patch {
+    /// Sets the value in `root`, failing if an intermediate segment does not exist.
+    ///
+    /// The final segment may add a new key to an object, e.g. an optional fork time
+    /// that was not serialized.
+    pub fn apply(&self, root: &mut Value) -> eyre::Result<()> {
+        let (last, parents) = self
+            .path
+            .split_last()
+            .ok_or_else(|| eyre!("Empty patch path"))?;
+        let mut target = root;
+        for segment in parents {
+            target = child(target, segment)
+                .ok_or_else(|| eyre!("Path {} not found at {segment:?}", self.path()))?;
+        }
+        match target {
+            Value::Object(map) => {
+                map.insert(last.clone(), self.value.clone());
+            }
+            Value::Array(items) => {
+                let slot = last
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| items.get_mut(i))
+                    .ok_or_else(|| eyre!("Path {} not found at {last:?}", self.path()))?;
+                *slot = self.value.clone();
+            }
+            _ => bail!(
+                "Path {} does not point into an object or array",
+                self.path()
+            ),
+        }
+        Ok(())
+    }
+
+    /// Returns the path in dot-separated form.
+    pub fn path(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+/// Returns the child of an object or array for a path segment.
+fn child<'a>(value: &'a mut Value, segment: &str) -> Option<&'a mut Value> {
+    match value {
+        Value::Object(map) => map.get_mut(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get_mut(i)),
+        _ => None,
+    }
+}
+
+/// Applies patches to a fixture, returning the re-parsed, canonical result.
+pub fn apply<T: Serialize + DeserializeOwned>(fixture: &T, patches: &[Patch]) -> eyre::Result<T> {
+    let mut value = serde_json::to_value(fixture)?;
+    for patch in patches {
+        patch.apply(&mut value)?;
+    }
+    let (patched, _) = Format::Json.parse_with(&value.to_string(), LoadMode::Strict)?;
+    Ok(patched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derivation::DerivationFixture;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_patch() {
+        let patch: Patch = "rollupConfig.block_time=4".parse().unwrap();
+        assert_eq!(patch.path, vec!["rollupConfig", "block_time"]);
+        assert_eq!(patch.value, json!(4));
+
+        let patch: Patch = "/l1Blocks/0/header/extraData=0xdead".parse().unwrap();
+        assert_eq!(patch.path, vec!["l1Blocks", "0", "header", "extraData"]);
+        assert_eq!(patch.value, json!("0xdead"));
+
+        let patch: Patch = "/a~1b=\"x=y\"".parse().unwrap();
+        assert_eq!(patch.path, vec!["a/b"]);
+        assert_eq!(patch.value, json!("x=y"));
+
+        assert!("rollupConfig".parse::<Patch>().is_err());
+        assert!("rollupConfig..block_time=1".parse::<Patch>().is_err());
+    }
+
+    #[test]
+    fn test_apply_patches() {
+        let fixture_str = include_str!("./testdata/derivation_fixture.json");
+        let fixture: DerivationFixture = serde_json::from_str(fixture_str).unwrap();
+        let patches = [
+            "l2CursorEnd=0x3".parse().unwrap(),
+            "rollupConfig.block_time=4".parse().unwrap(),
+            "description=wrong block time".parse().unwrap(),
+            "l1Blocks.0.header.timestamp=0x1".parse().unwrap(),
+        ];
+        let patched = apply(&fixture, &patches).unwrap();
+        assert_eq!(patched.l2_cursor_end, 3);
+        assert_eq!(patched.rollup_config.block_time, 4);
+        assert_eq!(patched.description.as_deref(), Some("wrong block time"));
+        assert_eq!(patched.l1_blocks[0].header.timestamp, 1);
+        assert_eq!(patched.l1_blocks[1], fixture.l1_blocks[1]);
+
+        let typo = ["l2_cursor_end=2".parse().unwrap()];
+        assert!(apply(&fixture, &typo).is_err());
+        let missing = ["rollupConfig.missing.block_time=4".parse().unwrap()];
+        assert!(apply(&fixture, &missing).is_err());
+        let out_of_bounds = ["l1Blocks.9.header.timestamp=1".parse().unwrap()];
+        assert!(apply(&fixture, &out_of_bounds).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Python bindings for authoring and inspecting fixtures.
+//!
+//! Fixtures are exposed as Python classes that wrap the Rust types, so fields are
+//! validated by the same (de)serialization logic the Rust consumers use. Nested values
+//! such as blocks and payloads are exchanged as JSON strings, which can be loaded with
+//! Python's `json` module.
+//!
+//! Build the extension module with [maturin](https://www.maturin.rs):
+//!
+//! ```sh
+//! maturin develop -m crates/op-test-vectors/Cargo.toml --features python,pyo3/extension-module
+//! ```
+
+// The `#[pymethods]` expansion converts `PyResult` errors into `PyErr`, which clippy flags.
+#![allow(clippy::useless_conversion)]
+
+use crate::{
+    derivation::{BeaconConfig, DerivationFixture, FixtureBlock},
+    execution::ExecutionFixture,
+    format,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+/// Converts any displayable error into a Python `ValueError`.
+fn value_error(err: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(format!("{err:#}"))
+}
+
+/// Deserializes a value from a JSON string.
+fn from_json<T: DeserializeOwned>(json: &str) -> PyResult<T> {
+    serde_json::from_str(json).map_err(value_error)
+}
+
+/// Serializes a value to a JSON string.
+fn to_json<T: Serialize>(value: &T, indent: bool) -> PyResult<String> {
+    let json = if indent {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    json.map_err(value_error)
+}
+
+/// Python wrapper around a [DerivationFixture].
+#[pyclass(name = "DerivationFixture", module = "op_test_vectors")]
+#[derive(Clone, Debug, Default)]
+pub struct PyDerivationFixture(pub DerivationFixture);
+
+#[pymethods]
+impl PyDerivationFixture {
+    /// Creates an empty fixture covering the given L2 block range.
+    #[new]
+    #[pyo3(signature = (l2_cursor_start = 0, l2_cursor_end = 0))]
+    fn new(l2_cursor_start: u64, l2_cursor_end: u64) -> Self {
+        Self(DerivationFixture {
+            l2_cursor_start,
+            l2_cursor_end,
+            ..Default::default()
+        })
+    }
+
+    /// Parses a fixture from a JSON string.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        from_json(json).map(Self)
+    }
+
+    /// Loads a fixture from a JSON or YAML file.
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        format::load(Path::new(path)).map(Self).map_err(value_error)
+    }
+
+    /// Serializes the fixture to a JSON string.
+    #[pyo3(signature = (indent = false))]
+    fn to_json(&self, indent: bool) -> PyResult<String> {
+        to_json(&self.0, indent)
+    }
+
+    /// Writes the fixture to a JSON file.
+    fn save(&self, path: &str) -> PyResult<()> {
+        std::fs::write(path, to_json(&self.0, true)?)?;
+        Ok(())
+    }
+
+    /// Validates the internal consistency of the fixture, raising `ValueError` on failure.
+    fn validate(&self) -> PyResult<()> {
+        self.0
+            .validate_blob_slots()
+            .and_then(|_| self.0.validate_forks())
+            .and_then(|_| self.0.validate_l2_block_infos())
+            .map_err(value_error)
+    }
+
+    #[getter]
+    fn get_description(&self) -> Option<String> {
+        self.0.description.clone()
+    }
+
+    #[setter]
+    fn set_description(&mut self, value: Option<String>) {
+        self.0.description = value;
+    }
+
+    #[getter]
+    fn get_l2_cursor_start(&self) -> u64 {
+        self.0.l2_cursor_start
+    }
+
+    #[setter]
+    fn set_l2_cursor_start(&mut self, value: u64) {
+        self.0.l2_cursor_start = value;
+    }
+
+    #[getter]
+    fn get_l2_cursor_end(&self) -> u64 {
+        self.0.l2_cursor_end
+    }
+
+    #[setter]
+    fn set_l2_cursor_end(&mut self, value: u64) {
+        self.0.l2_cursor_end = value;
+    }
+
+    /// The rollup config as a JSON string.
+    #[getter]
+    fn get_rollup_config(&self) -> PyResult<String> {
+        to_json(&self.0.rollup_config, false)
+    }
+
+    #[setter]
+    fn set_rollup_config(&mut self, json: &str) -> PyResult<()> {
+        self.0.rollup_config = from_json(json)?;
+        Ok(())
+    }
+
+    /// Sets the L1 beacon genesis time and slot duration used to derive blob slots.
+    fn set_beacon_config(&mut self, genesis_time: u64, seconds_per_slot: u64) {
+        self.0.l1_beacon_config = Some(BeaconConfig {
+            genesis_time,
+            seconds_per_slot,
+        });
+    }
+
+    /// The number of L1 blocks in the fixture.
+    fn l1_block_count(&self) -> usize {
+        self.0.l1_blocks.len()
+    }
+
+    /// Returns the L1 block at `index` as a JSON string.
+    fn l1_block(&self, index: usize) -> PyResult<String> {
+        let block = self
+            .0
+            .l1_blocks
+            .get(index)
+            .ok_or_else(|| value_error(format!("L1 block index {index} out of bounds")))?;
+        to_json(block, false)
+    }
+
+    /// Appends an L1 block, given as a JSON string.
+    fn push_l1_block(&mut self, json: &str) -> PyResult<()> {
+        let block: FixtureBlock = from_json(json)?;
+        self.0.l1_blocks.push(block);
+        Ok(())
+    }
+
+    /// The L2 block numbers that have expected payload attributes, in ascending order.
+    fn l2_payload_numbers(&self) -> Vec<u64> {
+        let mut numbers: Vec<u64> = self.0.l2_payloads.keys().copied().collect();
+        numbers.sort_unstable();
+        numbers
+    }
+
+    /// Returns the expected payload attributes for L2 block `number` as a JSON string.
+    fn l2_payload(&self, number: u64) -> PyResult<String> {
+        let payload = self
+            .0
+            .l2_payloads
+            .get(&number)
+            .ok_or_else(|| value_error(format!("No L2 payload for block {number}")))?;
+        to_json(payload, false)
+    }
+
+    /// Sets the expected payload attributes for L2 block `number`, given as a JSON string.
+    fn set_l2_payload(&mut self, number: u64, json: &str) -> PyResult<()> {
+        self.0.l2_payloads.insert(number, from_json(json)?);
+        Ok(())
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DerivationFixture(l2_cursor_start={}, l2_cursor_end={}, l1_blocks={})",
+            self.0.l2_cursor_start,
+            self.0.l2_cursor_end,
+            self.0.l1_blocks.len()
+        )
+    }
+}
+
+/// Python wrapper around an [ExecutionFixture].
+#[pyclass(name = "ExecutionFixture", module = "op_test_vectors")]
+#[derive(Debug, Default)]
+pub struct PyExecutionFixture(pub ExecutionFixture);
+
+#[pymethods]
+impl PyExecutionFixture {
+    /// Creates an empty fixture.
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a fixture from a JSON string.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        from_json(json).map(Self)
+    }
+
+    /// Loads a fixture from a JSON or YAML file.
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        format::load(Path::new(path)).map(Self).map_err(value_error)
+    }
+
+    /// Serializes the fixture to a JSON string.
+    #[pyo3(signature = (indent = false))]
+    fn to_json(&self, indent: bool) -> PyResult<String> {
+        to_json(&self.0, indent)
+    }
+
+    /// Writes the fixture to a JSON file.
+    fn save(&self, path: &str) -> PyResult<()> {
+        std::fs::write(path, to_json(&self.0, true)?)?;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_description(&self) -> Option<String> {
+        self.0.description.clone()
+    }
+
+    #[setter]
+    fn set_description(&mut self, value: Option<String>) {
+        self.0.description = value;
+    }
+
+    /// The execution environment as a JSON string.
+    #[getter]
+    fn get_env(&self) -> PyResult<String> {
+        to_json(&self.0.env, false)
+    }
+
+    #[setter]
+    fn set_env(&mut self, json: &str) -> PyResult<()> {
+        self.0.env = from_json(json)?;
+        Ok(())
+    }
+
+    /// The number of transactions in the fixture.
+    fn transaction_count(&self) -> usize {
+        self.0.transactions.len()
+    }
+
+    /// Returns the transaction at `index` as a JSON string.
+    fn transaction(&self, index: usize) -> PyResult<String> {
+        let tx = self
+            .0
+            .transactions
+            .get(index)
+            .ok_or_else(|| value_error(format!("Transaction index {index} out of bounds")))?;
+        to_json(tx, false)
+    }
+
+    /// Appends a transaction, given as a JSON string.
+    fn push_transaction(&mut self, json: &str) -> PyResult<()> {
+        self.0.transactions.push(from_json(json)?);
+        Ok(())
+    }
+
+    /// The expected execution result as a JSON string.
+    #[getter]
+    fn get_result(&self) -> PyResult<String> {
+        to_json(&self.0.result, false)
+    }
+
+    #[setter]
+    fn set_result(&mut self, json: &str) -> PyResult<()> {
+        self.0.result = from_json(json)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ExecutionFixture(transactions={})",
+            self.0.transactions.len()
+        )
+    }
+}
+
+/// The `op_test_vectors` Python module.
+#[pymodule]
+fn op_test_vectors(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDerivationFixture>()?;
+    m.add_class::<PyExecutionFixture>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivation_fixture_roundtrip() {
+        let json = include_str!("./testdata/derivation_fixture.json");
+        let fixture = PyDerivationFixture::from_json(json).unwrap();
+        assert_eq!(fixture.get_l2_cursor_start(), 0);
+        assert_eq!(fixture.get_l2_cursor_end(), 2);
+        assert_eq!(fixture.l1_block_count(), 3);
+        assert!(fixture.validate().is_ok());
+
+        let mut copy = PyDerivationFixture::new(0, 0);
+        copy.set_l2_cursor_start(0);
+        copy.set_l2_cursor_end(2);
+        copy.set_rollup_config(&fixture.get_rollup_config().unwrap())
+            .unwrap();
+        for i in 0..fixture.l1_block_count() {
+            copy.push_l1_block(&fixture.l1_block(i).unwrap()).unwrap();
+        }
+        for number in fixture.l2_payload_numbers() {
+            copy.set_l2_payload(number, &fixture.l2_payload(number).unwrap())
+                .unwrap();
+        }
+        copy.0.l2_system_configs = fixture.0.l2_system_configs.clone();
+        copy.0.l2_block_infos = fixture.0.l2_block_infos.clone();
+        copy.0.ref_payloads = fixture.0.ref_payloads.clone();
+        copy.0.version = fixture.0.version;
+        assert!(copy.__eq__(&fixture));
+        assert!(fixture.l1_block(3).is_err());
+    }
+}